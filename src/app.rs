@@ -1,4 +1,5 @@
-use crate::{model, view};
+use crate::view;
+use repo_save_manager::model;
 // App for egui integration
 pub struct RSMApp {
     view: view::AppView,
@@ -7,18 +8,19 @@ pub struct RSMApp {
 impl RSMApp {
     pub fn new(cc: &eframe::CreationContext<'_>, view: view::AppView) -> Self {
         // Load previous app state if persistence is enabled
-        if let Some(storage) = cc.storage {
-            if let Some(state) = eframe::get_value::<model::AppState>(storage, eframe::APP_KEY) {
-                if let Ok(mut current_state) = view.state.lock() {
-                    // Only restore persistent fields
-                    current_state.save_directory = state.save_directory;
-                    current_state.backup_directory = state.backup_directory;
-
-                    // Refresh save bundles based on loaded directories
-                    current_state.refresh_save_bundles();
-                }
-            }
+        if let Some(storage) = cc.storage
+            && let Some(state) = eframe::get_value::<model::AppState>(storage, eframe::APP_KEY)
+            && let Ok(mut current_state) = view.state.lock()
+        {
+            // Only restore persistent fields
+            current_state.restore_settings(state);
+        }
+        // Help choosing the directories on the first launch
+        if !view.state.lock().unwrap().setup_done {
+            view.start_setup();
         }
+        // Load the save bundles of the directories in the background
+        view.request_refresh();
 
         Self { view }
     }
@@ -0,0 +1,118 @@
+//! Commands working on arbitrary Easy Save 3 files, not only save bundles.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use thiserror::Error;
+
+use super::output::{EXIT_CODEC_FAILED, Failure, Output};
+use repo_save_manager::constant;
+use repo_save_manager::repo::crypt::{self, DecryptError, EncryptError, Es3Info};
+
+#[derive(Debug, Error)]
+pub enum CodecError {
+    #[error("Failed to read `{0}`: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("Failed to write `{0}`: {1}")]
+    Write(PathBuf, std::io::Error),
+    #[error("Failed to decode `{0}`: {1}")]
+    Decode(PathBuf, DecryptError),
+    #[error("Failed to encode `{0}`: {1}")]
+    Encode(PathBuf, EncryptError),
+    #[error("`{0}` is not valid JSON: {1}")]
+    InvalidJson(PathBuf, serde_json::Error),
+}
+
+impl From<CodecError> for Failure {
+    fn from(err: CodecError) -> Self {
+        Failure {
+            kind: "codec_failed",
+            message: err.to_string(),
+            exit_code: EXIT_CODEC_FAILED,
+        }
+    }
+}
+
+/// Decrypt `file` and write the JSON to `output`, or stdout if None.
+pub fn decrypt(
+    out: &Output,
+    file: &Path,
+    output: Option<&Path>,
+    password: Option<&str>,
+) -> ExitCode {
+    let (content, info) = match decode(file, password) {
+        Ok(decoded) => decoded,
+        Err(e) => return out.report("decrypt", Err(e.into()), ""),
+    };
+    if !info.valid_json {
+        log::warn!("Decrypted content of {:?} is not valid JSON.", file);
+    }
+
+    match output {
+        Some(output) => {
+            let result = std::fs::write(output, &content)
+                .map_err(|e| CodecError::Write(output.to_path_buf(), e).into());
+            out.report(
+                "decrypt",
+                result,
+                &format!("Decrypted to {}", output.display()),
+            )
+        }
+        None => {
+            // The decrypted JSON is the output, regardless of the output format.
+            let mut stdout = std::io::stdout();
+            match stdout.write_all(&content).and_then(|_| stdout.flush()) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    ExitCode::from(EXIT_CODEC_FAILED)
+                }
+            }
+        }
+    }
+}
+
+/// Encrypt the JSON file `json` to the es3 file `output`.
+pub fn encrypt(
+    out: &Output,
+    json: &Path,
+    output: &Path,
+    password: Option<&str>,
+    gzip: bool,
+) -> ExitCode {
+    let result = (|| {
+        let content = std::fs::read(json).map_err(|e| CodecError::Read(json.to_path_buf(), e))?;
+        serde_json::from_slice::<serde::de::IgnoredAny>(&content)
+            .map_err(|e| CodecError::InvalidJson(json.to_path_buf(), e))?;
+        crypt::encrypt_es3(
+            output,
+            &content,
+            password.unwrap_or(constant::ENCRYPTION_PASS),
+            gzip,
+        )
+        .map_err(|e| CodecError::Encode(output.to_path_buf(), e))
+    })();
+    out.report(
+        "encrypt",
+        result.map_err(Failure::from),
+        &format!("Encrypted to {}", output.display()),
+    )
+}
+
+/// Print how `file` is stored.
+pub fn inspect(out: &Output, file: &Path, password: Option<&str>) -> ExitCode {
+    match decode(file, password) {
+        Ok((_, info)) => out.inspection(file, &info),
+        Err(e) => out.report("inspect", Err(e.into()), ""),
+    }
+}
+
+/// Decode an es3 file with the given password, or the known password profiles if None.
+fn decode(file: &Path, password: Option<&str>) -> Result<(Vec<u8>, Es3Info), CodecError> {
+    let data = std::fs::read(file).map_err(|e| CodecError::Read(file.to_path_buf(), e))?;
+    let result = match password {
+        Some(password) => crypt::decode_es3(&data, &[("custom", password)]),
+        None => crypt::decode_es3(&data, &constant::PASSWORD_PROFILES),
+    };
+    result.map_err(|e| CodecError::Decode(file.to_path_buf(), e))
+}
@@ -0,0 +1,619 @@
+//! Headless command line interface.
+//!
+//! Commands run the same controller operations as the GUI, on the directories
+//! configured in the GUI for the active save source, unless another source is selected
+//! with `--source` or the directories are overridden with `--save-dir`/`--backup-dir`.
+
+mod codec;
+mod output;
+
+use clap::{Parser, Subcommand};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+
+use output::Output;
+use repo_save_manager::constant;
+use repo_save_manager::controller::{AppController, ControllerError, ControllerResult};
+use repo_save_manager::jobs::JobProgress;
+use repo_save_manager::model::{AppState, SaveLists};
+use repo_save_manager::repo::save::SaveGame;
+use repo_save_manager::rsm::edit::{self, EditError, ItemEdit, SaveEdits};
+use repo_save_manager::rsm::index::MetadataIndex;
+use repo_save_manager::rsm::patch::SavePatch;
+use repo_save_manager::rsm::save_bundle::{self, BundleId, SaveBundle};
+use repo_save_manager::rsm::template::NewSaveOptions;
+
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about,
+    after_help = "Commands changing a game save keep a copy of the original in the \
+        `.snapshots` directory of the backup directory first."
+)]
+pub struct Cli {
+    /// Save source configured in the GUI to work on (defaults to the active one)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub source: Option<String>,
+
+    /// Game save directory (defaults to the one configured in the GUI)
+    #[arg(long, global = true, value_name = "DIR")]
+    pub save_dir: Option<String>,
+
+    /// Backup directory (defaults to the one configured in the GUI)
+    #[arg(long, global = true, value_name = "DIR")]
+    pub backup_dir: Option<String>,
+
+    /// Print results as JSON
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Command to run without a GUI, the GUI is launched if omitted
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// List game saves and backups
+    List,
+    /// Backup a game save
+    Backup {
+        /// Name of the game save, eg. REPO_SAVE_2025_04_12_15_39_47, or its ID
+        name: String,
+    },
+    /// Backup all game saves
+    BackupAll,
+    /// Restore a backup to the game save directory
+    Restore {
+        /// Name or ID of the backup
+        name: String,
+        /// Overwrite the game save if it already exists
+        #[arg(long)]
+        force: bool,
+        /// Save source to restore to (defaults to the one the backups are listed from)
+        #[arg(long, value_name = "SOURCE")]
+        to: Option<String>,
+    },
+    /// Copy a game save or backup to the game save directory as a new save, named REPO_SAVE_<now>
+    Clone {
+        /// Name of the game save or its ID, or the ID of a backup
+        name: String,
+        /// Clone the backup with this name rather than the game save
+        #[arg(long)]
+        backup: bool,
+        /// Team name of the new save, defaults to the one of the save cloned
+        #[arg(long, value_name = "NAME")]
+        team: Option<String>,
+    },
+    /// Show what changes from a game save or backup to another: run stats, upgrades of the
+    /// players, items and time played, eg. before restoring a backup over a game save
+    Compare {
+        /// Name of the game save to compare from or its ID, or the ID of a backup
+        from: String,
+        /// Name of the game save to compare to or its ID, or the ID of a backup
+        to: String,
+        /// Compare from the backup with this name rather than the game save
+        #[arg(long)]
+        from_backup: bool,
+        /// Compare to the backup with this name rather than the game save
+        #[arg(long)]
+        to_backup: bool,
+    },
+    /// List the save templates
+    Templates,
+    /// Keep a game save or backup as a template, to create new saves from it
+    CaptureTemplate {
+        /// Name of the game save or its ID, or the ID of a backup
+        name: String,
+        /// Name of the template
+        template: String,
+        /// Capture the backup with this name rather than the game save
+        #[arg(long)]
+        backup: bool,
+        /// Remove the players, they are added to the new saves as they join
+        #[arg(long)]
+        strip_players: bool,
+    },
+    /// Delete a save template
+    DeleteTemplate {
+        /// Name of the template
+        template: String,
+    },
+    /// Create a new save in the game save directory from a template, named REPO_SAVE_<now>
+    NewSave {
+        /// Name of the template
+        template: String,
+        /// Team name of the new save, defaults to the one of the template
+        #[arg(long, value_name = "NAME")]
+        team: Option<String>,
+        /// Set the upgrades of the players of the template to 0
+        #[arg(long)]
+        reset_upgrades: bool,
+    },
+    /// Delete a backup
+    Delete {
+        /// Name or ID of the backup
+        name: String,
+    },
+    /// Show or edit the run stats of a game save (level, currency, lives, etc.)
+    Stats {
+        /// Name of the game save or its ID
+        name: String,
+        /// Set a run stat, eg. `--set currency=100`, can be repeated
+        #[arg(long = "set", value_name = "STAT=VALUE", value_parser = parse_assignment)]
+        set: Vec<(String, i32)>,
+    },
+    /// Show or edit the upgrades and crown of the players of a game save
+    Players {
+        /// Name of the game save or its ID
+        name: String,
+        /// Player to edit, by name or Steam ID
+        #[arg(long, requires = "set")]
+        player: Option<String>,
+        /// Remove a player from the save and every per-player dictionary, by name or Steam ID,
+        /// can be repeated
+        #[arg(long, value_name = "PLAYER", conflicts_with = "player")]
+        remove: Vec<String>,
+        /// Set an upgrade of the player, eg. `--set health=3` or `--set crown=1`, can be repeated
+        #[arg(
+            long = "set",
+            value_name = "UPGRADE=VALUE",
+            value_parser = parse_assignment,
+            requires = "player"
+        )]
+        set: Vec<(String, i32)>,
+    },
+    /// Apply a JSON Patch (RFC 6902) or JSON merge patch (RFC 7396) file to the JSON of a game save
+    Patch {
+        /// Name of the game save or its ID
+        name: String,
+        /// The patch, an array of operations for a JSON Patch or an object for a merge patch
+        file: PathBuf,
+        /// Show the changes the patch makes without writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Copy the upgrades of a player of a game save or backup to a player of a game save
+    Transplant {
+        /// Name of the game save or backup to copy from, or its ID
+        from: String,
+        /// Name of the game save to copy to, or its ID
+        to: String,
+        /// Player to copy, by name or Steam ID
+        #[arg(long)]
+        player: String,
+        /// Player to copy to, by name or Steam ID, defaults to the same player.
+        /// Players not in the save are added to it
+        #[arg(long = "as", value_name = "PLAYER")]
+        target_player: Option<String>,
+    },
+    /// Show or edit the items of a game save, with their battery
+    Items {
+        /// Name of the game save or its ID
+        name: String,
+        /// Add an item, fully charged, eg. `--add "Item Grenade Explosive"`, can be repeated
+        #[arg(long, value_name = "ITEM")]
+        add: Vec<String>,
+        /// Remove an instance of an item, eg. `--remove "Item Grenade Explosive/2"`, can be repeated
+        #[arg(long, value_name = "INSTANCE")]
+        remove: Vec<String>,
+        /// Recharge the battery of an instance of an item, can be repeated
+        #[arg(long, value_name = "INSTANCE")]
+        recharge: Vec<String>,
+    },
+    /// Rename the team of a game save, or give it a new name
+    Rename {
+        /// Name of the game save or its ID
+        name: String,
+        /// The new team name
+        #[arg(long, value_name = "NAME", required_unless_present = "new_identity")]
+        team: Option<String>,
+        /// Rename the save folder and file to `REPO_SAVE_<now>`, so that it shows up as a new save
+        #[arg(long)]
+        new_identity: bool,
+    },
+    /// Check that all game saves and backups can be read
+    Verify,
+    /// Look for suspicious values in the game saves and backups, eg. negative run stats,
+    /// upgrades out of range or entries of players who aren't in the save
+    Check {
+        /// Fix the issues of the game saves that can be fixed safely
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Read every game save and backup again, replacing the cached metadata
+    RebuildIndex,
+    /// Decrypt any Easy Save 3 file to JSON
+    Decrypt {
+        /// The .es3 file
+        file: PathBuf,
+        /// Write the JSON to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Password, the known password profiles are tried if omitted
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Encrypt a JSON file to an Easy Save 3 file
+    Encrypt {
+        /// The JSON file
+        #[arg(value_name = "JSON")]
+        input: PathBuf,
+        /// The .es3 file to write
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Password, defaults to the R.E.P.O. password
+        #[arg(long)]
+        password: Option<String>,
+        /// Gzip the JSON before encrypting it
+        #[arg(long)]
+        gzip: bool,
+    },
+    /// Show how any Easy Save 3 file is stored (IV, compression, password, JSON validity)
+    Inspect {
+        /// The .es3 file
+        file: PathBuf,
+        /// Password, the known password profiles are tried if omitted
+        #[arg(long)]
+        password: Option<String>,
+    },
+}
+
+/// Run a command headlessly and return the process exit code.
+pub fn run(cli: &Cli, command: &Command) -> ExitCode {
+    let output = Output::new(cli.json);
+
+    // Codec commands work on files given on the command line, they don't need any state.
+    match command {
+        Command::Decrypt {
+            file,
+            output: out_file,
+            password,
+        } => return codec::decrypt(&output, file, out_file.as_deref(), password.as_deref()),
+        Command::Encrypt {
+            input,
+            output: out_file,
+            password,
+            gzip,
+        } => return codec::encrypt(&output, input, out_file, password.as_deref(), *gzip),
+        Command::Inspect { file, password } => {
+            return codec::inspect(&output, file, password.as_deref());
+        }
+        _ => {}
+    }
+
+    let state = match load_state(cli) {
+        Ok(state) => Arc::new(Mutex::new(state)),
+        Err(err) => return output.operation("select_source", Err(err), ""),
+    };
+    let mut controller = AppController::new(state.clone());
+    if let Some(index_path) = MetadataIndex::default_path() {
+        controller = controller.with_index(index_path);
+    }
+    match command {
+        Command::RebuildIndex => controller.rebuild_index(),
+        _ => controller.refresh_saves(),
+    }
+    // Commands run in the foreground, nobody is there to watch or cancel them.
+    let progress = JobProgress::default();
+    let save_lists = state.lock().unwrap().save_lists();
+
+    match command {
+        Command::List => output.save_lists(&state.lock().unwrap()),
+        Command::Backup { name } => output.operation(
+            "backup",
+            controller.backup_save(&resolve(&save_lists.game_save_bundles, name), &progress),
+            "Backed up save",
+        ),
+        Command::BackupAll => output.operation(
+            "backup_all",
+            controller.backup_all(&progress),
+            "Backed up all saves",
+        ),
+        Command::Restore { name, force, to } => {
+            let id = resolve(&save_lists.backup_save_bundles, name);
+            let result = match to {
+                Some(source) => controller.restore_backup_to(&id, source, *force, &progress),
+                None => controller.restore_backup(&id, *force, &progress),
+            };
+            output.operation("restore", result, "Restored backup")
+        }
+        Command::Clone { name, backup, team } => {
+            let id = resolve_any(&save_lists, name, *backup);
+            output.save_cloned(controller.clone_save(&id, team.as_deref(), &progress))
+        }
+        Command::Compare {
+            from,
+            to,
+            from_backup,
+            to_backup,
+        } => {
+            let from = resolve_any(&save_lists, from, *from_backup);
+            let to = resolve_any(&save_lists, to, *to_backup);
+            match controller.compare_saves(&from, &to) {
+                Ok(diff) => output.save_diff(&diff),
+                Err(err) => output.operation("compare", Err(err), ""),
+            }
+        }
+        Command::Templates => output.templates(&controller.list_templates()),
+        Command::CaptureTemplate {
+            name,
+            template,
+            backup,
+            strip_players,
+        } => output.operation(
+            "capture_template",
+            controller
+                .capture_template(
+                    &resolve_any(&save_lists, name, *backup),
+                    template,
+                    *strip_players,
+                )
+                .map(|_| ()),
+            &format!("Saved as the template {template}"),
+        ),
+        Command::DeleteTemplate { template } => output.operation(
+            "delete_template",
+            controller.delete_template(template),
+            "Deleted template",
+        ),
+        Command::NewSave {
+            template,
+            team,
+            reset_upgrades,
+        } => {
+            let options = NewSaveOptions {
+                team_name: team.clone(),
+                reset_upgrades: *reset_upgrades,
+            };
+            output.new_save(controller.new_save_from_template(template, &options))
+        }
+        Command::Delete { name } => output.operation(
+            "delete",
+            controller.delete_backup(&resolve(&save_lists.backup_save_bundles, name)),
+            "Deleted backup",
+        ),
+        Command::Stats { name, set } => {
+            let id = resolve(&save_lists.game_save_bundles, name);
+            if set.is_empty() {
+                match controller.read_save(&id) {
+                    Ok(save) => {
+                        output.run_stats(&id, &save.dictionary_of_dictionaries.value.run_stats)
+                    }
+                    Err(err) => output.operation("stats", Err(err), ""),
+                }
+            } else {
+                let edits = SaveEdits {
+                    run_stats: set.iter().cloned().collect(),
+                    ..SaveEdits::default()
+                };
+                output.edit("set_stats", controller.apply_edits(&id, &edits))
+            }
+        }
+        Command::Players {
+            name,
+            player,
+            set,
+            remove,
+        } => {
+            let id = resolve(&save_lists.game_save_bundles, name);
+            if !remove.is_empty() {
+                return output.edit(
+                    "remove_players",
+                    controller.edit_save(&id, |save| remove_players(save, remove)),
+                );
+            }
+            match player {
+                Some(player) => output.edit(
+                    "set_player",
+                    controller.edit_save(&id, |save| set_player_upgrades(save, player, set)),
+                ),
+                None => match controller.read_save(&id) {
+                    Ok(save) => output.player_stats(&id, &edit::player_stats(&save)),
+                    Err(err) => output.operation("players", Err(err), ""),
+                },
+            }
+        }
+        Command::Patch {
+            name,
+            file,
+            dry_run,
+        } => {
+            let id = resolve(&save_lists.game_save_bundles, name);
+            let patch = match SavePatch::read(file) {
+                Ok(patch) => patch,
+                Err(err) => {
+                    return output.operation(
+                        "patch",
+                        Err(ControllerError::EditFailed(err.into())),
+                        "",
+                    );
+                }
+            };
+            if *dry_run {
+                match controller.preview_patch(&id, &patch) {
+                    Ok(changes) => output.json_changes(&id, &changes),
+                    Err(err) => output.operation("patch", Err(err), ""),
+                }
+            } else {
+                output.edit("patch", controller.patch_save(&id, &patch))
+            }
+        }
+        Command::Transplant {
+            from,
+            to,
+            player,
+            target_player,
+        } => {
+            let source = resolve(
+                &[
+                    &save_lists.game_save_bundles[..],
+                    &save_lists.backup_save_bundles[..],
+                ]
+                .concat(),
+                from,
+            );
+            let target = resolve(&save_lists.game_save_bundles, to);
+            let result =
+                controller.transplant_player(&source, player, &target, target_player.as_deref());
+            output.edit("transplant_player", result)
+        }
+        Command::Items {
+            name,
+            add,
+            remove,
+            recharge,
+        } => {
+            let id = resolve(&save_lists.game_save_bundles, name);
+            let items: Vec<ItemEdit> = add
+                .iter()
+                .map(|name| ItemEdit::Add(name.clone()))
+                .chain(remove.iter().map(|key| ItemEdit::Remove(key.clone())))
+                .chain(
+                    recharge
+                        .iter()
+                        .map(|key| ItemEdit::SetBattery(key.clone(), edit::FULL_BATTERY)),
+                )
+                .collect();
+            if items.is_empty() {
+                match controller.read_save(&id) {
+                    Ok(save) => output.inventory(&id, &edit::inventory(&save)),
+                    Err(err) => output.operation("items", Err(err), ""),
+                }
+            } else {
+                let edits = SaveEdits {
+                    items,
+                    ..SaveEdits::default()
+                };
+                output.edit("edit_items", controller.apply_edits(&id, &edits))
+            }
+        }
+        Command::Rename {
+            name,
+            team,
+            new_identity,
+        } => {
+            let id = resolve(&save_lists.game_save_bundles, name);
+            let edits = SaveEdits {
+                team_name: team.clone(),
+                new_name: new_identity.then(save_bundle::new_save_name),
+                ..SaveEdits::default()
+            };
+            output.rename(
+                controller.apply_edits(&id, &edits),
+                edits.new_name.as_deref(),
+            )
+        }
+        Command::Verify => match controller.verify_saves() {
+            Ok(checks) => output.verification(&checks),
+            Err(err) => output.operation("verify", Err(err), ""),
+        },
+        Command::Check { fix } => {
+            let mut save_check = controller.check_saves();
+            if *fix {
+                for lint in save_check.game_saves.iter().filter(|lint| lint.fixable()) {
+                    if let Err(err) = controller.fix_save(&lint.location) {
+                        return output.operation("check", Err(err), "");
+                    }
+                }
+                save_check = controller.check_saves();
+            }
+            output.save_check(&save_check)
+        }
+        Command::RebuildIndex => output.save_lists(&state.lock().unwrap()),
+        Command::Decrypt { .. } | Command::Encrypt { .. } | Command::Inspect { .. } => {
+            unreachable!("codec commands are handled above")
+        }
+    }
+}
+
+/// Find the bundle designated on the command line, by ID or by name.
+/// Unknown bundles are passed on as IDs, for the controller to report them as not found.
+fn resolve(save_bundles: &[SaveBundle], name_or_id: &str) -> BundleId {
+    save_bundles
+        .iter()
+        .find(|s| s.id.as_str() == name_or_id)
+        .or_else(|| save_bundles.iter().find(|s| s.name == name_or_id))
+        .map_or_else(|| BundleId::from(name_or_id), |s| s.id.clone())
+}
+
+/// Resolve a game save, or a backup if `backup` is set or no game save matches.
+fn resolve_any(save_lists: &SaveLists, name_or_id: &str, backup: bool) -> BundleId {
+    if backup {
+        resolve(&save_lists.backup_save_bundles, name_or_id)
+    } else {
+        resolve(
+            &[
+                &save_lists.game_save_bundles[..],
+                &save_lists.backup_save_bundles[..],
+            ]
+            .concat(),
+            name_or_id,
+        )
+    }
+}
+
+/// Set upgrades of a player given on the command line, by name or Steam ID,
+/// the upgrades being named as accepted by `edit::find_player_stat`.
+fn set_player_upgrades(
+    save: &mut SaveGame,
+    player: &str,
+    upgrades: &[(String, i32)],
+) -> Result<(), EditError> {
+    let steam_id =
+        edit::find_player(save, player).ok_or_else(|| EditError::UnknownPlayer(player.into()))?;
+    let stats = upgrades
+        .iter()
+        .map(|(name, value)| {
+            let spec =
+                edit::find_player_stat(name).ok_or_else(|| EditError::UnknownStat(name.clone()))?;
+            Ok((spec.key.to_string(), *value))
+        })
+        .collect::<Result<_, EditError>>()?;
+    edit::set_player_stats(save, &HashMap::from([(steam_id, stats)]))
+}
+
+/// Remove players given on the command line, by name or Steam ID.
+fn remove_players(save: &mut SaveGame, players: &[String]) -> Result<(), EditError> {
+    for player in players {
+        let steam_id = edit::find_player(save, player).unwrap_or(player.clone());
+        edit::remove_player(save, &steam_id)?;
+    }
+    Ok(())
+}
+
+/// Parse a `KEY=VALUE` argument.
+fn parse_assignment(argument: &str) -> Result<(String, i32), String> {
+    let (key, value) = argument
+        .split_once('=')
+        .ok_or_else(|| format!("expected STAT=VALUE, got `{argument}`"))?;
+    let value = value
+        .trim()
+        .parse()
+        .map_err(|e| format!("invalid value for `{key}`: {e}"))?;
+    Ok((key.trim().to_string(), value))
+}
+
+/// Build the state from the GUI's persisted settings and the command line overrides.
+fn load_state(cli: &Cli) -> ControllerResult<AppState> {
+    let mut state = eframe::storage_dir(constant::APP_ID)
+        .and_then(|dir| AppState::from_ron_storage(dir.join("app.ron"), eframe::APP_KEY))
+        .unwrap_or_default();
+    if let Some(source) = &cli.source {
+        let index = state
+            .sources
+            .iter()
+            .position(|s| s.name == *source)
+            .ok_or_else(|| ControllerError::SourceNotFound(source.clone()))?;
+        state.select_source(index);
+    }
+    if let Some(save_dir) = &cli.save_dir {
+        state.save_directory = save_dir.clone();
+    }
+    if let Some(backup_dir) = &cli.backup_dir {
+        state.backup_directory = backup_dir.clone();
+    }
+    Ok(state)
+}
@@ -0,0 +1,909 @@
+//! Printing of command results, either as human readable text or as JSON.
+//!
+//! JSON field names and exit codes are part of the command line interface,
+//! scripts rely on them so they must stay stable.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use repo_save_manager::controller::{ControllerError, ControllerResult};
+use repo_save_manager::model::{AppState, SaveCheck};
+use repo_save_manager::repo::crypt::Es3Info;
+use repo_save_manager::rsm::diff::{PlayerPresence, SaveDiff, ValueChange};
+use repo_save_manager::rsm::edit;
+use repo_save_manager::rsm::lint::BundleLint;
+use repo_save_manager::rsm::patch::JsonChange;
+use repo_save_manager::rsm::save_bundle::{BundleId, SaveBundle, SaveBundleError};
+use repo_save_manager::rsm::template::TemplateInfo;
+
+/// Exit code used when a command fails for a reason other than a `ControllerError`.
+pub const EXIT_FAILURE: u8 = 1;
+
+/// Exit code used when an es3 file can't be read, decoded, encoded or written.
+pub const EXIT_CODEC_FAILED: u8 = 20;
+
+/// Exit code of the command line interface for each `ControllerError` variant.
+pub fn exit_code(err: &ControllerError) -> u8 {
+    match err {
+        ControllerError::BackupFailed(_) => 10,
+        ControllerError::DeleteBackupFailed(_) => 11,
+        ControllerError::RestoreBackupFailed(_) => 12,
+        ControllerError::SaveExists(_) => 13,
+        ControllerError::GameRunning => 14,
+        ControllerError::SourceNotFound(_) => 15,
+        ControllerError::UnsafeDirectories(_) => 16,
+        ControllerError::EditFailed(_) => 17,
+        ControllerError::CloneFailed(_) => 18,
+        ControllerError::TemplateFailed(_) => 19,
+        ControllerError::CompareFailed(_) => 21,
+        ControllerError::VerifyFailed(_) => 22,
+        // Sources are only renamed in the graphical interface.
+        ControllerError::RenameSourceFailed(_) => EXIT_FAILURE,
+    }
+}
+
+/// Stable identifier of each `ControllerError` variant, used in JSON output.
+fn error_kind(err: &ControllerError) -> &'static str {
+    match err {
+        ControllerError::BackupFailed(_) => "backup_failed",
+        ControllerError::DeleteBackupFailed(_) => "delete_backup_failed",
+        ControllerError::RestoreBackupFailed(_) => "restore_backup_failed",
+        ControllerError::SaveExists(_) => "save_exists",
+        ControllerError::GameRunning => "game_running",
+        ControllerError::SourceNotFound(_) => "source_not_found",
+        ControllerError::UnsafeDirectories(_) => "unsafe_directories",
+        ControllerError::EditFailed(_) => "edit_failed",
+        ControllerError::CloneFailed(_) => "clone_failed",
+        ControllerError::TemplateFailed(_) => "template_failed",
+        ControllerError::CompareFailed(_) => "compare_failed",
+        ControllerError::VerifyFailed(_) => "verify_failed",
+        ControllerError::RenameSourceFailed(_) => "rename_source_failed",
+    }
+}
+
+#[derive(Serialize)]
+struct BundleJson<'a> {
+    id: &'a str,
+    location: &'a Path,
+    name: &'a str,
+    /// Level as displayed to the user (starts at 1).
+    level: i32,
+    players: &'a [String],
+    team_name: &'a str,
+    /// Time played in seconds.
+    time_played: f32,
+    date: &'a str,
+    /// Save source a backup was made from, if recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<&'a str>,
+}
+
+impl<'a> From<&'a SaveBundle> for BundleJson<'a> {
+    fn from(save_bundle: &'a SaveBundle) -> Self {
+        BundleJson {
+            id: save_bundle.id.as_str(),
+            location: &save_bundle.location,
+            name: &save_bundle.name,
+            level: save_bundle.level + 1,
+            players: &save_bundle.players,
+            team_name: &save_bundle.team_name,
+            time_played: save_bundle.time_played,
+            date: &save_bundle.date,
+            source: save_bundle.source.as_deref(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct DirectoryJson<'a> {
+    directory: &'a str,
+    bundles: Vec<BundleJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct ListJson<'a> {
+    source: &'a str,
+    game_saves: DirectoryJson<'a>,
+    backups: DirectoryJson<'a>,
+}
+
+#[derive(Serialize)]
+struct PlayerJson<'a> {
+    steam_id: &'a str,
+    name: &'a str,
+    /// Values by per-player dictionary, eg. `playerUpgradeHealth`
+    upgrades: BTreeMap<&'static str, i32>,
+}
+
+#[derive(Serialize)]
+struct PlayersJson<'a> {
+    id: &'a str,
+    players: Vec<PlayerJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct InstanceJson<'a> {
+    key: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    battery: Option<i32>,
+}
+
+#[derive(Serialize)]
+struct ItemJson<'a> {
+    name: &'a str,
+    purchased: i32,
+    purchased_total: i32,
+    battery_upgrades: i32,
+    upgrades_purchased: i32,
+    instances: Vec<InstanceJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct InventoryJson<'a> {
+    id: &'a str,
+    items: Vec<ItemJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonChangesJson<'a> {
+    id: &'a str,
+    changes: Vec<JsonChangeJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonChangeJson<'a> {
+    path: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    before: Option<&'a serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    after: Option<&'a serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct SaveDiffJson<'a> {
+    team_name: Option<ChangeJson<&'a str>>,
+    /// Time played in seconds
+    time_played: Option<ChangeJson<f32>>,
+    run_stats: Vec<ValueChangeJson<'a>>,
+    players: Vec<PlayerDiffJson<'a>>,
+    items: Vec<ItemDiffJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct ChangeJson<T> {
+    before: T,
+    after: T,
+}
+
+#[derive(Serialize)]
+struct ValueChangeJson<'a> {
+    key: &'a str,
+    label: &'a str,
+    /// None if the value is missing
+    before: Option<i32>,
+    after: Option<i32>,
+}
+
+impl<'a> From<&'a ValueChange> for ValueChangeJson<'a> {
+    fn from(change: &'a ValueChange) -> Self {
+        ValueChangeJson {
+            key: &change.key,
+            label: &change.label,
+            before: change.before,
+            after: change.after,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PlayerDiffJson<'a> {
+    steam_id: &'a str,
+    name: &'a str,
+    /// `both`, `added` or `removed`
+    presence: &'static str,
+    upgrades: Vec<ValueChangeJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct ItemDiffJson<'a> {
+    name: &'a str,
+    before: usize,
+    after: usize,
+}
+
+#[derive(Serialize)]
+struct TemplatesJson<'a> {
+    templates: Vec<TemplateJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct TemplateJson<'a> {
+    name: &'a str,
+    team_name: &'a str,
+    /// As displayed, starting at 1
+    level: i32,
+    players: &'a [String],
+}
+
+/// Reason a command failed.
+#[derive(Serialize)]
+pub struct Failure {
+    pub kind: &'static str,
+    pub message: String,
+    pub exit_code: u8,
+}
+
+impl From<ControllerError> for Failure {
+    fn from(err: ControllerError) -> Self {
+        Failure {
+            kind: error_kind(&err),
+            exit_code: exit_code(&err),
+            message: err.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct OperationJson<'a> {
+    operation: &'a str,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Failure>,
+    /// Where the original of an edited save is kept.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snapshot: Option<&'a Path>,
+    /// The new name of a renamed save.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct RunStatsJson<'a> {
+    id: &'a str,
+    run_stats: BTreeMap<&'a str, i32>,
+}
+
+#[derive(Serialize)]
+struct InspectJson<'a> {
+    file: &'a Path,
+    size: usize,
+    encrypted: bool,
+    /// Hex encoded IV
+    iv: Option<String>,
+    password_profile: Option<&'a str>,
+    gzipped: bool,
+    valid_json: bool,
+}
+
+#[derive(Serialize)]
+struct CheckJson<'a> {
+    location: &'a Path,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bundle: Option<BundleJson<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct VerifyJson<'a> {
+    ok: bool,
+    bundles: Vec<CheckJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct SaveCheckJson<'a> {
+    ok: bool,
+    game_saves: Vec<BundleLintJson<'a>>,
+    backups: Vec<BundleLintJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct BundleLintJson<'a> {
+    name: &'a str,
+    location: &'a Path,
+    issues: Vec<LintIssueJson>,
+    /// Why the save couldn't be read, if it couldn't
+    error: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct LintIssueJson {
+    message: String,
+    fixable: bool,
+}
+
+impl<'a> From<&'a BundleLint> for BundleLintJson<'a> {
+    fn from(lint: &'a BundleLint) -> Self {
+        BundleLintJson {
+            name: &lint.name,
+            location: &lint.location,
+            issues: lint
+                .issues
+                .iter()
+                .map(|issue| LintIssueJson {
+                    message: issue.to_string(),
+                    fixable: issue.fixable(),
+                })
+                .collect(),
+            error: lint.error.as_deref(),
+        }
+    }
+}
+
+/// Prints command results in the format selected on the command line.
+pub struct Output {
+    json: bool,
+}
+
+impl Output {
+    pub fn new(json: bool) -> Self {
+        Output { json }
+    }
+
+    /// Print the game saves and backups.
+    pub fn save_lists(&self, state: &AppState) -> ExitCode {
+        let save_lists = state.save_lists();
+        if self.json {
+            print_json(&ListJson {
+                source: &state.active_source().name,
+                game_saves: DirectoryJson {
+                    directory: &state.save_directory,
+                    bundles: save_lists
+                        .game_save_bundles
+                        .iter()
+                        .map(Into::into)
+                        .collect(),
+                },
+                backups: DirectoryJson {
+                    directory: &state.backup_directory,
+                    bundles: save_lists
+                        .backup_save_bundles
+                        .iter()
+                        .map(Into::into)
+                        .collect(),
+                },
+            });
+        } else {
+            println!("Source: {}", state.active_source().name);
+            println!();
+            print_bundles(
+                "Game saves",
+                &state.save_directory,
+                &save_lists.game_save_bundles,
+            );
+            println!();
+            print_bundles(
+                "Backups",
+                &state.backup_directory,
+                &save_lists.backup_save_bundles,
+            );
+        }
+        ExitCode::SUCCESS
+    }
+
+    /// Print the result of an operation and return the matching exit code.
+    ///
+    /// `operation` identifies the operation in JSON output, eg. `backup`.
+    pub fn operation(
+        &self,
+        operation: &str,
+        result: ControllerResult<()>,
+        success_message: &str,
+    ) -> ExitCode {
+        self.report(operation, result.map_err(Failure::from), success_message)
+    }
+
+    /// Print the result of an edit, with where the original save is kept.
+    pub fn edit(&self, operation: &str, result: ControllerResult<Option<PathBuf>>) -> ExitCode {
+        match result {
+            Ok(Some(snapshot)) => self.print_operation(
+                operation,
+                Ok(()),
+                &format!("Saved, the original is kept in {}", snapshot.display()),
+                Some(&snapshot),
+                None,
+            ),
+            Ok(None) => self.print_operation(
+                operation,
+                Ok(()),
+                "Nothing to save, the save is unchanged",
+                None,
+                None,
+            ),
+            Err(err) => self.print_operation(operation, Err(err.into()), "", None, None),
+        }
+    }
+
+    /// Print the result of cloning a save, with the name of the new save.
+    pub fn save_cloned(&self, result: ControllerResult<String>) -> ExitCode {
+        self.new_save_operation("clone", result, "Cloned to")
+    }
+
+    /// Print the result of creating a save from a template, with the name of the new save.
+    pub fn new_save(&self, result: ControllerResult<String>) -> ExitCode {
+        self.new_save_operation("new_save", result, "Created")
+    }
+
+    fn new_save_operation(
+        &self,
+        operation: &str,
+        result: ControllerResult<String>,
+        success_message: &str,
+    ) -> ExitCode {
+        match result {
+            Ok(new_name) => self.print_operation(
+                operation,
+                Ok(()),
+                &format!("{success_message} {new_name}"),
+                None,
+                Some(&new_name),
+            ),
+            Err(err) => self.print_operation(operation, Err(err.into()), "", None, None),
+        }
+    }
+
+    /// Print the result of renaming the team of a save or giving it a new name.
+    pub fn rename(
+        &self,
+        result: ControllerResult<Option<PathBuf>>,
+        new_name: Option<&str>,
+    ) -> ExitCode {
+        match result {
+            Ok(Some(snapshot)) => {
+                let message = match new_name {
+                    Some(new_name) => format!(
+                        "Renamed to {new_name}, the original is kept in {}",
+                        snapshot.display()
+                    ),
+                    None => format!("Saved, the original is kept in {}", snapshot.display()),
+                };
+                self.print_operation("rename", Ok(()), &message, Some(&snapshot), new_name)
+            }
+            Ok(None) => self.print_operation(
+                "rename",
+                Ok(()),
+                "Nothing to save, the save is unchanged",
+                None,
+                None,
+            ),
+            Err(err) => self.print_operation("rename", Err(err.into()), "", None, None),
+        }
+    }
+
+    /// Print the run stats of a save.
+    pub fn run_stats(&self, id: &BundleId, run_stats: &HashMap<String, i32>) -> ExitCode {
+        if self.json {
+            print_json(&RunStatsJson {
+                id: id.as_str(),
+                run_stats: run_stats
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), *value))
+                    .collect(),
+            });
+        } else {
+            for (key, value) in edit::sorted_run_stats(run_stats) {
+                println!("{key:<28} {value}");
+            }
+        }
+        ExitCode::SUCCESS
+    }
+
+    /// Print the upgrades and crown of the players of a save, one row per player.
+    pub fn player_stats(&self, id: &BundleId, players: &[edit::PlayerStats]) -> ExitCode {
+        if self.json {
+            print_json(&PlayersJson {
+                id: id.as_str(),
+                players: players
+                    .iter()
+                    .map(|player| PlayerJson {
+                        steam_id: &player.steam_id,
+                        name: &player.name,
+                        upgrades: edit::PLAYER_STATS
+                            .iter()
+                            .map(|spec| spec.key)
+                            .zip(player.values.iter().copied())
+                            .collect(),
+                    })
+                    .collect(),
+            });
+        } else {
+            let name_width = players
+                .iter()
+                .map(|p| p.name.len())
+                .max()
+                .unwrap_or(0)
+                .max(6);
+            print!("{:<name_width$}", "Player");
+            for spec in edit::PLAYER_STATS {
+                print!("  {}", spec.label);
+            }
+            println!();
+            for player in players {
+                print!("{:<name_width$}", player.name);
+                for (spec, value) in edit::PLAYER_STATS.iter().zip(&player.values) {
+                    print!("  {value:>width$}", width = spec.label.len());
+                }
+                println!("  [{}]", player.steam_id);
+            }
+        }
+        ExitCode::SUCCESS
+    }
+
+    /// Print the changes made to the JSON of a save, one per line: `+` for an added value,
+    /// `-` for a removed value and `~` for a changed value.
+    pub fn json_changes(&self, id: &BundleId, changes: &[JsonChange]) -> ExitCode {
+        if self.json {
+            print_json(&JsonChangesJson {
+                id: id.as_str(),
+                changes: changes
+                    .iter()
+                    .map(|change| JsonChangeJson {
+                        path: &change.path,
+                        before: change.before.as_ref(),
+                        after: change.after.as_ref(),
+                    })
+                    .collect(),
+            });
+        } else if changes.is_empty() {
+            println!("No changes");
+        } else {
+            for change in changes {
+                match (&change.before, &change.after) {
+                    (Some(before), Some(after)) => {
+                        println!("~ {}: {before} -> {after}", change.path)
+                    }
+                    (None, Some(after)) => println!("+ {}: {after}", change.path),
+                    (Some(before), None) => println!("- {}: {before}", change.path),
+                    (None, None) => println!("- {}", change.path),
+                }
+            }
+        }
+        ExitCode::SUCCESS
+    }
+
+    /// Print what changes from a save to another.
+    pub fn save_diff(&self, diff: &SaveDiff) -> ExitCode {
+        if self.json {
+            print_json(&SaveDiffJson {
+                team_name: diff.team_name.as_ref().map(|(before, after)| ChangeJson {
+                    before: before.as_str(),
+                    after: after.as_str(),
+                }),
+                time_played: diff
+                    .time_played
+                    .map(|(before, after)| ChangeJson { before, after }),
+                run_stats: diff.run_stats.iter().map(Into::into).collect(),
+                players: diff
+                    .players
+                    .iter()
+                    .map(|player| PlayerDiffJson {
+                        steam_id: &player.steam_id,
+                        name: &player.name,
+                        presence: match player.presence {
+                            PlayerPresence::Both => "both",
+                            PlayerPresence::Added => "added",
+                            PlayerPresence::Removed => "removed",
+                        },
+                        upgrades: player.upgrades.iter().map(Into::into).collect(),
+                    })
+                    .collect(),
+                items: diff
+                    .items
+                    .iter()
+                    .map(|item| ItemDiffJson {
+                        name: &item.name,
+                        before: item.before,
+                        after: item.after,
+                    })
+                    .collect(),
+            });
+            return ExitCode::SUCCESS;
+        }
+        if diff.is_empty() {
+            println!("No differences");
+            return ExitCode::SUCCESS;
+        }
+        let value = |value: Option<i32>| value.map_or("-".to_string(), |value| value.to_string());
+        if let Some((before, after)) = &diff.team_name {
+            println!("Team: {before} -> {after}");
+        }
+        if let Some((before, after)) = diff.time_played {
+            println!("Time played: {before:.0}s -> {after:.0}s");
+        }
+        if !diff.run_stats.is_empty() {
+            println!("Run stats");
+            for change in &diff.run_stats {
+                println!(
+                    "  {}: {} -> {}",
+                    change.label,
+                    value(change.before),
+                    value(change.after)
+                );
+            }
+        }
+        for player in &diff.players {
+            match player.presence {
+                PlayerPresence::Both => println!("{} ({})", player.name, player.steam_id),
+                PlayerPresence::Added => println!("{} ({}) added", player.name, player.steam_id),
+                PlayerPresence::Removed => {
+                    println!("{} ({}) removed", player.name, player.steam_id)
+                }
+            }
+            for change in &player.upgrades {
+                println!(
+                    "  {}: {} -> {}",
+                    change.label,
+                    value(change.before),
+                    value(change.after)
+                );
+            }
+        }
+        if !diff.items.is_empty() {
+            println!("Items");
+            for item in &diff.items {
+                println!("  {}: {} -> {}", item.name, item.before, item.after);
+            }
+        }
+        ExitCode::SUCCESS
+    }
+
+    /// Print the templates of the backup directory.
+    pub fn templates(&self, templates: &[TemplateInfo]) -> ExitCode {
+        if self.json {
+            print_json(&TemplatesJson {
+                templates: templates
+                    .iter()
+                    .map(|template| TemplateJson {
+                        name: &template.name,
+                        team_name: &template.team_name,
+                        level: template.level + 1,
+                        players: &template.players,
+                    })
+                    .collect(),
+            });
+        } else if templates.is_empty() {
+            println!("No templates, use capture-template to create one");
+        } else {
+            for template in templates {
+                print!(
+                    "{}  team {}  level {}",
+                    template.name,
+                    template.team_name,
+                    template.level + 1
+                );
+                if !template.players.is_empty() {
+                    print!("  players: {}", template.players.join(", "));
+                }
+                println!();
+            }
+        }
+        ExitCode::SUCCESS
+    }
+
+    /// Print the items of a save, each followed by its instances.
+    pub fn inventory(&self, id: &BundleId, items: &[edit::InventoryItem]) -> ExitCode {
+        if self.json {
+            print_json(&InventoryJson {
+                id: id.as_str(),
+                items: items
+                    .iter()
+                    .map(|item| ItemJson {
+                        name: &item.name,
+                        purchased: item.purchased,
+                        purchased_total: item.purchased_total,
+                        battery_upgrades: item.battery_upgrades,
+                        upgrades_purchased: item.upgrades_purchased,
+                        instances: item
+                            .instances
+                            .iter()
+                            .map(|instance| InstanceJson {
+                                key: &instance.key,
+                                battery: instance.battery,
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            });
+        } else {
+            for item in items {
+                print!(
+                    "{}  purchased {}  total {}",
+                    item.name, item.purchased, item.purchased_total
+                );
+                if item.battery_upgrades > 0 {
+                    print!("  battery upgrades {}", item.battery_upgrades);
+                }
+                if item.upgrades_purchased > 0 {
+                    print!("  upgrades purchased {}", item.upgrades_purchased);
+                }
+                println!();
+                for instance in &item.instances {
+                    match instance.battery {
+                        Some(battery) => println!("  {}  battery {battery}%", instance.key),
+                        None => println!("  {}", instance.key),
+                    }
+                }
+            }
+        }
+        ExitCode::SUCCESS
+    }
+
+    /// Print the result of any command and return the matching exit code.
+    pub fn report(
+        &self,
+        operation: &str,
+        result: Result<(), Failure>,
+        success_message: &str,
+    ) -> ExitCode {
+        self.print_operation(operation, result, success_message, None, None)
+    }
+
+    fn print_operation(
+        &self,
+        operation: &str,
+        result: Result<(), Failure>,
+        success_message: &str,
+        snapshot: Option<&Path>,
+        name: Option<&str>,
+    ) -> ExitCode {
+        let code = match &result {
+            Ok(()) => 0,
+            Err(failure) => failure.exit_code,
+        };
+        if self.json {
+            print_json(&OperationJson {
+                operation,
+                ok: result.is_ok(),
+                error: result.err(),
+                snapshot,
+                name,
+            });
+        } else {
+            match result {
+                Ok(()) => println!("{success_message}"),
+                Err(failure) => eprintln!("Error: {}", failure.message),
+            }
+        }
+        ExitCode::from(code)
+    }
+
+    /// Print how an es3 file is stored.
+    pub fn inspection(&self, file: &Path, info: &Es3Info) -> ExitCode {
+        let iv = info
+            .iv
+            .map(|iv| iv.iter().map(|b| format!("{b:02x}")).collect::<String>());
+        if self.json {
+            print_json(&InspectJson {
+                file,
+                size: info.size,
+                encrypted: info.iv.is_some(),
+                iv,
+                password_profile: info.password_profile.as_deref(),
+                gzipped: info.gzipped,
+                valid_json: info.valid_json,
+            });
+        } else {
+            let yes_no = |b: bool| if b { "yes" } else { "no" };
+            println!("File:      {}", file.display());
+            println!("Size:      {} bytes", info.size);
+            match (&iv, &info.password_profile) {
+                (Some(iv), Some(profile)) => {
+                    println!("Encrypted: yes (password profile: {profile})");
+                    println!("IV:        {iv}");
+                }
+                _ => println!("Encrypted: no"),
+            }
+            println!("Gzipped:   {}", yes_no(info.gzipped));
+            println!(
+                "JSON:      {}",
+                if info.valid_json { "valid" } else { "invalid" }
+            );
+        }
+        if info.valid_json {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(EXIT_CODEC_FAILED)
+        }
+    }
+
+    /// Print the result of reading every bundle, fails if any bundle can't be read.
+    pub fn verification(
+        &self,
+        checks: &[(PathBuf, Result<SaveBundle, SaveBundleError>)],
+    ) -> ExitCode {
+        let ok = checks.iter().all(|(_, save_bundle)| save_bundle.is_ok());
+        if self.json {
+            print_json(&VerifyJson {
+                ok,
+                bundles: checks
+                    .iter()
+                    .map(|(location, save_bundle)| CheckJson {
+                        location,
+                        ok: save_bundle.is_ok(),
+                        bundle: save_bundle.as_ref().ok().map(Into::into),
+                        error: save_bundle.as_ref().err().map(ToString::to_string),
+                    })
+                    .collect(),
+            });
+        } else {
+            for (location, save_bundle) in checks {
+                match save_bundle {
+                    Ok(_) => println!("OK      {}", location.display()),
+                    Err(e) => println!("FAILED  {}: {e}", location.display()),
+                }
+            }
+        }
+        if ok {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+
+    /// Print the issues found in the game saves and backups, failing if there are any.
+    pub fn save_check(&self, save_check: &SaveCheck) -> ExitCode {
+        let lints = || save_check.game_saves.iter().chain(&save_check.backups);
+        let ok = lints().all(BundleLint::is_clean);
+        if self.json {
+            print_json(&SaveCheckJson {
+                ok,
+                game_saves: save_check.game_saves.iter().map(Into::into).collect(),
+                backups: save_check.backups.iter().map(Into::into).collect(),
+            });
+        } else {
+            for lint in lints() {
+                if lint.is_clean() {
+                    println!("OK      {}", lint.location.display());
+                    continue;
+                }
+                println!("ISSUES  {}", lint.location.display());
+                if let Some(error) = &lint.error {
+                    println!("  {error}");
+                }
+                for issue in &lint.issues {
+                    let fix = if issue.fixable() {
+                        ""
+                    } else {
+                        " (fix by hand)"
+                    };
+                    println!("  {issue}{fix}");
+                }
+            }
+        }
+        if ok {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
+
+fn print_json(value: &impl Serialize) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{json}"),
+        Err(e) => log::error!(e:err; "Failed to serialise output."),
+    }
+}
+
+fn print_bundles(title: &str, directory: &str, save_bundles: &[SaveBundle]) {
+    println!("{title} ({directory})");
+    if save_bundles.is_empty() {
+        println!("  (none)");
+    }
+    for save_bundle in save_bundles {
+        print!(
+            "  {}  [{}]  level {}  players: {}",
+            save_bundle.name,
+            save_bundle.id,
+            save_bundle.level + 1,
+            save_bundle.players.join(", ")
+        );
+        match &save_bundle.source {
+            Some(source) => println!("  from: {source}"),
+            None => println!(),
+        }
+    }
+}
@@ -1,5 +1,13 @@
 pub const ENCRYPTION_PASS: &str = "Why would you want to cheat?... :o It's no fun. :') :'D";
+/// Known Easy Save 3 passwords as `(profile name, password)`, tried in order when decoding arbitrary es3 files.
+pub const PASSWORD_PROFILES: [(&str, &str); 2] =
+    [("repo", ENCRYPTION_PASS), ("es3-default", "password")];
 pub const QUALIFIER: &str = "uno";
 pub const ORGANISATION: &str = "robertbr";
 pub const APP_NAME: &str = "R.E.P.O. Save Manager";
 pub const APP_ID: &str = "repo-save-manager";
+pub const GAME_EXECUTABLE: &str = "REPO.exe";
+/// Steam app ID of R.E.P.O., names its Proton prefix on Linux.
+pub const STEAM_APP_ID: &str = "3241660";
+/// Prefix of the names the game gives to save bundles.
+pub const SAVE_PREFIX: &str = "REPO_SAVE_";
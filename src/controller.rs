@@ -1,31 +1,203 @@
 use log;
-use std::sync::mpsc::Receiver;
-use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::SystemTime;
 use thiserror::Error;
 
 use crate::fs_util;
-use crate::model::AppState;
-use crate::rsm::save_bundle::SaveBundle;
+use crate::jobs::{JobInfo, JobProgress, JobQueue, JobStatus};
+use crate::model::{AppState, PatchPreview, SaveCheck, SaveComparison, SaveLists};
+use crate::repo;
+use crate::repo::save::SaveGame;
+use crate::rsm::diff::{self, SaveDiff};
+use crate::rsm::discovery;
+use crate::rsm::edit::{self, EditError, SaveEdits};
+use crate::rsm::index::MetadataIndex;
+use crate::rsm::lint;
+use crate::rsm::patch::{self, JsonChange, SavePatch};
+use crate::rsm::save_bundle::{self, BundleId, SaveBundle, SaveBundleError};
+use crate::rsm::scan;
+use crate::rsm::template::{self, NewSaveOptions, TemplateError, TemplateInfo};
 
 #[derive(Debug, Error)]
 pub enum ControllerError {
     #[error("Backup failed: {0}")]
-    BackupFailed(String),
+    BackupFailed(FailureCause),
 
     #[error("Delete backup failed: {0}")]
-    DeleteBackupFailed(String),
+    DeleteBackupFailed(FailureCause),
 
     #[error("Restore backup failed: {0}")]
-    RestoreBackupFailed(String),
+    RestoreBackupFailed(FailureCause),
+
+    #[error("Edit failed: {0}")]
+    EditFailed(FailureCause),
+
+    #[error("Clone failed: {0}")]
+    CloneFailed(FailureCause),
+
+    #[error("Template failed: {0}")]
+    TemplateFailed(FailureCause),
+
+    #[error("Compare failed: {0}")]
+    CompareFailed(FailureCause),
+
+    #[error("Verify failed: {0}")]
+    VerifyFailed(FailureCause),
+
+    #[error("Renaming the source failed: {0}")]
+    RenameSourceFailed(FailureCause),
+
+    #[error("Save `{0}` already exists in the game save directory")]
+    SaveExists(String),
+
+    #[error("R.E.P.O. is running, close the game and try again")]
+    GameRunning,
+
+    #[error("Save source `{0}` not found")]
+    SourceNotFound(String),
+
+    #[error("Unsafe directories: {0}")]
+    UnsafeDirectories(fs_util::DirectoryProblem),
 }
 
-type ControllerResult<T> = Result<T, ControllerError>;
+/// Why an operation on a save bundle failed.
+#[derive(Debug, Error)]
+pub enum FailureCause {
+    #[error("Save bundle `{0}` not found")]
+    BundleNotFound(String),
+
+    #[error(transparent)]
+    FileSystem(#[from] fs_util::SaveManagerError),
+
+    #[error(transparent)]
+    Edit(#[from] EditError),
+
+    #[error(transparent)]
+    Template(#[from] TemplateError),
+}
+
+impl ControllerError {
+    /// What the user can do to fix the error.
+    pub fn hint(&self) -> String {
+        match self {
+            ControllerError::BackupFailed(cause)
+            | ControllerError::DeleteBackupFailed(cause)
+            | ControllerError::RestoreBackupFailed(cause)
+            | ControllerError::EditFailed(cause)
+            | ControllerError::CloneFailed(cause)
+            | ControllerError::TemplateFailed(cause)
+            | ControllerError::CompareFailed(cause)
+            | ControllerError::VerifyFailed(cause)
+            | ControllerError::RenameSourceFailed(cause) => cause.hint(),
+            ControllerError::SaveExists(_) => {
+                "Confirm the restore to overwrite the game save.".to_string()
+            }
+            ControllerError::GameRunning => {
+                "Close R.E.P.O. so that it doesn't overwrite the saves, then try again.".to_string()
+            }
+            ControllerError::SourceNotFound(_) => {
+                "Check the name of the source, it may have been renamed or removed.".to_string()
+            }
+            ControllerError::UnsafeDirectories(problem) => problem.hint(),
+        }
+    }
+}
+
+impl FailureCause {
+    /// What the user can do to fix the error.
+    pub fn hint(&self) -> String {
+        match self {
+            FailureCause::BundleNotFound(_) => {
+                "The save list may be out of date, refresh the saves and try again.".to_string()
+            }
+            FailureCause::FileSystem(err) => err.hint(),
+            FailureCause::Edit(err) => err.hint(),
+            FailureCause::Template(err) => err.hint(),
+        }
+    }
+}
+
+pub type ControllerResult<T> = Result<T, ControllerError>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotificationLevel {
+    Success,
+    Error,
+}
+
+/// Outcome of an operation, reported to the user.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: NotificationLevel,
+    /// What happened
+    pub message: String,
+    /// What the user can do about it
+    pub hint: Option<String>,
+    pub time: SystemTime,
+}
+
+impl Notification {
+    pub fn success(message: impl Into<String>) -> Self {
+        Notification {
+            level: NotificationLevel::Success,
+            message: message.into(),
+            hint: None,
+            time: SystemTime::now(),
+        }
+    }
+
+    pub fn error(err: &ControllerError) -> Self {
+        Notification {
+            level: NotificationLevel::Error,
+            message: err.to_string(),
+            hint: Some(err.hint()),
+            time: SystemTime::now(),
+        }
+    }
+}
+
+/// Sends notifications to the view and wakes it up, so that notifications and
+/// state changes are shown without waiting for user input.
+#[derive(Clone)]
+pub struct Notifier {
+    sender: Sender<Notification>,
+    wake: Arc<dyn Fn() + Send + Sync>,
+}
+
+impl Notifier {
+    pub fn new(sender: Sender<Notification>, wake: impl Fn() + Send + Sync + 'static) -> Self {
+        Notifier {
+            sender,
+            wake: Arc::new(wake),
+        }
+    }
+
+    pub fn notify(&self, notification: Notification) {
+        // The view may already be closed, in which case there is nobody left to notify.
+        let _ = self.sender.send(notification);
+        self.wake();
+    }
+
+    pub fn wake(&self) {
+        (self.wake)();
+    }
+}
 
 #[derive(Debug)]
 pub enum AppEvent {
-    // Directory operations
+    // Directory operations, on the active source
     UpdateSaveDirectory(String),
     UpdateBackupDirectory(String),
+    SetOwnBackupLibrary(bool),
+
+    // Save sources, by index
+    SelectSource(usize),
+    AddSource,
+    RemoveSource(usize),
+    RenameSource(usize, String),
 
     /// Refresh save and backup lists
     RefreshSaves,
@@ -33,172 +205,1400 @@ pub enum AppEvent {
     /// Backup all saves
     BackupAll,
 
-    // Save management
-    BackupSave(String),
-    RequestRestoreBackup(String),
-    ConfirmRestoreBackup(String),
-    CancelRestoreBackup,
+    // Save management, by game save ID
+    BackupSave(BundleId),
 
-    // Backup management
-    RequestDeleteBackup(String),
-    ConfirmDeleteBackup(String),
+    // Backup management, by backup ID, restores also take the name of the target source
+    RequestRestoreBackup(BundleId, String),
+    ConfirmRestoreBackup(BundleId, String),
+    CancelRestoreBackup,
+    RequestDeleteBackup(BundleId),
+    ConfirmDeleteBackup(BundleId),
     CancelDeleteBackup,
 
+    // Save editor, by game save ID
+    OpenEditor(BundleId),
+    SaveEdits(BundleId, SaveEdits),
+    CloseEditor,
+    /// Read a patch file and show the changes it makes to a game save, before applying it
+    PreviewPatch(BundleId, PathBuf),
+    ConfirmPatch,
+    CancelPatch,
+    /// Copy the upgrades of a player of a game save or backup to a player of a game save:
+    /// source ID, source player, target ID and target player (defaults to the same Steam ID),
+    /// players by name or Steam ID
+    TransplantPlayer(BundleId, String, BundleId, Option<String>),
+
+    /// Copy a game save or backup to the game save directory as a new save,
+    /// with a new team name if given
+    CloneSave(BundleId, Option<String>),
+
+    // Templates, by name
+    OpenTemplates,
+    CloseTemplates,
+    /// Keep a game save or backup as a template, without its players if set
+    CaptureTemplate(BundleId, String, bool),
+    DeleteTemplate(String),
+    NewSaveFromTemplate(String, NewSaveOptions),
+
+    /// Compare two game saves or backups: what changes from the first to the second
+    CompareSaves(BundleId, BundleId),
+    CloseComparison,
+
+    /// Check the game saves and backups for suspicious values, showing the issues found
+    CheckSaves,
+    CloseSaveCheck,
+    /// Fix the issues of a game save that can be fixed safely, by location,
+    /// as a save with a misnamed save file has no ID
+    FixSave(PathBuf),
+
+    /// Stop a queued or running job, by ID
+    CancelJob(u64),
+
+    /// Forget the cached metadata and read every save again
+    RebuildIndex,
+
+    // Setup wizard, choosing the directories of the active source
+    StartSetup,
+    SetupChooseSaveDirectory(String),
+    SetupChooseBackupDirectory(String),
+    FinishSetup,
+    CancelSetup,
+
     // App lifecycle
     Exit,
 }
 
+#[derive(Clone)]
 pub struct AppController {
     state: Arc<Mutex<AppState>>,
-    event_receiver: Receiver<AppEvent>,
+    notifier: Option<Notifier>,
+    jobs: JobQueue,
+    /// Runs the refreshes requested by the view, separately from the jobs
+    scans: JobQueue,
+    /// True if a refresh is queued and hasn't started yet
+    scan_pending: Arc<AtomicBool>,
+    /// Held while refreshing
+    scanning: Arc<Mutex<()>>,
+    /// Metadata of the bundles read so far
+    index: Arc<Mutex<MetadataIndex>>,
+    /// Where the index is persisted, None to keep it in memory only
+    index_path: Option<PathBuf>,
+    /// Tells whether the game is running, see `with_game_probe`
+    game_probe: Arc<dyn Fn() -> bool + Send + Sync>,
 }
 
 impl AppController {
-    pub fn new(state: Arc<Mutex<AppState>>, event_receiver: Receiver<AppEvent>) -> Self {
+    pub fn new(state: Arc<Mutex<AppState>>) -> Self {
         AppController {
             state,
-            event_receiver,
+            notifier: None,
+            jobs: JobQueue::start(),
+            scans: JobQueue::start(),
+            scan_pending: Arc::new(AtomicBool::new(false)),
+            scanning: Arc::new(Mutex::new(())),
+            index: Arc::new(Mutex::new(MetadataIndex::default())),
+            index_path: None,
+            game_probe: Arc::new(repo::process::is_game_running),
         }
     }
 
-    pub fn handle_events(&self) {
+    /// Cache the metadata of the bundles in the index file at `index_path`,
+    /// so that the saves can be listed without reading them (see `MetadataIndex`).
+    pub fn with_index(mut self, index_path: PathBuf) -> Self {
+        self.index = Arc::new(Mutex::new(MetadataIndex::load(&index_path)));
+        self.index_path = Some(index_path);
+        self
+    }
+
+    /// Tell whether the game is running with `probe` rather than by listing the processes,
+    /// eg. in tests.
+    pub fn with_game_probe(mut self, probe: impl Fn() -> bool + Send + Sync + 'static) -> Self {
+        self.game_probe = Arc::new(probe);
+        self
+    }
+
+    /// Report the outcome of the events handled by `handle_events` to the view.
+    pub fn with_notifier(mut self, notifier: Notifier) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Process events sent by the view until the channel is closed or `AppEvent::Exit` is received.
+    ///
+    /// Operations working on save files are run as jobs in the background,
+    /// so that the view stays responsive and new events are handled meanwhile.
+    pub fn handle_events(&self, event_receiver: Receiver<AppEvent>) {
         log::info!("Controller event loop started");
 
-        while let Ok(event) = self.event_receiver.recv() {
+        while let Ok(event) = event_receiver.recv() {
             log::debug!("Received event: {:?}", event);
 
-            let mut event_result: Option<ControllerResult<()>> = None;
             match event {
                 AppEvent::UpdateSaveDirectory(dir) => {
+                    self.change_directories(|state| state.update_save_directory(dir));
+                }
+                AppEvent::UpdateBackupDirectory(dir) => {
+                    self.change_directories(|state| state.update_backup_directory(dir));
+                }
+                AppEvent::SetOwnBackupLibrary(own) => {
+                    self.change_directories(|state| state.set_own_backup_library(own));
+                }
+                AppEvent::SelectSource(index) => {
                     if let Ok(mut state) = self.state.lock() {
-                        state.update_save_directory(dir);
+                        state.select_source(index);
                     }
+                    self.request_refresh();
                 }
-                AppEvent::UpdateBackupDirectory(dir) => {
+                AppEvent::AddSource => {
                     if let Ok(mut state) = self.state.lock() {
-                        state.update_backup_directory(dir);
+                        state.add_source();
                     }
+                    self.request_refresh();
                 }
-                AppEvent::RefreshSaves => {
+                AppEvent::RemoveSource(index) => {
                     if let Ok(mut state) = self.state.lock() {
-                        state.refresh_save_bundles();
+                        state.remove_source(index);
                     }
+                    self.request_refresh();
+                }
+                AppEvent::RenameSource(index, name) => {
+                    self.spawn_job(format!("Rename source to {name}"), move |controller, _| {
+                        controller.on_rename_source(index, &name)
+                    });
                 }
-                AppEvent::BackupSave(name) => {
-                    event_result = Some(self.on_backup_save(name));
+                AppEvent::RefreshSaves => {
+                    self.request_refresh();
                 }
-                AppEvent::RequestRestoreBackup(name) => {
-                    event_result = Some(self.on_request_restore_backup(name));
+                AppEvent::RebuildIndex => {
+                    let controller = self.clone();
+                    self.scans.push(move || controller.rebuild_index());
                 }
-                AppEvent::ConfirmRestoreBackup(name) => {
-                    event_result = Some(self.on_confirm_restore_backup(name));
+                AppEvent::BackupSave(id) => {
+                    let name = self.bundle_name(BundleList::GameSaves, &id);
+                    self.spawn_job(format!("Backup {name}"), move |controller, progress| {
+                        controller.on_backup_save(id, name, progress)
+                    });
+                }
+                AppEvent::RequestRestoreBackup(id, source) => {
+                    let name = self.bundle_name(BundleList::Backups, &id);
+                    self.spawn_job(
+                        format!("Restore {name} to {source}"),
+                        move |controller, progress| {
+                            controller.on_request_restore_backup(id, source, name, progress)
+                        },
+                    );
+                }
+                AppEvent::ConfirmRestoreBackup(id, source) => {
+                    let name = self.bundle_name(BundleList::Backups, &id);
+                    self.spawn_job(
+                        format!("Restore {name} to {source}"),
+                        move |controller, progress| {
+                            controller.on_confirm_restore_backup(id, source, name, progress)
+                        },
+                    );
                 }
                 AppEvent::CancelRestoreBackup => {
                     if let Ok(mut state) = self.state.lock() {
                         state.clear_restore_confirmation();
                     }
                 }
-                AppEvent::RequestDeleteBackup(name) => {
+                AppEvent::RequestDeleteBackup(id) => {
+                    // Probe before locking, listing the processes takes a while.
+                    let game_running = (self.game_probe)();
                     if let Ok(mut state) = self.state.lock() {
-                        state.confirm_delete_backup(name);
+                        state.game_running = game_running;
+                        state.confirm_delete_backup(id);
                     }
                 }
-                AppEvent::ConfirmDeleteBackup(name) => {
-                    event_result = Some(self.on_confirm_delete_backup(name));
+                AppEvent::ConfirmDeleteBackup(id) => {
+                    let name = self.bundle_name(BundleList::Backups, &id);
+                    self.spawn_job(format!("Delete {name}"), move |controller, _| {
+                        controller.on_confirm_delete_backup(id, name)
+                    });
                 }
                 AppEvent::CancelDeleteBackup => {
                     if let Ok(mut state) = self.state.lock() {
                         state.clear_delete_confirmation();
                     }
                 }
+                AppEvent::OpenEditor(id) => {
+                    // Decrypting the save takes a moment, keep the event loop responsive.
+                    let controller = self.clone();
+                    self.scans.push(move || controller.on_open_editor(id));
+                }
+                AppEvent::SaveEdits(id, edits) => {
+                    let name = self.bundle_name(BundleList::GameSaves, &id);
+                    self.spawn_job(format!("Edit {name}"), move |controller, _| {
+                        controller.on_save_edits(id, edits, name)
+                    });
+                }
+                AppEvent::TransplantPlayer(source, player, target, target_player) => {
+                    let name = self.bundle_name(BundleList::GameSaves, &target);
+                    self.spawn_job(format!("Copy upgrades to {name}"), move |controller, _| {
+                        controller.on_transplant_player(
+                            &source,
+                            &player,
+                            target,
+                            target_player.as_deref(),
+                            name,
+                        )
+                    });
+                }
+                AppEvent::PreviewPatch(id, file) => {
+                    let controller = self.clone();
+                    self.scans
+                        .push(move || controller.on_preview_patch(id, file));
+                }
+                AppEvent::ConfirmPatch => {
+                    let preview = self.lock_state().patch_preview.take();
+                    if let Some(preview) = preview {
+                        self.spawn_job(format!("Patch {}", preview.name), move |controller, _| {
+                            controller.on_confirm_patch(preview)
+                        });
+                    }
+                }
+                AppEvent::CancelPatch => {
+                    if let Ok(mut state) = self.state.lock() {
+                        state.clear_patch_preview();
+                    }
+                }
+                AppEvent::CloseEditor => {
+                    if let Ok(mut state) = self.state.lock() {
+                        state.close_editor();
+                    }
+                }
+                AppEvent::CancelJob(id) => {
+                    if let Ok(state) = self.state.lock()
+                        && let Some(job) = state.jobs.iter().find(|job| job.id == id)
+                    {
+                        job.progress.cancel();
+                    }
+                }
+                AppEvent::StartSetup => {
+                    if let Ok(mut state) = self.state.lock() {
+                        state.start_setup();
+                    }
+                    let controller = self.clone();
+                    self.scans.push(move || {
+                        let candidates = discovery::discover_save_directories();
+                        // Choose the first directory with saves, unless one is configured already
+                        let mut state = controller.lock_state();
+                        let configured = state.setup.as_ref().map(|s| s.save_directory.clone());
+                        let first_with_saves = candidates
+                            .iter()
+                            .find(|candidate| !candidate.saves.is_empty())
+                            .map(|candidate| candidate.path.to_string_lossy().to_string());
+                        state.set_setup_candidates(candidates);
+                        if let Some(save_directory) =
+                            configured.filter(|d| !d.is_empty()).or(first_with_saves)
+                        {
+                            let validation = validate_save_directory(&save_directory);
+                            state.choose_setup_save_directory(save_directory, validation);
+                        }
+                        drop(state);
+                        controller.wake();
+                    });
+                }
+                AppEvent::SetupChooseSaveDirectory(dir) => {
+                    let validation = validate_save_directory(&dir);
+                    if let Ok(mut state) = self.state.lock() {
+                        state.choose_setup_save_directory(dir, validation);
+                    }
+                }
+                AppEvent::SetupChooseBackupDirectory(dir) => {
+                    if let Ok(mut state) = self.state.lock() {
+                        state.choose_setup_backup_directory(dir);
+                    }
+                }
+                AppEvent::FinishSetup => {
+                    self.on_finish_setup();
+                }
+                AppEvent::CancelSetup => {
+                    if let Ok(mut state) = self.state.lock() {
+                        state.close_setup();
+                    }
+                }
                 AppEvent::Exit => {
                     log::info!("Exit requested");
                     break;
                 }
+                AppEvent::CloneSave(id, team_name) => {
+                    let name = self.any_bundle_name(&id);
+                    self.spawn_job(format!("Clone {name}"), move |controller, progress| {
+                        let new_name =
+                            controller.clone_save(&id, team_name.as_deref(), progress)?;
+                        controller.notify(Notification::success(format!(
+                            "Cloned {name} to {new_name}"
+                        )));
+                        Ok(())
+                    });
+                }
+                AppEvent::OpenTemplates => {
+                    let templates = self.list_templates();
+                    self.lock_state().show_templates(templates);
+                }
+                AppEvent::CloseTemplates => {
+                    if let Ok(mut state) = self.state.lock() {
+                        state.close_templates();
+                    }
+                }
+                AppEvent::CaptureTemplate(id, template_name, strip_players) => {
+                    let name = self.any_bundle_name(&id);
+                    self.spawn_job(
+                        format!("Save {name} as a template"),
+                        move |controller, _| {
+                            controller.capture_template(&id, &template_name, strip_players)?;
+                            controller.reload_templates();
+                            controller.notify(Notification::success(format!(
+                                "Saved {name} as the template {template_name}"
+                            )));
+                            Ok(())
+                        },
+                    );
+                }
+                AppEvent::DeleteTemplate(template_name) => {
+                    self.spawn_job(
+                        format!("Delete template {template_name}"),
+                        move |controller, _| {
+                            let result = controller.delete_template(&template_name);
+                            controller.reload_templates();
+                            result?;
+                            controller.notify(Notification::success(format!(
+                                "Deleted template {template_name}"
+                            )));
+                            Ok(())
+                        },
+                    );
+                }
+                AppEvent::NewSaveFromTemplate(template_name, options) => {
+                    self.spawn_job(
+                        format!("New save from {template_name}"),
+                        move |controller, _| {
+                            let new_name =
+                                controller.new_save_from_template(&template_name, &options)?;
+                            controller.notify(Notification::success(format!(
+                                "Created {new_name} from the template {template_name}"
+                            )));
+                            Ok(())
+                        },
+                    );
+                }
+                AppEvent::CompareSaves(before, after) => {
+                    // Decrypting the saves takes a moment, keep the event loop responsive.
+                    let controller = self.clone();
+                    self.scans
+                        .push(move || controller.on_compare_saves(before, after));
+                }
+                AppEvent::CloseComparison => {
+                    if let Ok(mut state) = self.state.lock() {
+                        state.close_comparison();
+                    }
+                }
+                AppEvent::CheckSaves => {
+                    self.spawn_job("Check saves".to_string(), |controller, _| {
+                        let save_check = controller.check_saves();
+                        controller.lock_state().set_save_check(save_check, true);
+                        Ok(())
+                    });
+                }
+                AppEvent::CloseSaveCheck => {
+                    if let Ok(mut state) = self.state.lock() {
+                        state.close_save_check();
+                    }
+                }
+                AppEvent::FixSave(location) => {
+                    let name = location
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    self.spawn_job(format!("Fix {name}"), move |controller, _| {
+                        let snapshot = controller.fix_save(&location);
+                        let save_check = controller.check_saves();
+                        controller.lock_state().set_save_check(save_check, false);
+                        controller.notify(edit_notification(&name, snapshot?));
+                        Ok(())
+                    });
+                }
                 AppEvent::BackupAll => {
-                    event_result = Some(self.on_backup_all());
+                    self.spawn_job("Backup all saves".to_string(), |controller, progress| {
+                        controller.on_backup_all(progress)
+                    });
                 }
             }
 
-            if let Some(result) = event_result {
-                if let Err(err) = result {
-                    log::error!("Error occurred: {}", err);
-                }
-            }
+            // The state may have changed, make sure the view shows it.
+            self.wake();
+        }
+
+        // Stop the jobs left, they leave the directories untouched when cancelled.
+        if let Ok(state) = self.state.lock() {
+            state.jobs.iter().for_each(|job| job.progress.cancel());
         }
 
         log::info!("Controller event loop terminated");
     }
 
-    fn on_confirm_delete_backup(&self, name: String) -> ControllerResult<()> {
+    fn notify(&self, notification: Notification) {
+        if let Some(notifier) = &self.notifier {
+            notifier.notify(notification);
+        }
+    }
+
+    fn wake(&self) {
+        if let Some(notifier) = &self.notifier {
+            notifier.wake();
+        }
+    }
+
+    /// Refresh the saves on the scan worker, unless a refresh is already waiting to start.
+    fn request_refresh(&self) {
+        if self.scan_pending.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let controller = self.clone();
+        self.scans.push(move || {
+            controller.scan_pending.store(false, Ordering::Relaxed);
+            controller.refresh_saves();
+        });
+    }
+
+    /// Queue a job, it's listed in the state until it completes.
+    /// Errors returned by the job are logged and reported to the view.
+    fn spawn_job(
+        &self,
+        label: String,
+        task: impl FnOnce(&AppController, &JobProgress) -> ControllerResult<()> + Send + 'static,
+    ) {
+        let id = self.jobs.next_id();
+        let progress = Arc::new(JobProgress::default());
         if let Ok(mut state) = self.state.lock() {
-            let backup_bundle = get_backup_save_bundle(&state, &name)
-                .map_err(|err_msg| ControllerError::DeleteBackupFailed(err_msg))?;
-            std::fs::remove_dir_all(&backup_bundle.location)
-                .map_err(|e| ControllerError::DeleteBackupFailed(e.to_string()))?;
-            state.confirm_backup_deletion_name = None;
-            state.refresh_save_bundles();
+            state.jobs.push(JobInfo {
+                id,
+                label,
+                status: JobStatus::Queued,
+                progress: progress.clone(),
+            });
+        }
+
+        let controller = self.clone();
+        let queued = self.jobs.push(move || {
+            controller.set_job_status(id, Some(JobStatus::Running));
+            let result = task(&controller, &progress);
+            controller.set_job_status(id, None);
+
+            if let Err(err) = result {
+                log::error!("Error occurred: {}", err);
+                controller.notify(Notification::error(&err));
+            }
+            controller.wake();
+        });
+        if !queued {
+            log::error!("Job worker is not running, job {id} dropped");
+            self.set_job_status(id, None);
+        }
+    }
+
+    /// Update the status of a job, None removes the job from the state.
+    fn set_job_status(&self, id: u64, status: Option<JobStatus>) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        match status {
+            Some(status) => {
+                if let Some(job) = state.jobs.iter_mut().find(|job| job.id == id) {
+                    job.status = status;
+                }
+            }
+            None => state.jobs.retain(|job| job.id != id),
+        }
+        drop(state);
+        self.wake();
+    }
+
+    /// Name of a bundle for messages, its ID if it's not in the list.
+    fn bundle_name(&self, list: BundleList, id: &BundleId) -> String {
+        let lists = self.lock_state().save_lists();
+        list.bundles(&lists)
+            .iter()
+            .find(|s| s.id == *id)
+            .map_or_else(|| id.to_string(), |s| s.name.clone())
+    }
+
+    /// Update the template window with the templates on disk, if it's shown.
+    fn reload_templates(&self) {
+        let templates = self.list_templates();
+        let mut state = self.lock_state();
+        if state.templates.is_some() {
+            state.show_templates(templates);
         }
+    }
+
+    /// Name of a game save or backup, its ID if it's in neither list.
+    fn any_bundle_name(&self, id: &BundleId) -> String {
+        let lists = self.lock_state().save_lists();
+        lists
+            .game_save(id)
+            .or_else(|| lists.backup(id))
+            .map_or_else(|| id.to_string(), |s| s.name.clone())
+    }
+
+    fn on_backup_save(
+        &self,
+        id: BundleId,
+        name: String,
+        progress: &JobProgress,
+    ) -> ControllerResult<()> {
+        self.backup_save(&id, progress)?;
+        self.notify(Notification::success(format!("Backed up {name}")));
+        Ok(())
+    }
+
+    fn on_backup_all(&self, progress: &JobProgress) -> ControllerResult<()> {
+        self.backup_all(progress)?;
+        self.notify(Notification::success("Backed up all saves"));
+        Ok(())
+    }
+
+    /// Rename a source, moving its own library and the source recorded on its backups.
+    /// The rename is refused if its library can't be moved.
+    fn on_rename_source(&self, index: usize, name: &str) -> ControllerResult<()> {
+        let Some(rename) = self.lock_state().source_rename(index, name) else {
+            return Ok(());
+        };
+        if let Some((from, to)) = &rename.library
+            && Path::new(from).exists()
+        {
+            let moved = if Path::new(to).exists() {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("`{to}` already exists"),
+                ))
+            } else {
+                std::fs::rename(from, to)
+            };
+            moved.map_err(|e| {
+                ControllerError::RenameSourceFailed(fs_util::SaveManagerError::from(e).into())
+            })?;
+        }
+        for directory in &rename.backup_directories {
+            // The recorded source is only informative, the rename goes on without it.
+            if let Err(e) =
+                save_bundle::rename_recorded_source(directory, &rename.old_name, &rename.new_name)
+            {
+                log::warn!(e:err; "Failed to record the new source name in {}", directory);
+            }
+        }
+        self.lock_state().rename_source(&rename);
+        self.request_refresh();
         Ok(())
     }
 
-    /// Backup a save
-    fn on_backup_save(&self, name: String) -> ControllerResult<()> {
+    fn on_confirm_delete_backup(&self, id: BundleId, name: String) -> ControllerResult<()> {
+        self.delete_backup(&id)?;
         if let Ok(mut state) = self.state.lock() {
-            let save_bundle = get_game_save_bundle(&state, &name)
-                .map_err(|err_msg| ControllerError::BackupFailed(err_msg))?;
-            fs_util::copy_directory(&save_bundle.location, &state.backup_directory, true)
-                .map_err(|e| ControllerError::BackupFailed(e.to_string()))?;
-            state.refresh_save_bundles();
+            state.clear_delete_confirmation();
         }
+        self.notify(Notification::success(format!("Deleted backup {name}")));
         Ok(())
     }
 
-    /// Backup all saves
-    fn on_backup_all(&self) -> ControllerResult<()> {
+    fn on_request_restore_backup(
+        &self,
+        id: BundleId,
+        source: String,
+        name: String,
+        progress: &JobProgress,
+    ) -> ControllerResult<()> {
+        match self.restore_backup_to(&id, &source, false, progress) {
+            // Ask the user before overwriting the game save, showing what the restore changes.
+            Err(ControllerError::SaveExists(_)) => {
+                let diff = self.restore_diff(&id, &source);
+                if let Ok(mut state) = self.state.lock() {
+                    state.confirm_restore_backup(id, source, diff);
+                }
+                Ok(())
+            }
+            // Let the confirmation popup explain why the restore can't happen yet.
+            Err(ControllerError::GameRunning) => {
+                let diff = self.restore_diff(&id, &source);
+                if let Ok(mut state) = self.state.lock() {
+                    state.confirm_restore_backup(id, source, diff);
+                }
+                Err(ControllerError::GameRunning)
+            }
+            Ok(()) => {
+                self.notify(Notification::success(format!(
+                    "Restored {name} to {source}"
+                )));
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn on_confirm_restore_backup(
+        &self,
+        id: BundleId,
+        source: String,
+        name: String,
+        progress: &JobProgress,
+    ) -> ControllerResult<()> {
+        self.restore_backup_to(&id, &source, true, progress)?;
         if let Ok(mut state) = self.state.lock() {
-            for save_bundle in state.game_save_bundles.iter() {
-                fs_util::copy_directory(&save_bundle.location, &state.backup_directory, true)
-                    .map_err(|e| ControllerError::BackupFailed(e.to_string()))?;
+            state.clear_restore_confirmation();
+        }
+        self.notify(Notification::success(format!(
+            "Restored {name} to {source}"
+        )));
+        Ok(())
+    }
+
+    /// What restoring a backup to a source changes to the game save it overwrites,
+    /// None if there's no such save or it can't be read.
+    fn restore_diff(&self, id: &BundleId, source: &str) -> Option<SaveDiff> {
+        let (backup_location, save_directory) = {
+            let state = self.lock_state();
+            let backup = get_backup_save_bundle(&state.save_lists(), id)
+                .ok()?
+                .location
+                .clone();
+            (backup, source_save_directory(&state, source).ok()?)
+        };
+        let target_location = Path::new(&save_directory).join(backup_location.file_name()?);
+        let read = |location: &Path| {
+            SaveBundle::new(location)
+                .and_then(|save_bundle| save_bundle.get_data())
+                .map_err(|e| log::warn!("Failed to read {:?} to compare it: {}", location, e))
+                .ok()
+        };
+        Some(diff::diff_saves(
+            &read(&target_location)?,
+            &read(&backup_location)?,
+        ))
+    }
+
+    fn on_compare_saves(&self, before: BundleId, after: BundleId) {
+        match self.compare_saves(&before, &after) {
+            Ok(diff) => {
+                let comparison = SaveComparison {
+                    before_name: self.any_bundle_name(&before),
+                    after_name: self.any_bundle_name(&after),
+                    diff,
+                };
+                self.lock_state().show_comparison(comparison);
+            }
+            Err(err) => {
+                log::error!("Error occurred: {}", err);
+                self.notify(Notification::error(&err));
+            }
+        }
+        self.wake();
+    }
+
+    fn on_open_editor(&self, id: BundleId) {
+        match self.read_save(&id) {
+            Ok(save) => {
+                let name = self.bundle_name(BundleList::GameSaves, &id);
+                self.lock_state().open_editor(id, name, save);
+            }
+            Err(err) => {
+                log::error!("Error occurred: {}", err);
+                self.notify(Notification::error(&err));
             }
-            state.refresh_save_bundles();
+        }
+        self.wake();
+    }
+
+    fn on_save_edits(&self, id: BundleId, edits: SaveEdits, name: String) -> ControllerResult<()> {
+        // Renaming the save changes its ID, the editor follows it.
+        let new_id = match &edits.new_name {
+            Some(new_name) => self.renamed_id(&id, new_name),
+            None => Some(id.clone()),
+        };
+        let snapshot = self.apply_edits(&id, &edits)?;
+        if let Some(new_id) = new_id {
+            self.reload_editor(&id, new_id)?;
+        }
+        self.notify(edit_notification(&name, snapshot));
+        if let Some(new_name) = &edits.new_name {
+            self.notify(Notification::success(format!(
+                "Renamed {name} to {new_name}"
+            )));
         }
         Ok(())
     }
 
-    fn on_request_restore_backup(&self, name: String) -> ControllerResult<()> {
-        if let Ok(mut state) = self.state.lock() {
-            let backup_bundle = get_backup_save_bundle(&state, &name)
-                .map_err(|err_msg| ControllerError::RestoreBackupFailed(err_msg))?;
+    fn on_preview_patch(&self, id: BundleId, file: PathBuf) {
+        let preview = SavePatch::read(&file)
+            .map_err(|e| ControllerError::EditFailed(e.into()))
+            .and_then(|patch| {
+                let changes = self.preview_patch(&id, &patch)?;
+                Ok(PatchPreview {
+                    name: self.bundle_name(BundleList::GameSaves, &id),
+                    id,
+                    file,
+                    patch,
+                    changes,
+                })
+            });
+        match preview {
+            Ok(preview) => self.lock_state().preview_patch(preview),
+            Err(err) => {
+                log::error!("Error occurred: {}", err);
+                self.notify(Notification::error(&err));
+            }
+        }
+        self.wake();
+    }
 
-            let res =
-                fs_util::copy_directory(&backup_bundle.location, &state.save_directory, false);
-            if let Err(err) = res {
-                match err {
-                    fs_util::SaveManagerError::SaveExists => {
-                        state.confirm_restore_backup_name = Some(name);
-                    }
-                    error => {
-                        return Err(ControllerError::RestoreBackupFailed(error.to_string()));
-                    }
-                }
+    fn on_confirm_patch(&self, preview: PatchPreview) -> ControllerResult<()> {
+        let snapshot = self.patch_save(&preview.id, &preview.patch)?;
+        self.reload_editor(&preview.id, preview.id.clone())?;
+        self.notify(edit_notification(&preview.name, snapshot));
+        Ok(())
+    }
+
+    fn on_transplant_player(
+        &self,
+        source: &BundleId,
+        player: &str,
+        target: BundleId,
+        target_player: Option<&str>,
+        name: String,
+    ) -> ControllerResult<()> {
+        let snapshot = self.transplant_player(source, player, &target, target_player)?;
+        self.reload_editor(&target, target.clone())?;
+        self.notify(edit_notification(&name, snapshot));
+        Ok(())
+    }
+
+    /// Show the data written to a save in the editor, if the save is still open.
+    fn reload_editor(&self, id: &BundleId, new_id: BundleId) -> ControllerResult<()> {
+        let save = self.read_save(&new_id)?;
+        let name = self.bundle_name(BundleList::GameSaves, &new_id);
+        let mut state = self.lock_state();
+        if state.editor.as_ref().is_some_and(|editor| editor.id == *id) {
+            state.open_editor(new_id, name, save);
+        }
+        Ok(())
+    }
+
+    /// Apply a change of directories, reporting it to the user if it's refused.
+    fn change_directories(
+        &self,
+        change: impl FnOnce(&mut AppState) -> Result<(), fs_util::DirectoryProblem>,
+    ) {
+        let result = change(&mut self.lock_state());
+        match result {
+            Ok(()) => self.request_refresh(),
+            Err(problem) => {
+                let err = ControllerError::UnsafeDirectories(problem);
+                log::warn!("Directory change refused: {}", err);
+                self.notify(Notification::error(&err));
+            }
+        }
+    }
+
+    /// Use the directories chosen in the setup wizard, if the save directory holds saves.
+    fn on_finish_setup(&self) {
+        let mut state = self.lock_state();
+        let Some(setup) = state.setup.clone() else {
+            return;
+        };
+        let validation = validate_save_directory(&setup.save_directory);
+        if validation.is_err() {
+            state.choose_setup_save_directory(setup.save_directory, validation);
+            return;
+        }
+        if let Err(problem) = state.update_directories(setup.save_directory, setup.backup_directory)
+        {
+            drop(state);
+            self.notify(Notification::error(&ControllerError::UnsafeDirectories(
+                problem,
+            )));
+            return;
+        }
+        state.close_setup();
+        drop(state);
+        self.notify(Notification::success("Directories set up"));
+        self.request_refresh();
+    }
+
+    // == Operations == //
+    // Shared by the event loop and the command line interface.
+    // The state is only locked to read the inputs and to publish the results,
+    // files are copied without holding the lock.
+
+    /// Re-read the save and backup directories.
+    ///
+    /// Only bundles that changed since the last refresh are read again, in parallel.
+    /// Each bundle is published to the state as soon as it's read.
+    pub fn refresh_saves(&self) {
+        // Concurrent refreshes could publish outdated lists over each other.
+        let _scanning = self.scanning.lock().unwrap_or_else(PoisonError::into_inner);
+        self.scan_directories();
+    }
+
+    /// Forget the metadata index and read every save again.
+    pub fn rebuild_index(&self) {
+        let _scanning = self.scanning.lock().unwrap_or_else(PoisonError::into_inner);
+        self.lock_index().clear();
+        {
+            let state = self.lock_state();
+            state
+                .save_lists
+                .update(|lists| *lists = SaveLists::default());
+        }
+        self.scan_directories();
+    }
+
+    fn scan_directories(&self) {
+        self.scan_directory(BundleList::GameSaves);
+        self.scan_directory(BundleList::Backups);
+    }
+
+    /// Bring one of the bundle lists of the state up to date with its directory.
+    /// Results are dropped if the directory is changed during the scan.
+    ///
+    /// If the list is empty, the bundles of the metadata index are shown while the
+    /// directory is scanned, and the index is updated with the result.
+    fn scan_directory(&self, list: BundleList) {
+        let (directory, lists) = {
+            let state = self.lock_state();
+            (list.directory(&state).clone(), state.save_lists())
+        };
+        let mut known = list.bundles(&lists).to_vec();
+        if known.is_empty() {
+            known = self.lock_index().bundles_in(&directory);
+            self.publish_bundles(list, &directory, |save_bundles| {
+                save_bundles.clone_from(&known)
+            });
+        }
+
+        let plan = scan::plan_scan(&directory, &known);
+        self.publish_bundles(list, &directory, |save_bundles| {
+            *save_bundles = plan.unchanged
+        });
+        scan::read_save_bundles(&plan.to_read, &known, |location, result| match result {
+            Ok(save_bundle) => self.publish_bundles(list, &directory, |save_bundles| {
+                scan::insert_sorted(save_bundles, save_bundle)
+            }),
+            Err(e) => log::error!(e:err; "Failed to read save bundle {:?}", location),
+        });
+
+        let lists = {
+            let state = self.lock_state();
+            if *list.directory(&state) != directory {
+                return;
             }
-            state.refresh_save_bundles();
+            state.save_lists()
+        };
+        let save_bundles = list.bundles(&lists);
+        let mut index = self.lock_index();
+        if index.update(&directory, save_bundles)
+            && let Some(index_path) = &self.index_path
+            && let Err(e) = index.save(index_path)
+        {
+            log::warn!(e:err; "Failed to save the metadata index.");
         }
+    }
+
+    /// Publish a new snapshot of a bundle list, made by applying `update` to the current list,
+    /// unless the list's directory is no longer `directory`.
+    ///
+    /// The state stays locked while publishing, so that a snapshot of the previous
+    /// directory can't be published after the directory is changed.
+    fn publish_bundles(
+        &self,
+        list: BundleList,
+        directory: &str,
+        update: impl FnOnce(&mut Vec<SaveBundle>),
+    ) {
+        let state = self.lock_state();
+        if *list.directory(&state) == directory {
+            state.save_lists.update(|lists| {
+                let mut save_bundles = list.bundles(lists).to_vec();
+                update(&mut save_bundles);
+                *list.bundles_mut(lists) = save_bundles.into();
+            });
+        }
+        drop(state);
+        self.wake();
+    }
+
+    /// Backup a game save of the active source, reporting the copied files to `progress`.
+    /// The backup records the source it was made from.
+    pub fn backup_save(&self, id: &BundleId, progress: &JobProgress) -> ControllerResult<()> {
+        let (location, backup_directory, source) = {
+            let state = self.lock_state();
+            let lists = state.save_lists();
+            let save_bundle =
+                get_game_save_bundle(&lists, id).map_err(ControllerError::BackupFailed)?;
+            ensure_safe_directories(
+                &state.save_directory,
+                &state.backup_directory,
+                &state.backup_directory,
+            )?;
+            (
+                save_bundle.location.clone(),
+                state.backup_directory.clone(),
+                state.active_source().name.clone(),
+            )
+        };
+        add_directory_sizes(progress, [&location]).map_err(ControllerError::BackupFailed)?;
+        let result = backup_directory_with_source(&location, &backup_directory, &source, progress);
+        self.refresh_saves();
+        result.map_err(|e| ControllerError::BackupFailed(e.into()))
+    }
+
+    /// Backup all saves of the active source, reporting the copied files to `progress`.
+    ///
+    /// Stops at the first failure, saves backed up before it are kept.
+    pub fn backup_all(&self, progress: &JobProgress) -> ControllerResult<()> {
+        let (locations, backup_directory, source) = {
+            let state = self.lock_state();
+            let locations: Vec<PathBuf> = state
+                .save_lists()
+                .game_save_bundles
+                .iter()
+                .map(|save_bundle| save_bundle.location.clone())
+                .collect();
+            ensure_safe_directories(
+                &state.save_directory,
+                &state.backup_directory,
+                &state.backup_directory,
+            )?;
+            (
+                locations,
+                state.backup_directory.clone(),
+                state.active_source().name.clone(),
+            )
+        };
+        add_directory_sizes(progress, &locations).map_err(ControllerError::BackupFailed)?;
+        let result = locations.iter().try_for_each(|location| {
+            backup_directory_with_source(location, &backup_directory, &source, progress)
+        });
+        self.refresh_saves();
+        result.map_err(|e| ControllerError::BackupFailed(e.into()))
+    }
+
+    /// Restore a backup to the game save directory of the active source,
+    /// reporting the copied files to `progress`.
+    ///
+    /// If a game save with the same name exists, it's only overwritten when `overwrite` is true,
+    /// otherwise `ControllerError::SaveExists` is returned.
+    pub fn restore_backup(
+        &self,
+        id: &BundleId,
+        overwrite: bool,
+        progress: &JobProgress,
+    ) -> ControllerResult<()> {
+        let source = self.lock_state().active_source().name.clone();
+        self.restore_backup_to(id, &source, overwrite, progress)
+    }
+
+    /// Restore a backup of the active source's library to the game save directory of
+    /// the source named `source`, see `restore_backup`.
+    pub fn restore_backup_to(
+        &self,
+        id: &BundleId,
+        source: &str,
+        overwrite: bool,
+        progress: &JobProgress,
+    ) -> ControllerResult<()> {
+        let (location, name, save_directory) = {
+            self.ensure_game_not_running()?;
+            let state = self.lock_state();
+            let lists = state.save_lists();
+            let backup_bundle =
+                get_backup_save_bundle(&lists, id).map_err(ControllerError::RestoreBackupFailed)?;
+            let save_directory = source_save_directory(&state, source)?;
+            ensure_safe_directories(&save_directory, &state.backup_directory, &save_directory)?;
+            (
+                backup_bundle.location.clone(),
+                backup_bundle.name.clone(),
+                save_directory,
+            )
+        };
+        add_directory_sizes(progress, [&location]).map_err(ControllerError::RestoreBackupFailed)?;
+
+        let res =
+            fs_util::copy_directory_with_progress(&location, &save_directory, overwrite, progress);
+        match res {
+            Err(fs_util::SaveManagerError::SaveExists) => {
+                return Err(ControllerError::SaveExists(name));
+            }
+            Err(err) => {
+                self.refresh_saves();
+                return Err(ControllerError::RestoreBackupFailed(err.into()));
+            }
+            Ok(()) => {}
+        }
+        self.refresh_saves();
         Ok(())
     }
 
-    fn on_confirm_restore_backup(&self, name: String) -> ControllerResult<()> {
-        if let Ok(mut state) = self.state.lock() {
-            let backup_bundle = get_backup_save_bundle(&state, &name)
-                .map_err(|err_msg| ControllerError::RestoreBackupFailed(err_msg))?;
+    /// Copy a game save or a backup to the game save directory of the active source as a new
+    /// save named `REPO_SAVE_<now>`, renaming its team if `team_name` is given.
+    /// The original is left untouched, see `edit::clone_save_bundle`.
+    ///
+    /// Returns the name of the new save.
+    pub fn clone_save(
+        &self,
+        id: &BundleId,
+        team_name: Option<&str>,
+        progress: &JobProgress,
+    ) -> ControllerResult<String> {
+        let (location, save_directory) = {
+            let state = self.lock_state();
+            let lists = state.save_lists();
+            let save_bundle = lists
+                .game_save(id)
+                .or_else(|| lists.backup(id))
+                .ok_or_else(|| {
+                    ControllerError::CloneFailed(FailureCause::BundleNotFound(id.to_string()))
+                })?;
+            ensure_safe_directories(
+                &state.save_directory,
+                &state.backup_directory,
+                &state.save_directory,
+            )?;
+            (save_bundle.location.clone(), state.save_directory.clone())
+        };
+        add_directory_sizes(progress, [&location]).map_err(ControllerError::CloneFailed)?;
+
+        let new_name = save_bundle::available_save_name(&save_directory);
+        let result = edit::clone_save_bundle(
+            &location,
+            &save_directory,
+            &new_name,
+            |save| match team_name {
+                Some(team_name) => edit::set_team_name(save, team_name),
+                None => Ok(()),
+            },
+            progress,
+        );
+        self.refresh_saves();
+        result.map_err(|e| ControllerError::CloneFailed(e.into()))?;
+        Ok(new_name)
+    }
+
+    fn template_directory(&self) -> PathBuf {
+        template::template_directory(&self.lock_state().backup_directory)
+    }
+
+    /// The templates of the backup directory of the active source, see `template::list_templates`.
+    pub fn list_templates(&self) -> Vec<TemplateInfo> {
+        template::list_templates(self.template_directory())
+    }
+
+    /// Keep a game save or a backup as a template named `name`, without its players if
+    /// `strip_players` is set, see `template::capture_template`.
+    pub fn capture_template(
+        &self,
+        id: &BundleId,
+        name: &str,
+        strip_players: bool,
+    ) -> ControllerResult<PathBuf> {
+        let save = self.read_bundle(id)?;
+        template::capture_template(&save, self.template_directory(), name, strip_players)
+            .map_err(|e| ControllerError::TemplateFailed(e.into()))
+    }
 
-            let res = fs_util::copy_directory(&backup_bundle.location, &state.save_directory, true);
-            if let Err(err) = res {
-                return Err(ControllerError::RestoreBackupFailed(err.to_string()));
+    pub fn delete_template(&self, name: &str) -> ControllerResult<()> {
+        template::delete_template(self.template_directory(), name)
+            .map_err(|e| ControllerError::TemplateFailed(e.into()))
+    }
+
+    /// Write a new save named `REPO_SAVE_<now>` to the game save directory of the active source
+    /// from a template, see `template::new_save_from_template`.
+    ///
+    /// Returns the name of the new save.
+    pub fn new_save_from_template(
+        &self,
+        name: &str,
+        options: &NewSaveOptions,
+    ) -> ControllerResult<String> {
+        let save_directory = {
+            let state = self.lock_state();
+            ensure_safe_directories(
+                &state.save_directory,
+                &state.backup_directory,
+                &state.save_directory,
+            )?;
+            state.save_directory.clone()
+        };
+        let new_name = save_bundle::available_save_name(&save_directory);
+        let result = template::read_template(self.template_directory(), name).and_then(|save| {
+            template::new_save_from_template(&save, &save_directory, &new_name, options)
+        });
+        self.refresh_saves();
+        result.map_err(|e| ControllerError::TemplateFailed(e.into()))?;
+        Ok(new_name)
+    }
+
+    /// Delete a backup from the backup directory.
+    pub fn delete_backup(&self, id: &BundleId) -> ControllerResult<()> {
+        let location = {
+            self.ensure_game_not_running()?;
+            let state = self.lock_state();
+            get_backup_save_bundle(&state.save_lists(), id)
+                .map_err(ControllerError::DeleteBackupFailed)?
+                .location
+                .clone()
+        };
+        let result = std::fs::remove_dir_all(&location)
+            .and_then(
+                |()| match std::fs::remove_file(save_bundle::source_file(&location)) {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                    _ => Ok(()),
+                },
+            )
+            .map_err(|e| {
+                ControllerError::DeleteBackupFailed(fs_util::SaveManagerError::from(e).into())
+            });
+        self.refresh_saves();
+        result
+    }
+
+    /// Read the data of a game save of the active source.
+    pub fn read_save(&self, id: &BundleId) -> ControllerResult<SaveGame> {
+        let save_bundle = get_game_save_bundle(&self.lock_state().save_lists(), id)
+            .map_err(ControllerError::EditFailed)?
+            .clone();
+        save_bundle
+            .get_data()
+            .map_err(|e| ControllerError::EditFailed(EditError::from(e).into()))
+    }
+
+    /// Read the data of a game save or a backup of the active source.
+    pub fn read_bundle(&self, id: &BundleId) -> ControllerResult<SaveGame> {
+        self.read_any_bundle(id)
+            .map_err(ControllerError::EditFailed)
+    }
+
+    fn read_any_bundle(&self, id: &BundleId) -> Result<SaveGame, FailureCause> {
+        let save_bundle = {
+            let lists = self.lock_state().save_lists();
+            lists
+                .game_save(id)
+                .or_else(|| lists.backup(id))
+                .cloned()
+                .ok_or_else(|| FailureCause::BundleNotFound(id.to_string()))?
+        };
+        save_bundle
+            .get_data()
+            .map_err(|e| EditError::from(e).into())
+    }
+
+    /// What changes from a game save or backup of the active source to another,
+    /// see `diff::diff_saves`.
+    pub fn compare_saves(&self, before: &BundleId, after: &BundleId) -> ControllerResult<SaveDiff> {
+        let before = self
+            .read_any_bundle(before)
+            .map_err(ControllerError::CompareFailed)?;
+        let after = self
+            .read_any_bundle(after)
+            .map_err(ControllerError::CompareFailed)?;
+        Ok(diff::diff_saves(&before, &after))
+    }
+
+    /// Copy the upgrades of a player of a game save or backup to a player of a game save,
+    /// see `edit::transplant_player` and `edit_save`.
+    ///
+    /// Players are given by name or Steam ID, the target player defaults to the same Steam ID
+    /// as the source player.
+    pub fn transplant_player(
+        &self,
+        source: &BundleId,
+        player: &str,
+        target: &BundleId,
+        target_player: Option<&str>,
+    ) -> ControllerResult<Option<PathBuf>> {
+        let source_save = self.read_bundle(source)?;
+        let steam_id = edit::find_player(&source_save, player).unwrap_or_else(|| player.into());
+        self.edit_save(target, |save| {
+            let target_id = match target_player {
+                Some(target_player) => {
+                    edit::find_player(save, target_player).unwrap_or_else(|| target_player.into())
+                }
+                None => steam_id.clone(),
+            };
+            edit::transplant_player(&source_save, &steam_id, save, &target_id)
+        })
+    }
+
+    /// The changes a patch would make to a game save, nothing is written.
+    pub fn preview_patch(
+        &self,
+        id: &BundleId,
+        patch: &SavePatch,
+    ) -> ControllerResult<Vec<JsonChange>> {
+        let save = self.read_save(id)?;
+        let mut patched = save.clone();
+        patch
+            .apply(&mut patched)
+            .and_then(|()| patch::json_changes(&save, &patched))
+            .map_err(|e| ControllerError::EditFailed(e.into()))
+    }
+
+    /// Apply a patch to a game save, see `SavePatch::apply` and `edit_save`.
+    pub fn patch_save(
+        &self,
+        id: &BundleId,
+        patch: &SavePatch,
+    ) -> ControllerResult<Option<PathBuf>> {
+        self.edit_save(id, |save| patch.apply(save))
+    }
+
+    /// Apply `edit` to a game save of the active source and write it back.
+    ///
+    /// The original save is first copied to the snapshot directory of the backup directory
+    /// (see `edit::edit_save_bundle`). Returns the location of the copy, or None if `edit`
+    /// left the save unchanged.
+    pub fn edit_save(
+        &self,
+        id: &BundleId,
+        edit: impl FnOnce(&mut SaveGame) -> Result<(), EditError>,
+    ) -> ControllerResult<Option<PathBuf>> {
+        self.edit_save_bundle(id, edit, None)
+    }
+
+    /// Apply the changes made in the editor to a game save of the active source, see `edit_save`.
+    /// If the changes give the save a new name, its ID changes too, see `renamed_id`.
+    pub fn apply_edits(
+        &self,
+        id: &BundleId,
+        edits: &SaveEdits,
+    ) -> ControllerResult<Option<PathBuf>> {
+        self.edit_save_bundle(id, |save| edits.apply(save), edits.new_name.as_deref())
+    }
+
+    fn edit_save_bundle(
+        &self,
+        id: &BundleId,
+        edit: impl FnOnce(&mut SaveGame) -> Result<(), EditError>,
+        new_name: Option<&str>,
+    ) -> ControllerResult<Option<PathBuf>> {
+        let (mut save_bundle, backup_directory) = {
+            self.ensure_game_not_running()?;
+            let state = self.lock_state();
+            let lists = state.save_lists();
+            let save_bundle = get_game_save_bundle(&lists, id)
+                .map_err(ControllerError::EditFailed)?
+                .clone();
+            ensure_safe_directories(
+                &state.save_directory,
+                &state.backup_directory,
+                &state.backup_directory,
+            )?;
+            (save_bundle, state.backup_directory.clone())
+        };
+        let result = edit::edit_save_bundle(
+            &mut save_bundle,
+            edit::snapshot_directory(&backup_directory),
+            edit,
+            new_name,
+        );
+        self.refresh_saves();
+        result.map_err(|e| ControllerError::EditFailed(e.into()))
+    }
+
+    /// Check the game saves and backups of the active source for suspicious values,
+    /// see `lint::lint_directory`.
+    pub fn check_saves(&self) -> SaveCheck {
+        let Some((save_directory, backup_directory)) = self.directories() else {
+            return SaveCheck::default();
+        };
+        SaveCheck {
+            game_saves: lint::lint_directory(&save_directory),
+            backups: lint::lint_directory(&backup_directory),
+        }
+    }
+
+    /// Fix the issues of a game save of the active source that can be fixed safely,
+    /// see `lint::fix_bundle`. Backups are left as they were made.
+    ///
+    /// The original save is first copied to the snapshot directory of the backup directory.
+    /// Returns the location of the copy, or None if there was nothing to fix.
+    pub fn fix_save(&self, location: &Path) -> ControllerResult<Option<PathBuf>> {
+        let backup_directory = {
+            self.ensure_game_not_running()?;
+            let state = self.lock_state();
+            if location.parent() != Some(Path::new(&state.save_directory)) {
+                return Err(ControllerError::EditFailed(FailureCause::BundleNotFound(
+                    location.display().to_string(),
+                )));
             }
-            state.confirm_restore_backup_name = None;
-            state.refresh_save_bundles();
+            ensure_safe_directories(
+                &state.save_directory,
+                &state.backup_directory,
+                &state.backup_directory,
+            )?;
+            state.backup_directory.clone()
+        };
+        let result = lint::fix_bundle(location, edit::snapshot_directory(&backup_directory));
+        self.refresh_saves();
+        result.map_err(|e| ControllerError::EditFailed(e.into()))
+    }
+
+    /// ID a game save of the active source has once renamed to `new_name`.
+    pub fn renamed_id(&self, id: &BundleId, new_name: &str) -> Option<BundleId> {
+        let lists = self.lock_state().save_lists();
+        let save_bundle = lists.game_save(id)?;
+        Some(BundleId::from_location(
+            save_bundle.location.with_file_name(new_name),
+        ))
+    }
+
+    /// Check that every bundle in the save and backup directories can be read.
+    ///
+    /// Fails if one of the directories can't be read, eg. if it doesn't exist.
+    pub fn verify_saves(
+        &self,
+    ) -> ControllerResult<Vec<(PathBuf, Result<SaveBundle, SaveBundleError>)>> {
+        let (save_directory, backup_directory) = {
+            let state = self.lock_state();
+            (state.save_directory.clone(), state.backup_directory.clone())
+        };
+        let mut checks = Vec::new();
+        for directory in [save_directory, backup_directory] {
+            let bundles = save_bundle::check_save_bundles(&directory).map_err(|e| {
+                let e = std::io::Error::new(e.kind(), format!("{directory}: {e}"));
+                ControllerError::VerifyFailed(fs_util::SaveManagerError::from(e).into())
+            })?;
+            checks.extend(bundles);
+        }
+        Ok(checks)
+    }
+
+    /// The save and backup directories, None if the state is poisoned.
+    fn directories(&self) -> Option<(String, String)> {
+        let state = self.state.lock().ok()?;
+        Some((state.save_directory.clone(), state.backup_directory.clone()))
+    }
+
+    fn lock_index(&self) -> MutexGuard<'_, MetadataIndex> {
+        self.index.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the state, recovering it if a thread panicked while holding the lock.
+    /// Operations only read plain values from it, which can't be left half updated.
+    fn lock_state(&self) -> MutexGuard<'_, AppState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Refuse destructive operations while the game is running, as it would overwrite
+    /// the changes with the save it holds in memory.
+    /// The result is stored in the state so that the confirmation popups can warn the user.
+    ///
+    /// The processes are listed before locking the state, it takes a while.
+    fn ensure_game_not_running(&self) -> ControllerResult<()> {
+        let game_running = (self.game_probe)();
+        self.lock_state().game_running = game_running;
+        if game_running {
+            return Err(ControllerError::GameRunning);
         }
         Ok(())
     }
@@ -206,25 +1606,438 @@ impl AppController {
 
 // == Helper functions == //
 
-/// Extract backup save bundle from state, returns Err with message if not found.
+/// One of the bundle lists of the state.
+#[derive(Debug, Clone, Copy)]
+enum BundleList {
+    GameSaves,
+    Backups,
+}
+
+impl BundleList {
+    fn directory(self, state: &AppState) -> &String {
+        match self {
+            BundleList::GameSaves => &state.save_directory,
+            BundleList::Backups => &state.backup_directory,
+        }
+    }
+
+    fn bundles(self, lists: &SaveLists) -> &[SaveBundle] {
+        match self {
+            BundleList::GameSaves => &lists.game_save_bundles,
+            BundleList::Backups => &lists.backup_save_bundles,
+        }
+    }
+
+    fn bundles_mut(self, lists: &mut SaveLists) -> &mut Arc<[SaveBundle]> {
+        match self {
+            BundleList::GameSaves => &mut lists.game_save_bundles,
+            BundleList::Backups => &mut lists.backup_save_bundles,
+        }
+    }
+}
+
+/// The game save directory of a source.
+fn source_save_directory(state: &AppState, source: &str) -> ControllerResult<String> {
+    // The directories of the active source may be overridden, eg. on the command line.
+    if state.active_source().name == source {
+        Ok(state.save_directory.clone())
+    } else {
+        Ok(state
+            .source(source)
+            .ok_or_else(|| ControllerError::SourceNotFound(source.to_string()))?
+            .save_directory
+            .clone())
+    }
+}
+
+/// Copy a game save to a backup directory and record the source it was made from.
+/// The backup is kept if the source can't be recorded, it's only informative.
+fn backup_directory_with_source(
+    location: &std::path::Path,
+    backup_directory: &str,
+    source: &str,
+    progress: &JobProgress,
+) -> Result<(), fs_util::SaveManagerError> {
+    fs_util::copy_directory_with_progress(location, backup_directory, true, progress)?;
+    if let Some(name) = location.file_name()
+        && let Err(e) =
+            save_bundle::write_source(std::path::Path::new(backup_directory).join(name), source)
+    {
+        log::warn!(e:err; "Failed to record the source of backup {:?}", name);
+    }
+    Ok(())
+}
+
+/// Refuse to copy to `destination` if the game save and backup directories conflict,
+/// or if it isn't writable.
+fn ensure_safe_directories(
+    save_directory: &str,
+    backup_directory: &str,
+    destination: &str,
+) -> ControllerResult<()> {
+    match fs_util::check_directory_conflict(save_directory, backup_directory)
+        .or_else(|| fs_util::check_writable(destination))
+    {
+        Some(problem) => Err(ControllerError::UnsafeDirectories(problem)),
+        None => Ok(()),
+    }
+}
+
+/// Tell the user where the original of an edited save is kept.
+fn edit_notification(name: &str, snapshot: Option<PathBuf>) -> Notification {
+    match snapshot {
+        Some(snapshot) => Notification::success(format!(
+            "Saved {name}, the original is kept in {}",
+            snapshot.display()
+        )),
+        None => Notification::success(format!("Nothing to save, {name} is unchanged")),
+    }
+}
+
+/// Validate a save directory for the setup wizard, errors are kept as messages to show.
+fn validate_save_directory(directory: &str) -> Result<Vec<String>, String> {
+    discovery::validate_save_directory(directory).map_err(|e| e.to_string())
+}
+
+/// Announce the size of the directories about to be copied to `progress`.
+fn add_directory_sizes(
+    progress: &JobProgress,
+    directories: impl IntoIterator<Item = impl AsRef<std::path::Path>>,
+) -> Result<(), FailureCause> {
+    for directory in directories {
+        let (files, bytes) = fs_util::directory_size(directory)?;
+        progress.add_total(files, bytes);
+    }
+    Ok(())
+}
+
+/// Extract backup save bundle from the save lists, returns Err if not found.
 fn get_backup_save_bundle<'a>(
-    state: &'a AppState,
-    name: &String,
-) -> Result<&'a SaveBundle, String> {
-    let backup_bundle = state
-        .backup_save_bundles
-        .iter()
-        .find(|s| s.name == *name)
-        .ok_or(format!("Backup Save bundle with name `{name}` not found"))?;
-    Ok(backup_bundle)
-}
-
-/// Extract game save bundle from state, returns Err with message if not found.
-fn get_game_save_bundle<'a>(state: &'a AppState, name: &String) -> Result<&'a SaveBundle, String> {
-    let game_save_bundle = state
-        .game_save_bundles
-        .iter()
-        .find(|s| s.name == *name)
-        .ok_or(format!("Game Save bundle with name `{name}` not found"))?;
-    Ok(game_save_bundle)
+    lists: &'a SaveLists,
+    id: &BundleId,
+) -> Result<&'a SaveBundle, FailureCause> {
+    lists
+        .backup(id)
+        .ok_or(FailureCause::BundleNotFound(id.to_string()))
+}
+
+/// Extract game save bundle from the save lists, returns Err if not found.
+fn get_game_save_bundle<'a>(
+    lists: &'a SaveLists,
+    id: &BundleId,
+) -> Result<&'a SaveBundle, FailureCause> {
+    lists
+        .game_save(id)
+        .ok_or(FailureCause::BundleNotFound(id.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::SaveSource;
+    use crate::repo::save::fixtures;
+    use tempfile::{TempDir, tempdir};
+
+    /// A controller with the game save `REPO_SAVE_1` and a backup of it, lists loaded.
+    fn controller_with_backup() -> (TempDir, AppController) {
+        let root = tempdir().unwrap();
+        let save_directory = root.path().join("saves");
+        let backup_directory = root.path().join("backups");
+        fixtures::save_bundle_dir(&save_directory, "REPO_SAVE_1");
+        fixtures::save_bundle_dir(&backup_directory, "REPO_SAVE_1");
+        let state = AppState::new(
+            save_directory.to_string_lossy(),
+            backup_directory.to_string_lossy(),
+        );
+        let controller = AppController::new(Arc::new(Mutex::new(state))).with_game_probe(|| false);
+        controller.refresh_saves();
+        (root, controller)
+    }
+
+    fn game_save_id(root: &TempDir) -> BundleId {
+        BundleId::from_location(root.path().join("saves").join("REPO_SAVE_1"))
+    }
+
+    fn backup_id(root: &TempDir) -> BundleId {
+        BundleId::from_location(root.path().join("backups").join("REPO_SAVE_1"))
+    }
+
+    fn save_file(root: &TempDir) -> PathBuf {
+        root.path().join("saves/REPO_SAVE_1/REPO_SAVE_1.es3")
+    }
+
+    mod verify_saves {
+        use super::*;
+
+        #[test]
+        fn reads_every_bundle() {
+            // Arrange
+            let (_root, controller) = controller_with_backup();
+
+            // Act
+            let checks = controller.verify_saves().unwrap();
+
+            // Assert
+            assert_eq!(checks.len(), 2);
+            assert!(checks.iter().all(|(_, save_bundle)| save_bundle.is_ok()));
+        }
+
+        #[test]
+        fn fails_on_missing_directory() {
+            // Arrange
+            let (root, controller) = controller_with_backup();
+            std::fs::remove_dir_all(root.path().join("backups")).unwrap();
+
+            // Act
+            let result = controller.verify_saves();
+
+            // Assert
+            assert!(matches!(result, Err(ControllerError::VerifyFailed(_))));
+        }
+    }
+
+    mod restore_backup_to {
+        use super::*;
+
+        #[test]
+        fn refuses_overlapping_directories() {
+            // Arrange
+            let (root, controller) = controller_with_backup();
+            let nested = root.path().join("backups").join("saves");
+            controller
+                .lock_state()
+                .sources
+                .push(SaveSource::new("Nested", nested.to_string_lossy()));
+
+            // Act
+            let result = controller.restore_backup_to(
+                &backup_id(&root),
+                "Nested",
+                true,
+                &JobProgress::default(),
+            );
+
+            // Assert
+            assert!(matches!(
+                result,
+                Err(ControllerError::UnsafeDirectories(
+                    fs_util::DirectoryProblem::SavesInsideBackup
+                ))
+            ));
+            assert!(!nested.exists());
+        }
+
+        #[test]
+        fn restores_to_another_source() {
+            // Arrange
+            let (root, controller) = controller_with_backup();
+            let other = root.path().join("other");
+            controller
+                .lock_state()
+                .sources
+                .push(SaveSource::new("Other", other.to_string_lossy()));
+
+            // Act
+            let result = controller.restore_backup_to(
+                &backup_id(&root),
+                "Other",
+                false,
+                &JobProgress::default(),
+            );
+
+            // Assert
+            assert!(result.is_ok());
+            assert!(other.join("REPO_SAVE_1/REPO_SAVE_1.es3").is_file());
+        }
+
+        #[test]
+        fn refuses_unknown_source() {
+            // Arrange
+            let (root, controller) = controller_with_backup();
+
+            // Act
+            let result = controller.restore_backup_to(
+                &backup_id(&root),
+                "Unknown",
+                true,
+                &JobProgress::default(),
+            );
+
+            // Assert
+            assert!(matches!(result, Err(ControllerError::SourceNotFound(_))));
+        }
+    }
+
+    mod on_rename_source {
+        use super::*;
+
+        #[test]
+        fn moves_own_library_and_records() {
+            // Arrange
+            let (root, controller) = controller_with_backup();
+            let library = {
+                let mut state = controller.lock_state();
+                state.set_own_backup_library(true).unwrap();
+                PathBuf::from(&state.backup_directory)
+            };
+            fixtures::save_bundle_dir(&library, "REPO_SAVE_2");
+            save_bundle::write_source(library.join("REPO_SAVE_2"), "Default").unwrap();
+            save_bundle::write_source(root.path().join("backups/REPO_SAVE_1"), "Default").unwrap();
+
+            // Act
+            controller.on_rename_source(0, "Deck").unwrap();
+
+            // Assert
+            let state = controller.lock_state();
+            let moved = root.path().join("backups (Deck)");
+            assert_eq!(PathBuf::from(&state.backup_directory), moved);
+            assert!(!library.exists());
+            assert_eq!(
+                save_bundle::read_source(moved.join("REPO_SAVE_2")).as_deref(),
+                Some("Deck")
+            );
+            assert_eq!(
+                save_bundle::read_source(root.path().join("backups/REPO_SAVE_1")).as_deref(),
+                Some("Deck")
+            );
+        }
+
+        #[test]
+        fn refuses_when_library_is_in_the_way() {
+            // Arrange
+            let (root, controller) = controller_with_backup();
+            controller
+                .lock_state()
+                .set_own_backup_library(true)
+                .unwrap();
+            std::fs::create_dir_all(root.path().join("backups (Default)")).unwrap();
+            std::fs::create_dir_all(root.path().join("backups (Deck)")).unwrap();
+
+            // Act
+            let result = controller.on_rename_source(0, "Deck");
+
+            // Assert
+            assert!(matches!(
+                result,
+                Err(ControllerError::RenameSourceFailed(_))
+            ));
+            assert_eq!(controller.lock_state().active_source().name, "Default");
+        }
+    }
+
+    mod restore_backup {
+        use super::*;
+
+        #[test]
+        fn overwrite_requires_confirmation() {
+            // Arrange
+            let (root, controller) = controller_with_backup();
+            std::fs::write(save_file(&root), "changed").unwrap();
+
+            // Act
+            let refused =
+                controller.restore_backup(&backup_id(&root), false, &JobProgress::default());
+            let kept = std::fs::read(save_file(&root)).unwrap();
+            let confirmed =
+                controller.restore_backup(&backup_id(&root), true, &JobProgress::default());
+
+            // Assert
+            assert!(
+                matches!(refused, Err(ControllerError::SaveExists(name)) if name == "REPO_SAVE_1")
+            );
+            assert_eq!(kept, b"changed");
+            assert!(confirmed.is_ok());
+            assert_ne!(std::fs::read(save_file(&root)).unwrap(), b"changed");
+        }
+
+        #[test]
+        fn cancelled_job_leaves_destination_untouched() {
+            // Arrange
+            let (root, controller) = controller_with_backup();
+            std::fs::write(save_file(&root), "changed").unwrap();
+            let progress = JobProgress::default();
+            progress.cancel();
+
+            // Act
+            let result = controller.restore_backup(&backup_id(&root), true, &progress);
+
+            // Assert
+            assert!(matches!(
+                result,
+                Err(ControllerError::RestoreBackupFailed(
+                    FailureCause::FileSystem(fs_util::SaveManagerError::Cancelled)
+                ))
+            ));
+            assert_eq!(std::fs::read(save_file(&root)).unwrap(), b"changed");
+        }
+    }
+
+    mod edit_save {
+        use super::*;
+
+        #[test]
+        fn writes_snapshot() {
+            // Arrange
+            let (root, controller) = controller_with_backup();
+            let original = std::fs::read(save_file(&root)).unwrap();
+
+            // Act
+            let snapshot = controller
+                .edit_save(&game_save_id(&root), |save| {
+                    edit::set_team_name(save, "Team A")
+                })
+                .unwrap();
+
+            // Assert
+            let snapshot = snapshot.unwrap();
+            assert!(snapshot.starts_with(root.path().join("backups")));
+            assert_eq!(
+                std::fs::read(snapshot.join("REPO_SAVE_1.es3")).unwrap(),
+                original
+            );
+            let save = controller.read_save(&game_save_id(&root)).unwrap();
+            assert_eq!(save.team_name.value, "Team A");
+        }
+    }
+
+    mod renamed_id {
+        use super::*;
+
+        #[test]
+        fn follows_new_name() {
+            // Arrange
+            let (root, controller) = controller_with_backup();
+
+            // Act
+            let id = controller.renamed_id(&game_save_id(&root), "REPO_SAVE_2");
+
+            // Assert
+            assert_eq!(
+                id,
+                Some(BundleId::from_location(
+                    root.path().join("saves").join("REPO_SAVE_2")
+                ))
+            );
+        }
+    }
+
+    mod fix_save {
+        use super::*;
+
+        #[test]
+        fn refuses_bundle_outside_game_saves() {
+            // Arrange
+            let (root, controller) = controller_with_backup();
+            let backup = root.path().join("backups").join("REPO_SAVE_1");
+
+            // Act
+            let result = controller.fix_save(&backup);
+
+            // Assert
+            assert!(matches!(
+                result,
+                Err(ControllerError::EditFailed(FailureCause::BundleNotFound(_)))
+            ));
+        }
+    }
 }
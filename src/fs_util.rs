@@ -4,14 +4,159 @@ use std::path::{Path, PathBuf};
 use std::{fs, io};
 use thiserror::Error;
 
+use crate::jobs::JobProgress;
+
 #[derive(Debug, Error)]
 pub enum SaveManagerError {
     #[error("Save already exists")]
     SaveExists,
     #[error("IO error: {0}")]
     IOError(#[from] io::Error),
-    #[error("FsExtra error: {0}")]
-    FsExtraError(#[from] fs_extra::error::Error),
+    #[error("Operation cancelled")]
+    Cancelled,
+}
+
+impl SaveManagerError {
+    /// What the user can do to fix the error.
+    pub fn hint(&self) -> String {
+        let kind = match self {
+            SaveManagerError::SaveExists => {
+                return "A save with the same name already exists in the destination.".to_string();
+            }
+            SaveManagerError::Cancelled => {
+                return "Nothing was changed, start the operation again to complete it."
+                    .to_string();
+            }
+            SaveManagerError::IOError(e) => e.kind(),
+        };
+        match kind {
+            io::ErrorKind::NotFound => {
+                "Check that the game save and backup directories exist, then refresh the saves."
+            }
+            io::ErrorKind::PermissionDenied => {
+                "Check that you have permission to write to the game save and backup directories."
+            }
+            io::ErrorKind::ReadOnlyFilesystem => {
+                "The directory is on a read-only drive, choose another directory."
+            }
+            io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded => {
+                "The drive is full, free some space and try again."
+            }
+            io::ErrorKind::AlreadyExists => {
+                "A file with the same name is in the way, move or delete it and try again."
+            }
+            _ => "Check that the game save and backup directories are accessible and try again.",
+        }
+        .to_string()
+    }
+}
+
+/// A configuration of the game save and backup directories that would make operations
+/// misbehave, eg. backups copied into the game save directory.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum DirectoryProblem {
+    #[error("The game save and backup directories are the same")]
+    SameDirectory,
+    #[error("The backup directory is inside the game save directory")]
+    BackupInsideSaves,
+    #[error("The game save directory is inside the backup directory")]
+    SavesInsideBackup,
+    #[error("`{0}` is not writable")]
+    NotWritable(PathBuf),
+    #[error("`{0}` is on a read-only drive")]
+    ReadOnly(PathBuf),
+}
+
+impl DirectoryProblem {
+    /// True if the directories can't be used together at all, other problems only
+    /// prevent writing to one of them.
+    pub fn is_conflict(&self) -> bool {
+        matches!(
+            self,
+            DirectoryProblem::SameDirectory
+                | DirectoryProblem::BackupInsideSaves
+                | DirectoryProblem::SavesInsideBackup
+        )
+    }
+
+    /// What the user can do to fix the problem.
+    pub fn hint(&self) -> String {
+        match self {
+            DirectoryProblem::SameDirectory
+            | DirectoryProblem::BackupInsideSaves
+            | DirectoryProblem::SavesInsideBackup => {
+                "Choose a backup directory outside of the game save directory.".to_string()
+            }
+            DirectoryProblem::NotWritable(_) => {
+                "Check the permissions of the directory, or choose another one.".to_string()
+            }
+            DirectoryProblem::ReadOnly(_) => {
+                "Choose a directory on another drive, or mount the drive read-write.".to_string()
+            }
+        }
+    }
+}
+
+/// Check that the game save and backup directories can be used together: they must not be
+/// the same directory or be nested, symbolic links included.
+/// Directories that don't exist yet are compared by the path they would be created at.
+///
+/// Returns the first conflict found, unconfigured (empty) directories are not checked.
+pub fn check_directory_conflict(
+    save_directory: impl AsRef<Path>,
+    backup_directory: impl AsRef<Path>,
+) -> Option<DirectoryProblem> {
+    let (save_directory, backup_directory) = (save_directory.as_ref(), backup_directory.as_ref());
+    if save_directory.as_os_str().is_empty() || backup_directory.as_os_str().is_empty() {
+        return None;
+    }
+    let save_directory = resolve_path(save_directory);
+    let backup_directory = resolve_path(backup_directory);
+    if save_directory == backup_directory {
+        Some(DirectoryProblem::SameDirectory)
+    } else if backup_directory.starts_with(&save_directory) {
+        Some(DirectoryProblem::BackupInsideSaves)
+    } else if save_directory.starts_with(&backup_directory) {
+        Some(DirectoryProblem::SavesInsideBackup)
+    } else {
+        None
+    }
+}
+
+/// Check that files can be created in a directory, or in the closest existing parent
+/// if the directory doesn't exist yet, as it's created when needed.
+pub fn check_writable(directory: impl AsRef<Path>) -> Option<DirectoryProblem> {
+    let directory = directory.as_ref();
+    if directory.as_os_str().is_empty() {
+        return None;
+    }
+    let existing = directory.ancestors().find(|path| path.is_dir())?;
+    match tempfile::tempfile_in(existing) {
+        Ok(_) => None,
+        Err(e) if e.kind() == io::ErrorKind::ReadOnlyFilesystem => {
+            Some(DirectoryProblem::ReadOnly(directory.to_path_buf()))
+        }
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            Some(DirectoryProblem::NotWritable(directory.to_path_buf()))
+        }
+        Err(e) => {
+            log::warn!(e:err; "Failed to check if {:?} is writable.", directory);
+            None
+        }
+    }
+}
+
+/// Absolute path with symbolic links resolved, for the part of the path that exists.
+fn resolve_path(path: &Path) -> PathBuf {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    for existing in path.ancestors() {
+        if let Ok(resolved) = existing.canonicalize() {
+            // The rest of the path doesn't exist, it can't be a link
+            let rest = path.strip_prefix(existing).unwrap_or(Path::new(""));
+            return resolved.join(rest);
+        }
+    }
+    path
 }
 
 /// Returns the first existing path that's a directory (if one exists).
@@ -42,11 +187,58 @@ pub fn copy_directory(
     source_dir: impl AsRef<Path>,
     destination_root: impl AsRef<Path>,
     overwrite: bool,
+) -> Result<(), SaveManagerError> {
+    let progress = JobProgress::default();
+    let (files, bytes) = directory_size(source_dir.as_ref())?;
+    progress.add_total(files, bytes);
+    copy_directory_with_progress(source_dir, destination_root, overwrite, &progress)
+}
+
+/// Count the files in a directory and their total size in bytes.
+pub fn directory_size(dir: impl AsRef<Path>) -> Result<(u64, u64), SaveManagerError> {
+    let mut files = 0;
+    let mut bytes = 0;
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry.map_err(io::Error::from)?;
+        if entry.file_type().is_file() {
+            files += 1;
+            bytes += entry.metadata().map_err(io::Error::from)?.len();
+        }
+    }
+    Ok((files, bytes))
+}
+
+/// Copy a directory recursively like `copy_directory`, reporting each copied file to `progress`.
+///
+/// The totals aren't added to `progress`, so that callers copying several directories
+/// can announce all the work upfront (see `directory_size`).
+///
+/// The files are first copied to a hidden staging directory next to the destination,
+/// which then replaces the destination (see `swap_directory`). When the copy fails or `progress`
+/// is cancelled, the staging directory is removed and the destination is left untouched.
+/// A destination left aside by an interrupted copy is moved back first
+/// (see `recover_interrupted_swap`).
+pub fn copy_directory_with_progress(
+    source_dir: impl AsRef<Path>,
+    destination_root: impl AsRef<Path>,
+    overwrite: bool,
+    progress: &JobProgress,
 ) -> Result<(), SaveManagerError> {
     let source_dir_path = source_dir.as_ref();
     let destination_root_path = destination_root.as_ref();
 
-    let destination_dir_path = destination_root_path.join(source_dir_path.file_name().unwrap());
+    let dir_name = source_dir_path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Source has no name"))?;
+    let destination_dir_path = destination_root_path.join(dir_name);
+    let staging_dir_path =
+        destination_root_path.join(format!(".{}.partial", dir_name.to_string_lossy()));
+    recover_interrupted_swap(
+        destination_root_path,
+        dir_name,
+        &staging_dir_path,
+        &destination_dir_path,
+    )?;
 
     // Check if destination already exists
     if destination_dir_path.exists() && !overwrite {
@@ -54,38 +246,134 @@ pub fn copy_directory(
     }
 
     // Create the destination_root directory if it doesn't exist
-    if !destination_root_path.exists() {
-        std::fs::create_dir_all(&source_dir_path).map_err(|e| {
-            log::error!(e:err; "Failed to create backup directory");
-            SaveManagerError::IOError(e)
-        })?;
+    fs::create_dir_all(destination_root_path).map_err(|e| {
+        log::error!(e:err; "Failed to create destination root directory");
+        SaveManagerError::IOError(e)
+    })?;
+
+    if staging_dir_path.exists() {
+        fs::remove_dir_all(&staging_dir_path)?;
     }
 
-    // If the destination dir exists, delete it
-    if destination_dir_path.exists() {
-        std::fs::remove_dir_all(&destination_dir_path).map_err(|e| {
-            log::error!(e:err; "Failed to remove existing destination directory");
-            SaveManagerError::IOError(e)
-        })?;
+    if let Err(e) = copy_tree(source_dir_path, &staging_dir_path, progress) {
+        if let Err(cleanup_err) = fs::remove_dir_all(&staging_dir_path) {
+            log::warn!(cleanup_err:err; "Failed to remove staging directory {:?}", staging_dir_path);
+        }
+        return Err(e);
     }
 
-    // Create an empty destination dir
-    fs::create_dir_all(destination_dir_path).map_err(|e| {
-        log::error!(e:err; "Failed to create destination directory");
-        SaveManagerError::IOError(e)
-    })?;
+    let old_dir_path = old_dir_paths(destination_root_path, dir_name)
+        .find(|path| !path.exists())
+        .unwrap_or_default();
+    swap_directory(&staging_dir_path, &destination_dir_path, &old_dir_path)
+}
 
-    // copy source_dir_path to destination_root
-    fs_extra::dir::copy(
-        &source_dir_path,
-        &destination_root_path,
-        &fs_extra::dir::CopyOptions::new(),
-    )
-    .map_err(|e| {
-        log::error!(e:err; "Failed to copy directory");
-        SaveManagerError::FsExtraError(e)
-    })?;
+/// Where the destination `dir_name` is moved aside while it's replaced: `.NAME.old`, then
+/// `.NAME.old.1`, `.NAME.old.2`, etc. if a previous copy couldn't remove its own.
+fn old_dir_paths(
+    destination_root: &Path,
+    dir_name: &std::ffi::OsStr,
+) -> impl Iterator<Item = PathBuf> {
+    let old_name = format!(".{}.old", dir_name.to_string_lossy());
+    let first = destination_root.join(&old_name);
+    std::iter::once(first)
+        .chain((1..).map(move |index| destination_root.join(format!("{old_name}.{index}"))))
+}
 
+/// Move back the destination moved aside by a copy interrupted during `swap_directory`,
+/// eg. if the process died between the two renames. The last one moved aside is restored.
+///
+/// A swap was interrupted only if the destination is missing while its staging directory is
+/// still there. Otherwise the copies moved aside are leftovers of copies that couldn't remove
+/// them, and if the destination is missing it was deleted since: they are removed rather than
+/// bringing back a deleted save.
+fn recover_interrupted_swap(
+    destination_root: &Path,
+    dir_name: &std::ffi::OsStr,
+    staging: &Path,
+    destination: &Path,
+) -> Result<(), SaveManagerError> {
+    if destination.exists() {
+        return Ok(());
+    }
+    let old_dirs: Vec<PathBuf> = old_dir_paths(destination_root, dir_name)
+        .take_while(|path| path.exists())
+        .collect();
+    let Some(old) = old_dirs.last() else {
+        return Ok(());
+    };
+    if staging.exists() {
+        log::warn!(
+            "Restoring {:?} from {:?}, left aside by an interrupted copy",
+            destination,
+            old
+        );
+        fs::rename(old, destination)?;
+        return Ok(());
+    }
+    for old in &old_dirs {
+        log::warn!("Removing {:?}, left behind by a previous copy", old);
+        fs::remove_dir_all(old)?;
+    }
+    Ok(())
+}
+
+/// Replace `destination` with `staging`. The destination is moved aside to `old` until the
+/// staging directory is in place, and moved back if it can't be, so that it's never lost.
+/// Only the `old` directory moved aside here is removed.
+fn swap_directory(staging: &Path, destination: &Path, old: &Path) -> Result<(), SaveManagerError> {
+    let had_destination = destination.exists();
+    if had_destination {
+        fs::rename(destination, old).map_err(|e| {
+            log::error!(e:err; "Failed to move existing destination directory");
+            SaveManagerError::IOError(e)
+        })?;
+    }
+    if let Err(e) = fs::rename(staging, destination) {
+        log::error!(e:err; "Failed to move copied directory into place");
+        if had_destination && let Err(restore_err) = fs::rename(old, destination) {
+            log::error!(
+                restore_err:err;
+                "Failed to move {:?} back, the original is kept in {:?}", destination, old
+            );
+        }
+        if staging.exists()
+            && let Err(cleanup_err) = fs::remove_dir_all(staging)
+        {
+            log::warn!(cleanup_err:err; "Failed to remove staging directory {:?}", staging);
+        }
+        return Err(SaveManagerError::IOError(e));
+    }
+    // The copy is in place, failing to remove the previous one only leaves it behind.
+    if had_destination && let Err(e) = fs::remove_dir_all(old) {
+        log::warn!(e:err; "Failed to remove the previous copy {:?}", old);
+    }
+    Ok(())
+}
+
+/// Copy the content of `source` to `destination`, checking for cancellation between files.
+fn copy_tree(
+    source: &Path,
+    destination: &Path,
+    progress: &JobProgress,
+) -> Result<(), SaveManagerError> {
+    for entry in walkdir::WalkDir::new(source) {
+        if progress.is_cancelled() {
+            return Err(SaveManagerError::Cancelled);
+        }
+        let entry = entry.map_err(io::Error::from)?;
+        let relative_path = entry
+            .path()
+            .strip_prefix(source)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        let target = destination.join(relative_path);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            let bytes = fs::copy(entry.path(), &target)?;
+            progress.add_done(1, bytes);
+        }
+    }
     Ok(())
 }
 
@@ -97,6 +385,118 @@ mod tests {
     use std::path::PathBuf;
     use tempfile::{TempDir, tempdir};
 
+    mod check_directory_conflict {
+        use super::*;
+
+        /// A temporary directory with an existing `saves` directory in it.
+        fn saves_dir() -> (TempDir, PathBuf) {
+            let temp_dir = tempdir().unwrap();
+            let saves = temp_dir.path().join("saves");
+            fs::create_dir(&saves).unwrap();
+            (temp_dir, saves)
+        }
+
+        #[test]
+        fn detects_same_directory() {
+            // Arrange
+            let (_temp_dir, saves) = saves_dir();
+
+            // Act
+            let problem = check_directory_conflict(&saves, saves.join("../saves"));
+
+            // Assert
+            assert_eq!(problem, Some(DirectoryProblem::SameDirectory));
+        }
+
+        #[test]
+        fn detects_backup_inside_saves() {
+            // Arrange
+            let (_temp_dir, saves) = saves_dir();
+
+            // Act
+            let problem = check_directory_conflict(&saves, saves.join("backups/new"));
+
+            // Assert
+            assert_eq!(problem, Some(DirectoryProblem::BackupInsideSaves));
+        }
+
+        #[test]
+        fn detects_saves_inside_backup() {
+            // Arrange
+            let (temp_dir, saves) = saves_dir();
+
+            // Act
+            let problem = check_directory_conflict(&saves, temp_dir.path());
+
+            // Assert
+            assert_eq!(problem, Some(DirectoryProblem::SavesInsideBackup));
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn follows_symbolic_links() {
+            // Arrange
+            let (temp_dir, saves) = saves_dir();
+            let link = temp_dir.path().join("link");
+            std::os::unix::fs::symlink(&saves, &link).unwrap();
+
+            // Act
+            let problem = check_directory_conflict(&saves, &link);
+
+            // Assert
+            assert_eq!(problem, Some(DirectoryProblem::SameDirectory));
+        }
+
+        #[test]
+        fn accepts_sibling_with_same_prefix() {
+            // Arrange
+            let (temp_dir, saves) = saves_dir();
+
+            // Act
+            let problem = check_directory_conflict(&saves, temp_dir.path().join("saves_backups"));
+
+            // Assert
+            assert_eq!(problem, None);
+        }
+
+        #[test]
+        fn ignores_unset_directory() {
+            // Arrange
+            let (_temp_dir, saves) = saves_dir();
+
+            // Act
+            let problem = check_directory_conflict("", &saves);
+
+            // Assert
+            assert_eq!(problem, None);
+        }
+    }
+
+    mod check_writable {
+        use super::*;
+
+        #[test]
+        fn accepts_writable_and_missing_directories() -> Result<(), Box<dyn std::error::Error>> {
+            // Arrange
+            let root = tempdir()?;
+
+            // Act
+            let existing = check_writable(root.path());
+            let missing = check_writable(root.path().join("not").join("created"));
+
+            // Assert
+            assert_eq!(existing, None);
+            assert_eq!(missing, None);
+
+            Ok(())
+        }
+
+        #[test]
+        fn ignores_unset_directory() {
+            assert_eq!(check_writable(""), None);
+        }
+    }
+
     mod first_existing_dir {
         use super::*;
 
@@ -161,7 +561,7 @@ mod tests {
 
             // Assert
             assert!(result.is_ok());
-            assert_dirs_equal(&source_dir, &dest_dir.join(source_dir.file_name().unwrap()));
+            assert_dirs_equal(&source_dir, dest_dir.join(source_dir.file_name().unwrap()));
 
             Ok(())
         }
@@ -198,6 +598,124 @@ mod tests {
 
             Ok(())
         }
+
+        #[test]
+        fn cancelled_leaves_destination_untouched() -> Result<(), Box<dyn std::error::Error>> {
+            // Arrange
+            let (_tempdir1, _source_root_dir, source_dir) = sample_dir("src_")?;
+            let (_tempdir2, dest_root_dir, dest_dir) = sample_dir("dest_")?;
+            let progress = JobProgress::default();
+            progress.cancel();
+
+            // Act
+            let result = copy_directory_with_progress(&source_dir, &dest_root_dir, true, &progress);
+
+            // Assert
+            assert!(matches!(result, Err(SaveManagerError::Cancelled)));
+            assert!(dest_dir.join("dest_file1.txt").exists());
+            assert!(!dest_dir.join("src_file1.txt").exists());
+            assert_eq!(fs::read_dir(&dest_root_dir)?.count(), 1);
+
+            Ok(())
+        }
+
+        #[test]
+        fn restores_destination_left_aside() -> Result<(), Box<dyn std::error::Error>> {
+            // Arrange
+            let (_tempdir1, _source_root_dir, source_dir) = sample_dir("src_")?;
+            let (_tempdir2, dest_root_dir, dest_dir) = sample_dir("dest_")?;
+            let old_dir = dest_root_dir.join(".base_subdir.old");
+            fs::rename(&dest_dir, &old_dir)?;
+            fs::create_dir(dest_root_dir.join(".base_subdir.partial"))?;
+
+            // Act
+            let result = copy_directory(&source_dir, &dest_root_dir, false);
+
+            // Assert
+            assert!(matches!(result, Err(SaveManagerError::SaveExists)));
+            assert!(dest_dir.join("dest_file1.txt").exists());
+            assert!(!old_dir.exists());
+
+            Ok(())
+        }
+
+        #[test]
+        fn removes_copy_left_behind_of_deleted_destination()
+        -> Result<(), Box<dyn std::error::Error>> {
+            // Arrange
+            let (_tempdir1, _source_root_dir, source_dir) = sample_dir("src_")?;
+            let (_tempdir2, dest_root_dir, dest_dir) = sample_dir("dest_")?;
+            let old_dir = dest_root_dir.join(".base_subdir.old");
+            fs::rename(&dest_dir, &old_dir)?;
+
+            // Act
+            let result = copy_directory(&source_dir, &dest_root_dir, false);
+
+            // Assert
+            assert!(result.is_ok());
+            assert_dirs_equal(&source_dir, &dest_dir);
+            assert!(!old_dir.exists());
+
+            Ok(())
+        }
+
+        #[test]
+        fn keeps_previous_copies_left_behind() -> Result<(), Box<dyn std::error::Error>> {
+            // Arrange
+            let (_tempdir1, _source_root_dir, source_dir) = sample_dir("src_")?;
+            let (_tempdir2, dest_root_dir, dest_dir) = sample_dir("dest_")?;
+            let old_dir = dest_root_dir.join(".base_subdir.old");
+            fs::create_dir(&old_dir)?;
+            fs::write(old_dir.join("kept.txt"), "previous copy")?;
+
+            // Act
+            let result = copy_directory(&source_dir, &dest_root_dir, true);
+
+            // Assert
+            assert!(result.is_ok());
+            assert_dirs_equal(&source_dir, &dest_dir);
+            assert!(old_dir.join("kept.txt").exists());
+            assert!(!dest_root_dir.join(".base_subdir.old.1").exists());
+
+            Ok(())
+        }
+
+        #[test]
+        fn failed_swap_keeps_destination() -> Result<(), Box<dyn std::error::Error>> {
+            // Arrange
+            let (_tempdir, dest_root_dir, dest_dir) = sample_dir("dest_")?;
+            let missing_staging = dest_root_dir.join(".base_subdir.partial");
+            let old_dir = dest_root_dir.join(".base_subdir.old");
+
+            // Act
+            let result = swap_directory(&missing_staging, &dest_dir, &old_dir);
+
+            // Assert
+            assert!(matches!(result, Err(SaveManagerError::IOError(_))));
+            assert!(dest_dir.join("dest_file1.txt").exists());
+            assert!(!old_dir.exists());
+
+            Ok(())
+        }
+
+        #[test]
+        fn reports_progress() -> Result<(), Box<dyn std::error::Error>> {
+            // Arrange
+            let (_tempdir, _source_root_dir, source_dir) = sample_dir("src_")?;
+            let dest_dir = tempdir()?;
+            let progress = JobProgress::default();
+            let (files, bytes) = directory_size(&source_dir)?;
+            progress.add_total(files, bytes);
+
+            // Act
+            copy_directory_with_progress(&source_dir, dest_dir.path(), false, &progress)?;
+
+            // Assert
+            assert_eq!(progress.files(), (2, 2));
+            assert_eq!(progress.bytes(), (bytes, bytes));
+
+            Ok(())
+        }
     }
 
     /// Helper function to set up test directories with sample files
@@ -0,0 +1,158 @@
+//! Background jobs, used to run long operations without blocking the UI.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+/// Progress of a job, updated by the job and read by the view.
+/// It's also how a job is asked to stop.
+#[derive(Debug, Default)]
+pub struct JobProgress {
+    files_done: AtomicU64,
+    files_total: AtomicU64,
+    bytes_done: AtomicU64,
+    bytes_total: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+impl JobProgress {
+    /// Add work to be done.
+    pub fn add_total(&self, files: u64, bytes: u64) {
+        self.files_total.fetch_add(files, Ordering::Relaxed);
+        self.bytes_total.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Record work done.
+    pub fn add_done(&self, files: u64, bytes: u64) {
+        self.files_done.fetch_add(files, Ordering::Relaxed);
+        self.bytes_done.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn files(&self) -> (u64, u64) {
+        (
+            self.files_done.load(Ordering::Relaxed),
+            self.files_total.load(Ordering::Relaxed),
+        )
+    }
+
+    pub fn bytes(&self) -> (u64, u64) {
+        (
+            self.bytes_done.load(Ordering::Relaxed),
+            self.bytes_total.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Fraction of the bytes processed, between 0 and 1.
+    pub fn fraction(&self) -> f32 {
+        match self.bytes() {
+            (_, 0) => 0.0,
+            (done, total) => (done as f64 / total as f64).min(1.0) as f32,
+        }
+    }
+
+    /// Ask the job to stop. Jobs check this between files and leave things as they were.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JobStatus {
+    Queued,
+    Running,
+}
+
+/// A job waiting in the queue or running.
+#[derive(Debug, Clone)]
+pub struct JobInfo {
+    pub id: u64,
+    /// Description shown to the user, eg. `Backup REPO_SAVE_2025_04_12_15_39_47`
+    pub label: String,
+    pub status: JobStatus,
+    pub progress: Arc<JobProgress>,
+}
+
+type Task = Box<dyn FnOnce() + Send>;
+
+/// Runs jobs one after the other on a worker thread,
+/// so that two operations never modify the same directories at once.
+///
+/// The worker thread stops once every clone of the queue is dropped.
+#[derive(Clone)]
+pub struct JobQueue {
+    sender: Sender<Task>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl JobQueue {
+    pub fn start() -> Self {
+        let (sender, receiver) = mpsc::channel::<Task>();
+        let spawned = thread::Builder::new()
+            .name("rsm-jobs".to_string())
+            .spawn(move || {
+                for task in receiver {
+                    task();
+                }
+            });
+        if let Err(e) = spawned {
+            log::error!(e:err; "Failed to start the job worker thread.");
+        }
+        JobQueue {
+            sender,
+            next_id: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Returns a new unique job ID.
+    pub fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Queue a task, returns false if the worker thread isn't running.
+    pub fn push(&self, task: impl FnOnce() + Send + 'static) -> bool {
+        self.sender.send(Box::new(task)).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn runs_tasks_in_order() {
+        // Arrange
+        let queue = JobQueue::start();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let (done_sender, done_receiver) = mpsc::channel();
+
+        // Act
+        for i in 0..5 {
+            let order = order.clone();
+            queue.push(move || order.lock().unwrap().push(i));
+        }
+        queue.push(move || done_sender.send(()).unwrap());
+        done_receiver.recv().unwrap();
+
+        // Assert
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn progress_fraction() {
+        // Arrange
+        let progress = JobProgress::default();
+
+        // Act & Assert
+        assert_eq!(progress.fraction(), 0.0);
+        progress.add_total(2, 200);
+        progress.add_done(1, 50);
+        assert_eq!(progress.fraction(), 0.25);
+        assert_eq!(progress.files(), (1, 2));
+    }
+}
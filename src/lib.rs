@@ -0,0 +1,58 @@
+//! Core of the R.E.P.O. Save Manager, usable without the GUI.
+//!
+//! - [`repo`]: R.E.P.O. and Easy Save 3 specifics: save file encryption ([`repo::crypt`]),
+//!   the save file JSON model ([`repo::save`]) and default save locations, including the
+//!   Steam libraries on Linux ([`repo::steam`]).
+//! - [`rsm::discovery`]: discovery and validation of the save directories, for the setup.
+//! - [`rsm::save_bundle`]: save bundles, the directories in which the game stores a save,
+//!   along with reading and writing their save file.
+//! - [`rsm::edit`]: validated edits of the saves, keeping a snapshot of the original
+//!   (see [`rsm::edit::edit_save_bundle`]).
+//! - [`rsm::patch`]: JSON Patch and JSON merge patch edits of the saves.
+//! - [`rsm::diff`]: semantic differences between two saves, eg. before a restore.
+//! - [`rsm::lint`]: checks for suspicious saves, with automatic fixes.
+//! - [`rsm::template`]: save templates, to start new saves from the same state.
+//! - [`controller`]: operations on the save and backup directories (backup, restore, delete).
+//! - [`jobs`]: background jobs running the operations, with progress and cancellation.
+//! - [`model`]: the state the operations work on.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use repo_save_manager::controller::AppController;
+//! use repo_save_manager::jobs::JobProgress;
+//! use repo_save_manager::model::AppState;
+//! use repo_save_manager::rsm::edit;
+//! use std::collections::HashMap;
+//! use std::sync::{Arc, Mutex};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // List saves
+//! let state = AppState::new("/path/to/game/saves", "/path/to/backups");
+//! for save_bundle in state.save_lists().game_save_bundles.iter() {
+//!     println!("{}: level {}", save_bundle.name, save_bundle.level + 1);
+//! }
+//!
+//! // Backup and restore
+//! let controller = AppController::new(Arc::new(Mutex::new(state.clone())));
+//! let progress = JobProgress::default();
+//! controller.backup_all(&progress)?;
+//! let backup = &state.save_lists().backup_save_bundles[0];
+//! controller.restore_backup(&backup.id, true, &progress)?;
+//!
+//! // Edit a save, refused while the game runs. The original is kept as a snapshot first,
+//! // see `rsm::edit::edit_save_bundle` to edit a bundle found elsewhere.
+//! let save_bundle = &state.save_lists().game_save_bundles[0];
+//! let currency = HashMap::from([("currency".to_string(), 100)]);
+//! controller.edit_save(&save_bundle.id, |save| edit::set_run_stats(save, &currency))?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod constant;
+pub mod controller;
+pub mod fs_util;
+pub mod jobs;
+pub mod model;
+pub mod repo;
+pub mod rsm;
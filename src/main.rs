@@ -1,33 +1,34 @@
+use clap::Parser;
 use eframe::egui;
+use std::process::ExitCode;
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 use std::thread;
 
-pub mod app;
-pub mod constant;
-pub mod controller;
-pub mod fs_util;
-pub mod model;
-pub mod repo;
-pub mod rsm;
-// pub mod ui;
-pub mod view;
-
-fn main() {
+use repo_save_manager::rsm::index::MetadataIndex;
+use repo_save_manager::{constant, controller, model};
+
+mod app;
+mod cli;
+mod view;
+
+fn main() -> ExitCode {
+    // Log warnings by default, can be changed with the RUST_LOG environment variable
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    // Run headlessly if a command was given
+    let cli = cli::Cli::parse();
+    if let Some(command) = &cli.command {
+        return cli::run(&cli, command);
+    }
+
     // Initialize the model
     let app_state = model::AppState::default();
     let shared_state = Arc::new(Mutex::new(app_state));
 
-    // Set up the communication channel between view and controller
+    // Set up the communication channels between view and controller
     let (event_sender, event_receiver) = mpsc::channel();
-
-    // Create the controller with the shared state and receiver
-    let controller = controller::AppController::new(shared_state.clone(), event_receiver);
-
-    // Start the controller in a separate thread
-    _ = thread::spawn(move || {
-        controller.handle_events();
-    });
+    let (notification_sender, notification_receiver) = mpsc::channel();
 
     // Configure and run the UI
     let native_options = eframe::NativeOptions {
@@ -39,14 +40,34 @@ fn main() {
     };
 
     // Create the view and run the UI
-    let app_view = view::AppView::new(shared_state, event_sender);
+    let app_view = view::AppView::new(shared_state.clone(), event_sender, notification_receiver);
 
     _ = eframe::run_native(
         constant::APP_NAME,
         native_options,
-        Box::new(|cc| Ok(Box::new(app::RSMApp::new(cc, app_view)))),
+        Box::new(|cc| {
+            // The controller wakes the UI up when it has something new to show
+            let egui_ctx = cc.egui_ctx.clone();
+            let notifier =
+                controller::Notifier::new(notification_sender, move || egui_ctx.request_repaint());
+
+            // Create the controller with the shared state
+            let mut controller =
+                controller::AppController::new(shared_state).with_notifier(notifier);
+            if let Some(index_path) = MetadataIndex::default_path() {
+                controller = controller.with_index(index_path);
+            }
+
+            // Start the controller in a separate thread
+            _ = thread::spawn(move || {
+                controller.handle_events(event_receiver);
+            });
+
+            Ok(Box::new(app::RSMApp::new(cc, app_view)))
+        }),
     );
 
     // If we reach here, the application is closing
     // We don't need to explicitly join the controller thread as it will be terminated when the program exits
+    ExitCode::SUCCESS
 }
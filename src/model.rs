@@ -1,30 +1,266 @@
+use arc_swap::ArcSwap;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
 use thiserror::Error;
 
-use crate::{constant, fs_util, repo, rsm};
+use crate::fs_util::DirectoryProblem;
+use crate::jobs::JobInfo;
+use crate::repo::save::SaveGame;
+use crate::rsm::diff::SaveDiff;
+use crate::rsm::discovery::DirectoryCandidate;
+use crate::rsm::lint::BundleLint;
+use crate::rsm::patch::{JsonChange, SavePatch};
+use crate::rsm::save_bundle::{BundleId, SaveBundle};
+use crate::rsm::template::TemplateInfo;
+use crate::{fs_util, repo, rsm};
 
 #[derive(Error, Debug)]
 pub enum RSMError {}
 
+/// A patch to apply to a game save, with the changes it makes, waiting for confirmation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchPreview {
+    pub id: BundleId,
+    pub name: String,
+    /// File the patch was read from
+    pub file: PathBuf,
+    pub patch: SavePatch,
+    pub changes: Vec<JsonChange>,
+}
+
+/// What changes from a save or backup to another, as shown in the comparison window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveComparison {
+    pub before_name: String,
+    pub after_name: String,
+    pub diff: SaveDiff,
+}
+
+/// The issues found in the saves of the active source, see `lint::lint_directory`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SaveCheck {
+    pub game_saves: Vec<BundleLint>,
+    pub backups: Vec<BundleLint>,
+}
+
+impl SaveCheck {
+    /// The issues found in a game save or backup, None if it wasn't checked.
+    pub fn bundle(&self, id: &BundleId) -> Option<&BundleLint> {
+        self.game_saves
+            .iter()
+            .chain(&self.backups)
+            .find(|lint| lint.id == *id)
+    }
+}
+
+/// A game save opened in the editor.
+#[derive(Debug, Clone)]
+pub struct SaveEditor {
+    pub id: BundleId,
+    pub name: String,
+    /// Content of the save when it was opened, or last written by the editor.
+    /// Replaced rather than modified, so that the view tells it changed by comparing pointers.
+    pub save: Arc<SaveGame>,
+}
+
+/// Immutable snapshot of the save lists.
+/// A new snapshot is published whenever a list changes, so that readers can keep one
+/// for as long as they want without blocking writers or copying the lists.
+#[derive(Debug, Clone, Default)]
+pub struct SaveLists {
+    /// Incremented with every snapshot published, to tell if the lists changed
+    pub generation: u64,
+    pub game_save_bundles: Arc<[SaveBundle]>,
+    pub backup_save_bundles: Arc<[SaveBundle]>,
+}
+
+/// Holds the current `SaveLists` snapshot, it can be read without locking.
+#[derive(Debug, Default)]
+pub struct SharedSaveLists {
+    current: ArcSwap<SaveLists>,
+    /// Serialises writers, readers never wait
+    writer: Mutex<()>,
+}
+
+/// Progress of the setup wizard.
+#[derive(Debug, Clone, Default)]
+pub struct SetupState {
+    /// True while the save directories are being looked for
+    pub discovering: bool,
+    pub candidates: Vec<DirectoryCandidate>,
+    /// The save directory chosen
+    pub save_directory: String,
+    /// Saves found in the chosen directory, or why it can't be used
+    pub validation: Option<Result<Vec<String>, String>>,
+    /// The backup directory proposed
+    pub backup_directory: String,
+}
+
+fn setup_done_default() -> bool {
+    true
+}
+
+fn ensure_no_conflict(
+    save_directory: &str,
+    backup_directory: &str,
+) -> Result<(), DirectoryProblem> {
+    match fs_util::check_directory_conflict(save_directory, backup_directory) {
+        Some(problem) => Err(problem),
+        None => Ok(()),
+    }
+}
+
+/// Name of the source created from the directories found on the first launch.
+const DEFAULT_SOURCE_NAME: &str = "Default";
+
+impl SaveSource {
+    pub fn new(name: impl Into<String>, save_directory: impl Into<String>) -> Self {
+        SaveSource {
+            name: name.into(),
+            save_directory: save_directory.into(),
+            backup_directory: None,
+        }
+    }
+}
+
+impl SaveLists {
+    pub fn game_save(&self, id: &BundleId) -> Option<&SaveBundle> {
+        self.game_save_bundles.iter().find(|s| s.id == *id)
+    }
+
+    pub fn backup(&self, id: &BundleId) -> Option<&SaveBundle> {
+        self.backup_save_bundles.iter().find(|s| s.id == *id)
+    }
+}
+
+impl SharedSaveLists {
+    pub fn load(&self) -> Arc<SaveLists> {
+        self.current.load_full()
+    }
+
+    /// Publish a new snapshot, made by applying `update` to a copy of the current one.
+    /// Returns the generation of the new snapshot.
+    pub fn update(&self, update: impl FnOnce(&mut SaveLists)) -> u64 {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut lists = SaveLists::clone(&self.current.load());
+        update(&mut lists);
+        lists.generation += 1;
+        let generation = lists.generation;
+        self.current.store(Arc::new(lists));
+        generation
+    }
+}
+
+/// What renaming a save source changes, see `AppState::source_rename`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceRename {
+    pub index: usize,
+    pub old_name: String,
+    pub new_name: String,
+    /// Own backup library of the source, moved from the first to the second directory
+    /// as it's named after the source
+    pub library: Option<(String, String)>,
+    /// Backup libraries where backups may record the old name, once the library is moved
+    pub backup_directories: Vec<String>,
+}
+
+/// A game save directory, eg. a Proton prefix or a Steam account.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct SaveSource {
+    /// Unique among the sources, recorded on the backups made from this source
+    pub name: String,
+    pub save_directory: String,
+    /// Backup library of this source, None to use the shared one
+    pub backup_directory: Option<String>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(default)]
 pub struct AppState {
+    /// Game save directory of the active source
     pub save_directory: String,
+    /// Backup directory of the active source
     pub backup_directory: String,
 
-    pub game_save_bundles: Vec<rsm::save_bundle::SaveBundle>,
-    pub backup_save_bundles: Vec<rsm::save_bundle::SaveBundle>,
+    /// Save sources, there is always at least one.
+    /// The directories of the active one are mirrored in the fields above.
+    pub sources: Vec<SaveSource>,
+    /// Index of the source shown and worked on
+    pub active_source: usize,
+    /// Backup library of the sources without their own
+    pub shared_backup_directory: String,
+
+    /// True once the directories were chosen with the setup wizard, or the wizard skipped.
+    /// States persisted before the wizard existed were set up by hand.
+    #[serde(default = "setup_done_default")]
+    pub setup_done: bool,
+
+    /// Problems with the directories of the active source, shown as warnings.
+    /// Operations refuse to run while the directories conflict.
+    #[serde(skip)]
+    pub directory_warnings: Vec<DirectoryProblem>,
+
+    /// The setup wizard, shown when not None.
+    #[serde(skip)]
+    pub setup: Option<SetupState>,
+
+    /// The game save opened in the editor, shown when not None.
+    #[serde(skip)]
+    pub editor: Option<SaveEditor>,
+
+    /// A patch waiting for confirmation, shown when not None.
+    #[serde(skip)]
+    pub patch_preview: Option<PatchPreview>,
+
+    /// The templates of the backup directory, shown in the template window when not None.
+    #[serde(skip)]
+    pub templates: Option<Vec<TemplateInfo>>,
+
+    /// The last check of the saves, shown in the check window when `show_save_check` is set.
+    /// Shared with the view, which reads it every frame.
+    #[serde(skip)]
+    pub save_check: Option<Arc<SaveCheck>>,
+    #[serde(skip)]
+    pub show_save_check: bool,
+
+    /// Two saves compared, shown in the comparison window when not None.
+    #[serde(skip)]
+    pub comparison: Option<SaveComparison>,
+
+    /// Bundles of the save and backup directories, readable without locking the state.
+    /// They aren't persisted with the state, they are cached in the metadata index.
+    #[serde(skip)]
+    pub save_lists: Arc<SharedSaveLists>,
 
-    /// If not None, contains the name of a backup to restore.
+    /// If not None, contains the ID of a backup to restore.
     /// When not None, this triggers a popup to restore backup with overwrite power.
     #[serde(skip)]
-    pub confirm_restore_backup_name: Option<String>,
+    pub confirm_restore_backup_id: Option<BundleId>,
+
+    /// Name of the source the backup in `confirm_restore_backup_id` is restored to.
+    #[serde(skip)]
+    pub confirm_restore_source: Option<String>,
 
-    /// If not None, contains the name of a backup to delete.
+    /// What the restore in `confirm_restore_backup_id` changes to the game save it overwrites,
+    /// if it could be read.
+    #[serde(skip)]
+    pub confirm_restore_diff: Option<SaveDiff>,
+
+    /// If not None, contains the ID of a backup to delete.
     /// When not None, this triggers a popup to delete a backup.
     #[serde(skip)]
-    pub confirm_backup_deletion_name: Option<String>,
+    pub confirm_backup_deletion_id: Option<BundleId>,
+
+    /// True if the game was found running the last time a destructive operation
+    /// was requested. Used to warn the user in the confirmation popups.
+    #[serde(skip)]
+    pub game_running: bool,
+
+    /// Jobs waiting in the queue or running, in the order they will complete.
+    #[serde(skip)]
+    pub jobs: Vec<JobInfo>,
 }
 
 impl Default for AppState {
@@ -35,62 +271,704 @@ impl Default for AppState {
                 .map(|p| p.to_string_lossy().to_string())
                 .unwrap_or_default();
 
-        let project_dir = directories_next::ProjectDirs::from("", "", constant::APP_ID);
-        let backup_directory: String;
-        if let Some(project_dir) = project_dir {
-            backup_directory = project_dir
-                .data_dir()
-                .join("backups")
-                .to_string_lossy()
-                .to_string();
-        } else {
-            let home_path = PathBuf::from(std::env::var("HOME").unwrap_or_default());
-            backup_directory = home_path
-                .join(".local/share/rsm/backups")
-                .to_string_lossy()
-                .to_string();
-        }
+        let backup_directory = rsm::discovery::default_backup_directory();
 
+        Self::with_directories(save_directory, backup_directory)
+    }
+}
+
+impl AppState {
+    /// Create a state working on the given directories, with their save bundles loaded.
+    pub fn new(save_directory: impl Into<String>, backup_directory: impl Into<String>) -> Self {
+        let mut state = AppState {
+            setup_done: true,
+            ..Self::with_directories(save_directory.into(), backup_directory.into())
+        };
+        state.refresh_save_bundles();
+        state
+    }
+
+    /// A state working on the given directories, nothing loaded yet.
+    /// Unlike `default`, no directory is discovered.
+    fn with_directories(save_directory: String, backup_directory: String) -> Self {
         Self {
+            sources: vec![SaveSource::new(DEFAULT_SOURCE_NAME, &save_directory)],
+            active_source: 0,
+            shared_backup_directory: backup_directory.clone(),
             save_directory,
             backup_directory,
-            game_save_bundles: Vec::new(),
-            backup_save_bundles: Vec::new(),
-            confirm_restore_backup_name: None,
-            confirm_backup_deletion_name: None,
+            save_lists: Arc::default(),
+            confirm_restore_backup_id: None,
+            confirm_restore_source: None,
+            confirm_restore_diff: None,
+            confirm_backup_deletion_id: None,
+            game_running: false,
+            jobs: Vec::new(),
+            setup_done: false,
+            directory_warnings: Vec::new(),
+            setup: None,
+            editor: None,
+            patch_preview: None,
+            templates: None,
+            save_check: None,
+            show_save_check: false,
+            comparison: None,
         }
     }
-}
 
-impl AppState {
+    /// Load the state persisted by the GUI in its RON storage file, given the key it was stored under.
+    /// This allows reusing the configured directories outside the GUI.
+    ///
+    /// Returns None if the file doesn't exist or can't be parsed.
+    pub fn from_ron_storage(storage_file: impl AsRef<Path>, key: &str) -> Option<Self> {
+        let contents = std::fs::read_to_string(storage_file).ok()?;
+        // The storage is a map of keys to RON serialised values.
+        let storage: HashMap<String, String> = match ron::from_str(&contents) {
+            Ok(storage) => storage,
+            Err(e) => {
+                log::error!(e:err; "Failed to parse storage file.");
+                return None;
+            }
+        };
+        match ron::from_str::<AppState>(storage.get(key)?) {
+            Ok(mut state) => {
+                state.migrate_sources();
+                Some(state)
+            }
+            Err(e) => {
+                log::error!(e:err; "Failed to parse persisted app state.");
+                None
+            }
+        }
+    }
+
+    /// Re-read the save and backup directories, only bundles that changed are decrypted again.
     pub fn refresh_save_bundles(&mut self) {
-        self.game_save_bundles = rsm::save_bundle::extract_save_bundles(&self.save_directory);
-        self.backup_save_bundles = rsm::save_bundle::extract_save_bundles(&self.backup_directory);
+        let lists = self.save_lists.load();
+        let game_save_bundles =
+            rsm::scan::scan_save_bundles(&self.save_directory, &lists.game_save_bundles);
+        let backup_save_bundles =
+            rsm::scan::scan_save_bundles(&self.backup_directory, &lists.backup_save_bundles);
+        self.save_lists.update(|lists| {
+            lists.game_save_bundles = game_save_bundles.into();
+            lists.backup_save_bundles = backup_save_bundles.into();
+        });
+    }
+
+    /// The current save lists.
+    pub fn save_lists(&self) -> Arc<SaveLists> {
+        self.save_lists.load()
+    }
+
+    /// Take the persisted settings of `persisted`, eg. when restoring them on startup.
+    pub fn restore_settings(&mut self, persisted: AppState) {
+        self.save_directory = persisted.save_directory;
+        self.backup_directory = persisted.backup_directory;
+        self.sources = persisted.sources;
+        self.active_source = persisted.active_source;
+        self.shared_backup_directory = persisted.shared_backup_directory;
+        self.setup_done = persisted.setup_done;
+        self.migrate_sources();
+    }
+
+    /// Make the sources consistent with the directories, states persisted before
+    /// sources existed only have the directories, which become the default source.
+    fn migrate_sources(&mut self) {
+        if self.sources.is_empty() {
+            self.sources = vec![SaveSource::new(DEFAULT_SOURCE_NAME, &self.save_directory)];
+            self.shared_backup_directory = self.backup_directory.clone();
+        }
+        self.active_source = self.active_source.min(self.sources.len() - 1);
+        self.mirror_active_source();
+    }
+
+    /// Copy the directories of the active source to `save_directory` and `backup_directory`.
+    fn mirror_active_source(&mut self) {
+        let source = &self.sources[self.active_source];
+        self.save_directory = source.save_directory.clone();
+        self.backup_directory = source
+            .backup_directory
+            .clone()
+            .unwrap_or_else(|| self.shared_backup_directory.clone());
+        self.check_directories();
+    }
+
+    /// Look for problems with the directories of the active source, to warn the user.
+    fn check_directories(&mut self) {
+        self.directory_warnings =
+            fs_util::check_directory_conflict(&self.save_directory, &self.backup_directory)
+                .into_iter()
+                .chain(fs_util::check_writable(&self.save_directory))
+                .chain(fs_util::check_writable(&self.backup_directory))
+                .collect();
+    }
+
+    /// The source shown and worked on.
+    pub fn active_source(&self) -> &SaveSource {
+        &self.sources[self.active_source]
+    }
+
+    pub fn source(&self, name: &str) -> Option<&SaveSource> {
+        self.sources.iter().find(|source| source.name == name)
+    }
+
+    /// Backup directory of a source.
+    pub fn source_backup_directory(&self, source: &SaveSource) -> String {
+        source
+            .backup_directory
+            .clone()
+            .unwrap_or_else(|| self.shared_backup_directory.clone())
+    }
+
+    /// Switch to another source, its bundles are loaded by the next refresh.
+    /// Out of range indexes are ignored.
+    pub fn select_source(&mut self, index: usize) {
+        if index >= self.sources.len() || index == self.active_source {
+            return;
+        }
+        self.active_source = index;
+        self.mirror_active_source();
+        self.save_lists.update(|lists| {
+            lists.game_save_bundles = Arc::default();
+            lists.backup_save_bundles = Arc::default();
+        });
+    }
+
+    /// Add a source without directory and switch to it.
+    pub fn add_source(&mut self) {
+        let name = self.unique_source_name("New source", None);
+        self.sources.push(SaveSource::new(name, ""));
+        self.select_source(self.sources.len() - 1);
+    }
+
+    /// Remove a source, the last one can't be removed.
+    /// Its saves and backups are left on disk.
+    pub fn remove_source(&mut self, index: usize) {
+        if self.sources.len() <= 1 || index >= self.sources.len() {
+            return;
+        }
+        self.sources.remove(index);
+        if self.active_source != index {
+            // The active source stays the same, only its index may change
+            if self.active_source > index {
+                self.active_source -= 1;
+            }
+            return;
+        }
+        self.active_source = index.min(self.sources.len() - 1);
+        self.mirror_active_source();
+        self.save_lists
+            .update(|lists| *lists = SaveLists::default());
     }
 
-    pub fn update_save_directory(&mut self, new_directory: String) {
+    /// What renaming a source to `name` changes, a number is appended to the name if another
+    /// source already has it. None if the name is empty or unchanged.
+    ///
+    /// The backups record the name of their source and an own library in its default location
+    /// is named after the source, the controller migrates both before calling `rename_source`.
+    pub fn source_rename(&self, index: usize, name: &str) -> Option<SourceRename> {
+        let name = name.trim();
+        let source = self.sources.get(index)?;
+        if name.is_empty() || name == source.name {
+            return None;
+        }
+        let new_name = self.unique_source_name(name, Some(index));
+        let library = source
+            .backup_directory
+            .as_ref()
+            .filter(|library| **library == self.own_library_directory(&source.name))
+            .map(|library| (library.clone(), self.own_library_directory(&new_name)));
+        let mut backup_directories = vec![self.shared_backup_directory.clone()];
+        for (i, other) in self.sources.iter().enumerate() {
+            let own = match &library {
+                Some((_, to)) if i == index => Some(to),
+                _ => other.backup_directory.as_ref(),
+            };
+            if let Some(own) = own
+                && !backup_directories.contains(own)
+            {
+                backup_directories.push(own.clone());
+            }
+        }
+        Some(SourceRename {
+            index,
+            old_name: source.name.clone(),
+            new_name,
+            library,
+            backup_directories,
+        })
+    }
+
+    /// Rename a source once its backups were migrated, see `source_rename`.
+    /// Ignored if the source was renamed meanwhile.
+    pub fn rename_source(&mut self, rename: &SourceRename) {
+        let Some(source) = self.sources.get_mut(rename.index) else {
+            return;
+        };
+        if source.name != rename.old_name {
+            return;
+        }
+        source.name.clone_from(&rename.new_name);
+        if let Some((_, to)) = &rename.library {
+            source.backup_directory = Some(to.clone());
+            if rename.index == self.active_source {
+                self.mirror_active_source();
+                self.save_lists
+                    .update(|lists| lists.backup_save_bundles = Arc::default());
+            }
+        }
+    }
+
+    /// `name`, or `name` followed by a number if a source other than `except` has it.
+    fn unique_source_name(&self, name: &str, except: Option<usize>) -> String {
+        let taken = |candidate: &str| {
+            self.sources
+                .iter()
+                .enumerate()
+                .any(|(i, source)| Some(i) != except && source.name == candidate)
+        };
+        let mut candidate = name.to_string();
+        let mut number = 2;
+        while taken(&candidate) {
+            candidate = format!("{name} {number}");
+            number += 1;
+        }
+        candidate
+    }
+
+    /// Default own backup library of a source, next to the shared one and named after the source.
+    /// It isn't inside the shared library, so that scanning the shared library doesn't list it.
+    fn own_library_directory(&self, source_name: &str) -> String {
+        let shared = PathBuf::from(&self.shared_backup_directory);
+        let shared_name = shared
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "backups".to_string());
+        let source_name = source_name.replace(['/', '\\'], "_");
+        shared
+            .with_file_name(format!("{shared_name} ({source_name})"))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Refuse a backup directory conflicting with the save directory of any source,
+    /// the backup libraries may be shared and the active save directory overridden.
+    fn ensure_no_conflict_with_saves(
+        &self,
+        backup_directory: &str,
+    ) -> Result<(), DirectoryProblem> {
+        ensure_no_conflict(&self.save_directory, backup_directory)?;
+        self.sources
+            .iter()
+            .try_for_each(|source| ensure_no_conflict(&source.save_directory, backup_directory))
+    }
+
+    /// Give the active source its own backup library, or make it use the shared one.
+    /// Its own library defaults to a directory next to the shared one, named after the source.
+    ///
+    /// Refused if the resulting backup directory conflicts with a save directory.
+    pub fn set_own_backup_library(&mut self, own: bool) -> Result<(), DirectoryProblem> {
+        let source = &self.sources[self.active_source];
+        if source.backup_directory.is_some() == own {
+            return Ok(());
+        }
+        let backup_directory = own.then(|| self.own_library_directory(&source.name));
+        let effective = backup_directory
+            .clone()
+            .unwrap_or_else(|| self.shared_backup_directory.clone());
+        self.ensure_no_conflict_with_saves(&effective)?;
+
+        self.sources[self.active_source].backup_directory = backup_directory;
+        self.backup_directory = effective;
+        self.check_directories();
+        self.save_lists
+            .update(|lists| lists.backup_save_bundles = Arc::default());
+        Ok(())
+    }
+
+    /// Change the save directory of the active source, its bundles are loaded by the next refresh.
+    ///
+    /// Refused if the directory is the backup directory or is nested with it.
+    pub fn update_save_directory(&mut self, new_directory: String) -> Result<(), DirectoryProblem> {
+        ensure_no_conflict(&new_directory, &self.backup_directory)?;
+        self.sources[self.active_source].save_directory = new_directory.clone();
         self.save_directory = new_directory;
-        self.refresh_save_bundles();
+        self.check_directories();
+        self.save_lists
+            .update(|lists| lists.game_save_bundles = Arc::default());
+        Ok(())
     }
 
-    pub fn update_backup_directory(&mut self, new_directory: String) {
+    /// Change the backup directory of the active source, its bundles are loaded by the next refresh.
+    /// Changes the shared library if the source doesn't have its own.
+    ///
+    /// Refused if the directory is the save directory of a source or is nested with one.
+    pub fn update_backup_directory(
+        &mut self,
+        new_directory: String,
+    ) -> Result<(), DirectoryProblem> {
+        self.ensure_no_conflict_with_saves(&new_directory)?;
+        match &mut self.sources[self.active_source].backup_directory {
+            Some(backup_directory) => backup_directory.clone_from(&new_directory),
+            None => self.shared_backup_directory.clone_from(&new_directory),
+        }
         self.backup_directory = new_directory;
-        self.refresh_save_bundles();
+        self.check_directories();
+        self.save_lists
+            .update(|lists| lists.backup_save_bundles = Arc::default());
+        Ok(())
+    }
+
+    /// Change both directories of the active source at once, see `update_save_directory`.
+    pub fn update_directories(
+        &mut self,
+        save_directory: String,
+        backup_directory: String,
+    ) -> Result<(), DirectoryProblem> {
+        ensure_no_conflict(&save_directory, &backup_directory)?;
+        // Neither can conflict with the previous directories once both are changed.
+        self.backup_directory.clear();
+        self.update_save_directory(save_directory)?;
+        self.update_backup_directory(backup_directory)
+    }
+
+    /// Open the setup wizard, proposing the current backup directory.
+    /// The save directories are discovered separately, see `set_setup_candidates`.
+    pub fn start_setup(&mut self) {
+        self.setup = Some(SetupState {
+            discovering: true,
+            candidates: Vec::new(),
+            save_directory: self.save_directory.clone(),
+            validation: None,
+            backup_directory: self.backup_directory.clone(),
+        });
+    }
+
+    /// Show the discovered save directories in the setup wizard.
+    pub fn set_setup_candidates(&mut self, candidates: Vec<DirectoryCandidate>) {
+        if let Some(setup) = &mut self.setup {
+            setup.discovering = false;
+            setup.candidates = candidates;
+        }
     }
 
-    pub fn confirm_restore_backup(&mut self, backup_name: String) {
-        self.confirm_restore_backup_name = Some(backup_name);
+    /// Choose the save directory in the setup wizard, with the result of validating it.
+    pub fn choose_setup_save_directory(
+        &mut self,
+        save_directory: String,
+        validation: Result<Vec<String>, String>,
+    ) {
+        if let Some(setup) = &mut self.setup {
+            setup.save_directory = save_directory;
+            setup.validation = Some(validation);
+        }
+    }
+
+    pub fn choose_setup_backup_directory(&mut self, backup_directory: String) {
+        if let Some(setup) = &mut self.setup {
+            setup.backup_directory = backup_directory;
+        }
+    }
+
+    /// Close the setup wizard, it won't be opened again on launch.
+    pub fn close_setup(&mut self) {
+        self.setup = None;
+        self.setup_done = true;
+    }
+
+    /// Show a game save in the editor, replacing the one shown if any.
+    pub fn open_editor(&mut self, id: BundleId, name: String, save: SaveGame) {
+        self.editor = Some(SaveEditor {
+            id,
+            name,
+            save: Arc::new(save),
+        });
+    }
+
+    pub fn close_editor(&mut self) {
+        self.editor = None;
+    }
+
+    /// Ask for confirmation before applying a patch, showing the changes it makes.
+    pub fn preview_patch(&mut self, preview: PatchPreview) {
+        self.patch_preview = Some(preview);
+    }
+
+    pub fn clear_patch_preview(&mut self) {
+        self.patch_preview = None;
     }
 
-    pub fn confirm_delete_backup(&mut self, backup_name: String) {
-        self.confirm_backup_deletion_name = Some(backup_name);
+    /// Show the template window with the templates given, updating it if it's shown.
+    pub fn show_templates(&mut self, templates: Vec<TemplateInfo>) {
+        self.templates = Some(templates);
+    }
+
+    pub fn close_templates(&mut self) {
+        self.templates = None;
+    }
+
+    /// Keep the result of a check of the saves, showing it in the check window if `show` is set.
+    pub fn set_save_check(&mut self, save_check: SaveCheck, show: bool) {
+        self.save_check = Some(Arc::new(save_check));
+        self.show_save_check |= show;
+    }
+
+    pub fn close_save_check(&mut self) {
+        self.show_save_check = false;
+    }
+
+    /// Show two saves compared, replacing the comparison shown if any.
+    pub fn show_comparison(&mut self, comparison: SaveComparison) {
+        self.comparison = Some(comparison);
+    }
+
+    pub fn close_comparison(&mut self) {
+        self.comparison = None;
+    }
+
+    pub fn confirm_restore_backup(
+        &mut self,
+        backup_id: BundleId,
+        source: String,
+        diff: Option<SaveDiff>,
+    ) {
+        self.confirm_restore_backup_id = Some(backup_id);
+        self.confirm_restore_source = Some(source);
+        self.confirm_restore_diff = diff;
+    }
+
+    pub fn confirm_delete_backup(&mut self, backup_id: BundleId) {
+        self.confirm_backup_deletion_id = Some(backup_id);
     }
 
     pub fn clear_restore_confirmation(&mut self) {
-        self.confirm_restore_backup_name = None;
+        self.confirm_restore_backup_id = None;
+        self.confirm_restore_source = None;
+        self.confirm_restore_diff = None;
+        self.game_running = false;
     }
 
     pub fn clear_delete_confirmation(&mut self) {
-        self.confirm_backup_deletion_name = None;
+        self.confirm_backup_deletion_id = None;
+        self.game_running = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod sources {
+        use super::*;
+
+        #[test]
+        fn migrates_state_without_sources() {
+            // Arrange
+            let mut state = AppState::new("", "");
+            let persisted = AppState {
+                save_directory: "/saves".to_string(),
+                backup_directory: "/backups".to_string(),
+                sources: Vec::new(),
+                ..AppState::new("", "")
+            };
+
+            // Act
+            state.restore_settings(persisted);
+
+            // Assert
+            assert_eq!(state.sources, vec![SaveSource::new("Default", "/saves")]);
+            assert_eq!(state.shared_backup_directory, "/backups");
+            assert_eq!(state.backup_directory, "/backups");
+        }
+
+        #[test]
+        fn select_mirrors_directories() {
+            // Arrange
+            let mut state = AppState::new("/saves", "/backups");
+            state.add_source();
+            state
+                .update_save_directory("/other/saves".to_string())
+                .unwrap();
+            state.set_own_backup_library(true).unwrap();
+
+            // Act
+            state.select_source(0);
+            let default_directories =
+                (state.save_directory.clone(), state.backup_directory.clone());
+            state.select_source(1);
+
+            // Assert
+            assert_eq!(
+                default_directories,
+                ("/saves".to_string(), "/backups".to_string())
+            );
+            assert_eq!(state.active_source().name, "New source");
+            assert_eq!(state.save_directory, "/other/saves");
+            assert_eq!(
+                PathBuf::from(&state.backup_directory),
+                PathBuf::from("/backups (New source)")
+            );
+        }
+
+        #[test]
+        fn names_stay_unique() {
+            // Arrange
+            let mut state = AppState::new("/saves", "/backups");
+            state.add_source();
+            state.add_source();
+
+            // Act
+            let rename = state.source_rename(2, "Default").unwrap();
+            state.rename_source(&rename);
+            state.remove_source(0);
+
+            // Assert
+            let names: Vec<&str> = state.sources.iter().map(|s| s.name.as_str()).collect();
+            assert_eq!(names, vec!["New source", "Default 2"]);
+            assert_eq!(state.active_source().name, "Default 2");
+        }
+
+        #[test]
+        fn rename_follows_own_library() {
+            // Arrange
+            let mut state = AppState::new("/saves", "/backups");
+            state.add_source();
+            state.set_own_backup_library(true).unwrap();
+
+            // Act
+            let rename = state.source_rename(1, "Deck").unwrap();
+            state.rename_source(&rename);
+
+            // Assert
+            let (from, to) = rename.library.unwrap();
+            assert_eq!(PathBuf::from(from), PathBuf::from("/backups (New source)"));
+            assert_eq!(PathBuf::from(&to), PathBuf::from("/backups (Deck)"));
+            assert_eq!(
+                rename.backup_directories,
+                vec!["/backups".to_string(), to.clone()]
+            );
+            assert_eq!(state.active_source().name, "Deck");
+            assert_eq!(state.backup_directory, to);
+        }
+
+        #[test]
+        fn shared_library_refuses_saves_of_other_sources() {
+            // Arrange
+            let mut state = AppState::new("/saves", "/backups");
+            state.add_source();
+            state
+                .update_save_directory("/other/saves".to_string())
+                .unwrap();
+            state.select_source(0);
+
+            // Act
+            let result = state.update_backup_directory("/other/saves/backups".to_string());
+
+            // Assert
+            assert_eq!(result, Err(DirectoryProblem::BackupInsideSaves));
+            assert_eq!(state.shared_backup_directory, "/backups");
+        }
+    }
+
+    mod update_directories {
+        use super::*;
+        use tempfile::tempdir;
+
+        /// A state on the `saves` and `backups` directories of a temporary directory.
+        fn state_in(temp_dir: &tempfile::TempDir) -> (AppState, String, String) {
+            let path = |name: &str| temp_dir.path().join(name).to_string_lossy().to_string();
+            let (saves, backups) = (path("saves"), path("backups"));
+            (AppState::new(&saves, &backups), saves, backups)
+        }
+
+        #[test]
+        fn refuses_backup_inside_saves() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let (mut state, saves, backups) = state_in(&temp_dir);
+
+            // Act
+            let result = state.update_backup_directory(format!("{saves}/backups"));
+
+            // Assert
+            assert_eq!(result, Err(DirectoryProblem::BackupInsideSaves));
+            assert_eq!(state.backup_directory, backups);
+        }
+
+        #[test]
+        fn refuses_same_directory() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let (mut state, saves, backups) = state_in(&temp_dir);
+
+            // Act
+            let result = state.update_save_directory(backups);
+
+            // Assert
+            assert_eq!(result, Err(DirectoryProblem::SameDirectory));
+            assert_eq!(state.save_directory, saves);
+        }
+
+        #[test]
+        fn swaps_directories() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let (mut state, saves, backups) = state_in(&temp_dir);
+
+            // Act
+            let result = state.update_directories(backups.clone(), saves.clone());
+
+            // Assert
+            assert_eq!(result, Ok(()));
+            assert_eq!(
+                (state.save_directory, state.backup_directory),
+                (backups, saves)
+            );
+            assert!(state.directory_warnings.is_empty());
+        }
+    }
+
+    mod setup {
+        use super::*;
+
+        #[test]
+        fn only_fresh_state_needs_setup() {
+            // Arrange
+            let persisted = r#"(save_directory: "/saves", backup_directory: "/backups")"#;
+
+            // Act
+            let state: AppState = ron::from_str(persisted).unwrap();
+
+            // Assert
+            assert!(state.setup_done);
+            assert!(!AppState::default().setup_done);
+        }
+    }
+
+    mod shared_save_lists {
+        use super::*;
+        use crate::repo::save::fixtures;
+        use tempfile::tempdir;
+
+        #[test]
+        fn update_publishes_new_snapshot() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let location = fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_1");
+            let save_bundle = SaveBundle::new(location).unwrap();
+            let shared_save_lists = SharedSaveLists::default();
+            let before = shared_save_lists.load();
+
+            // Act
+            let generation = shared_save_lists
+                .update(|lists| lists.game_save_bundles = vec![save_bundle.clone()].into());
+
+            // Assert
+            let after = shared_save_lists.load();
+            assert_eq!(generation, before.generation + 1);
+            assert_eq!(after.generation, generation);
+            assert!(before.game_save_bundles.is_empty());
+            assert_eq!(&after.game_save_bundles[..], &[save_bundle]);
+        }
     }
 }
@@ -1,15 +1,22 @@
 use aes;
-use aes::cipher::{BlockDecryptMut, KeyIvInit, block_padding::Pkcs7};
+use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit, block_padding::Pkcs7};
 use cbc;
 use flate2;
 use pbkdf2;
 use sha1::Sha1;
-use std::io::Read;
+use std::io::{Read, Write};
 use std::path::Path;
 use std::str;
 use thiserror::Error;
 
 type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
+type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
+
+/// Length of the Initialization Vector stored at the start of encrypted es3 data.
+pub const IV_LEN: usize = 16;
+
+/// GZip magic number
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
 
 #[derive(Error, Debug)]
 pub enum DecryptError {
@@ -21,6 +28,18 @@ pub enum DecryptError {
     DecryptionError(String),
     #[error("Decompression failed: {0}")]
     DecompressionError(String),
+    #[error("None of the passwords could decrypt the data")]
+    NoMatchingPassword,
+}
+
+#[derive(Error, Debug)]
+pub enum EncryptError {
+    #[error("Failed to write file: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Failed to generate IV: {0}")]
+    RandomError(String),
+    #[error("Compression failed: {0}")]
+    CompressionError(String),
 }
 
 /// Decrypt an es3 file, returning decrypted bytes (which should be JSON)
@@ -40,38 +59,283 @@ pub enum DecryptError {
 pub fn decrypt_es3(file_path: &Path, password: &str) -> Result<Vec<u8>, DecryptError> {
     // Read encrypted data
     let encrypted_data = std::fs::read(file_path)?;
+    decrypt_es3_bytes(&encrypted_data, password)
+}
+
+/// Decrypt es3 data held in memory, see `decrypt_es3`.
+pub fn decrypt_es3_bytes(encrypted_data: &[u8], password: &str) -> Result<Vec<u8>, DecryptError> {
+    let decrypted_bytes = decrypt_raw(encrypted_data, password)?;
+    if is_gzipped(&decrypted_bytes) {
+        gunzip(&decrypted_bytes)
+    } else {
+        Ok(decrypted_bytes)
+    }
+}
+
+/// Encrypt data (which should be JSON) and write it to an es3 file.
+/// The data is optionally gzipped before being encrypted.
+///
+/// See `decrypt_es3` for details on the format.
+pub fn encrypt_es3(
+    file_path: &Path,
+    data: &[u8],
+    password: &str,
+    gzip: bool,
+) -> Result<(), EncryptError> {
+    let encrypted_data = encrypt_es3_bytes(data, password, gzip)?;
+    std::fs::write(file_path, encrypted_data)?;
+    Ok(())
+}
+
+/// Encrypt data held in memory, see `encrypt_es3`.
+/// A new random IV is generated for every call.
+pub fn encrypt_es3_bytes(data: &[u8], password: &str, gzip: bool) -> Result<Vec<u8>, EncryptError> {
+    let mut iv = [0u8; IV_LEN];
+    getrandom::fill(&mut iv).map_err(|e| EncryptError::RandomError(e.to_string()))?;
+
+    let plain_text = if gzip {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all(data)
+            .map_err(|e| EncryptError::CompressionError(e.to_string()))?;
+        encoder
+            .finish()
+            .map_err(|e| EncryptError::CompressionError(e.to_string()))?
+    } else {
+        data.to_vec()
+    };
+
+    let key = derive_key(password, &iv);
+    let encryptor = Aes128CbcEnc::new(&key.into(), &iv.into());
+    let cypher_text = encryptor.encrypt_padded_vec_mut::<Pkcs7>(&plain_text);
+
+    let mut encrypted_data = Vec::with_capacity(IV_LEN + cypher_text.len());
+    encrypted_data.extend_from_slice(&iv);
+    encrypted_data.extend_from_slice(&cypher_text);
+    Ok(encrypted_data)
+}
+
+/// Description of how an es3 file is stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Es3Info {
+    /// Size of the file in bytes
+    pub size: usize,
+    /// IV of the encrypted data, None if the file isn't encrypted
+    pub iv: Option<[u8; IV_LEN]>,
+    /// Name of the password profile that decrypted the file, None if the file isn't encrypted
+    pub password_profile: Option<String>,
+    /// True if the content is gzipped
+    pub gzipped: bool,
+    /// True if the decoded content is valid JSON
+    pub valid_json: bool,
+}
+
+/// Decode any Easy Save 3 file, returning the decoded content and how it was stored.
+///
+/// `password_profiles` is a list of `(name, password)` tried in order. Easy Save files can
+/// also be stored unencrypted, possibly gzipped, which is detected before trying passwords.
+///
+/// The first password producing valid JSON is used. If none do but a password decrypts
+/// the data (valid padding), that result is returned with `valid_json` set to false.
+pub fn decode_es3(
+    data: &[u8],
+    password_profiles: &[(&str, &str)],
+) -> Result<(Vec<u8>, Es3Info), DecryptError> {
+    let mut info = Es3Info {
+        size: data.len(),
+        iv: None,
+        password_profile: None,
+        gzipped: is_gzipped(data),
+        valid_json: false,
+    };
 
+    // Unencrypted file
+    let plain_text = if info.gzipped {
+        gunzip(data).ok()
+    } else {
+        Some(data.to_vec())
+    };
+    if let Some(plain_text) = plain_text
+        && is_json(&plain_text)
+    {
+        info.valid_json = true;
+        return Ok((plain_text, info));
+    }
+
+    let mut fallback = None;
+    for (name, password) in password_profiles {
+        let Ok(decrypted_bytes) = decrypt_raw(data, password) else {
+            continue;
+        };
+        let gzipped = is_gzipped(&decrypted_bytes);
+        let content = if gzipped {
+            match gunzip(&decrypted_bytes) {
+                Ok(content) => content,
+                Err(_) => continue,
+            }
+        } else {
+            decrypted_bytes
+        };
+        let candidate = Es3Info {
+            iv: data[..IV_LEN].try_into().ok(),
+            password_profile: Some(name.to_string()),
+            gzipped,
+            valid_json: is_json(&content),
+            ..info.clone()
+        };
+        if candidate.valid_json {
+            return Ok((content, candidate));
+        }
+        fallback.get_or_insert((content, candidate));
+    }
+    fallback.ok_or(DecryptError::NoMatchingPassword)
+}
+
+/// Decrypt data without decompressing it.
+fn decrypt_raw(encrypted_data: &[u8], password: &str) -> Result<Vec<u8>, DecryptError> {
     // Ensure we have at least enough data for IV
-    if encrypted_data.len() < 16 {
+    if encrypted_data.len() < IV_LEN {
         return Err(DecryptError::InvalidFormat);
     }
 
     // Extract IV and encrypted data
-    let iv = &encrypted_data[..16];
-    let cypher_text = &encrypted_data[16..];
+    let iv = &encrypted_data[..IV_LEN];
+    let cypher_text = &encrypted_data[IV_LEN..];
 
-    // Derive key using PBKDF2
-    // TODO: could be optimised by storing the byte array generated by this function
-    //       call so as to not have to compute it at runtime.
-    let mut key = [0u8; 16];
-    pbkdf2::pbkdf2_hmac::<Sha1>(password.as_bytes(), iv, 100, &mut key);
+    let key = derive_key(password, iv);
 
     // Create cipher instance and decrypt
     let decryptor = Aes128CbcDec::new(&key.into(), iv.into());
 
-    let decrypted_bytes = decryptor
-        .decrypt_padded_vec_mut::<Pkcs7>(&cypher_text)
-        .unwrap();
-
-    // Check for GZip compression (magic number 0x1f, 0x8b)
-    if decrypted_bytes.starts_with(&[0x1f, 0x8b]) {
-        let mut decoder = flate2::read::GzDecoder::new(&decrypted_bytes[..]);
-        let mut decompressed = Vec::new();
-        decoder
-            .read_to_end(&mut decompressed)
-            .map_err(|e| DecryptError::DecompressionError(e.to_string()))?;
-        Ok(decompressed)
-    } else {
-        Ok(decrypted_bytes)
+    decryptor
+        .decrypt_padded_vec_mut::<Pkcs7>(cypher_text)
+        .map_err(|e| DecryptError::DecryptionError(e.to_string()))
+}
+
+/// Derive key using PBKDF2, the IV is used as salt.
+// TODO: could be optimised by storing the byte array generated by this function
+//       call so as to not have to compute it at runtime.
+fn derive_key(password: &str, iv: &[u8]) -> [u8; 16] {
+    let mut key = [0u8; 16];
+    pbkdf2::pbkdf2_hmac::<Sha1>(password.as_bytes(), iv, 100, &mut key);
+    key
+}
+
+/// Check for GZip compression (magic number 0x1f, 0x8b)
+fn is_gzipped(data: &[u8]) -> bool {
+    data.starts_with(&GZIP_MAGIC)
+}
+
+fn gunzip(data: &[u8]) -> Result<Vec<u8>, DecryptError> {
+    let mut decoder = flate2::read::GzDecoder::new(data);
+    let mut decompressed = Vec::new();
+    decoder
+        .read_to_end(&mut decompressed)
+        .map_err(|e| DecryptError::DecompressionError(e.to_string()))?;
+    Ok(decompressed)
+}
+
+fn is_json(data: &[u8]) -> bool {
+    serde_json::from_slice::<serde::de::IgnoredAny>(data).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &[u8] = br#"{"teamName":{"__type":"string","value":"R.E.P.O."}}"#;
+    const PROFILES: [(&str, &str); 2] = [("first", "not it"), ("second", "secret")];
+
+    mod round_trip {
+        use super::*;
+
+        #[test]
+        fn plain() {
+            // Act
+            let encrypted_data = encrypt_es3_bytes(JSON, "secret", false).unwrap();
+            let decrypted_data = decrypt_es3_bytes(&encrypted_data, "secret").unwrap();
+
+            // Assert
+            assert_eq!(decrypted_data, JSON);
+        }
+
+        #[test]
+        fn gzipped() {
+            // Act
+            let encrypted_data = encrypt_es3_bytes(JSON, "secret", true).unwrap();
+            let decrypted_data = decrypt_es3_bytes(&encrypted_data, "secret").unwrap();
+
+            // Assert
+            assert_eq!(decrypted_data, JSON);
+        }
+
+        #[test]
+        fn wrong_password_fails() {
+            // Arrange
+            let encrypted_data = encrypt_es3_bytes(JSON, "secret", false).unwrap();
+
+            // Act
+            let result = decrypt_es3_bytes(&encrypted_data, "not it");
+
+            // Assert
+            assert!(result.is_err() || result.unwrap() != JSON);
+        }
+    }
+
+    mod decode_es3 {
+        use super::*;
+
+        #[test]
+        fn matches_password_profile() {
+            // Arrange
+            let encrypted_data = encrypt_es3_bytes(JSON, "secret", true).unwrap();
+
+            // Act
+            let (content, info) = decode_es3(&encrypted_data, &PROFILES).unwrap();
+
+            // Assert
+            assert_eq!(content, JSON);
+            assert_eq!(info.password_profile.as_deref(), Some("second"));
+            assert_eq!(
+                info.iv.as_ref().map(|iv| &iv[..]),
+                Some(&encrypted_data[..16])
+            );
+            assert!(info.gzipped);
+            assert!(info.valid_json);
+        }
+
+        #[test]
+        fn detects_unencrypted_json() {
+            // Act
+            let (content, info) = decode_es3(JSON, &PROFILES).unwrap();
+
+            // Assert
+            assert_eq!(content, JSON);
+            assert_eq!(info.iv, None);
+            assert_eq!(info.password_profile, None);
+            assert!(!info.gzipped);
+            assert!(info.valid_json);
+        }
+
+        #[test]
+        fn no_matching_password() {
+            // Arrange
+            let encrypted_data = encrypt_es3_bytes(JSON, "something else", false).unwrap();
+
+            // Act
+            let result = decode_es3(&encrypted_data, &PROFILES[..1]);
+
+            // Assert
+            assert!(!matches!(
+                result,
+                Ok((
+                    _,
+                    Es3Info {
+                        valid_json: true,
+                        ..
+                    }
+                ))
+            ));
+        }
     }
 }
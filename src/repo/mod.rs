@@ -1,4 +1,6 @@
 // REPO specific stuff
 pub mod crypt;
+pub mod process;
 pub mod save;
+pub mod steam;
 pub mod utils;
@@ -0,0 +1,255 @@
+//! Detection of a running R.E.P.O. game process.
+//!
+//! The game keeps its save in memory and writes it back to disk, so any
+//! change made to the save directory while it's running can be overwritten.
+
+use std::path::Path;
+
+use crate::constant;
+
+/// Returns true if a R.E.P.O. game process is currently running.
+pub fn is_game_running() -> bool {
+    #[cfg(target_os = "linux")]
+    return find_game_pid(Path::new("/proc")).is_some();
+
+    #[cfg(not(target_os = "linux"))]
+    return list_process_commands()
+        .iter()
+        .any(|command| command_matches_game(command));
+}
+
+/// Scan a procfs style directory for a process running the game executable.
+///
+/// On Linux the game runs through Proton/Wine, which means the process can be
+/// identified either by its `comm` (`REPO.exe`) or by the executable of its command line:
+/// its first argument, or the one Proton is asked to run
+/// (eg. `proton waitforexitandrun .../REPO/REPO.exe`). Other arguments are ignored,
+/// eg. `vim .../REPO/REPO.exe` isn't the game.
+///
+/// Returns the PID of the first matching process.
+pub fn find_game_pid(proc_root: &Path) -> Option<u32> {
+    let own_pid = std::process::id();
+    let entries = match std::fs::read_dir(proc_root) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::error!(e:err; "Failed to read process directory.");
+            return None;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if pid == own_pid {
+            continue;
+        }
+
+        // Processes can exit while we scan, unreadable entries are simply skipped.
+        if let Ok(comm) = std::fs::read_to_string(entry.path().join("comm"))
+            && command_matches_game(comm.trim())
+        {
+            return Some(pid);
+        }
+        if let Ok(cmdline) = std::fs::read(entry.path().join("cmdline"))
+            && cmdline_runs_game(&cmdline)
+        {
+            return Some(pid);
+        }
+    }
+    None
+}
+
+/// Returns true if a NUL separated command line runs the game executable.
+fn cmdline_runs_game(cmdline: &[u8]) -> bool {
+    let args: Vec<String> = cmdline
+        .split(|b| *b == 0)
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    let proton_target = args
+        .iter()
+        .position(|arg| arg == "waitforexitandrun")
+        .and_then(|index| args.get(index + 1));
+    args.first()
+        .into_iter()
+        .chain(proton_target)
+        .any(|arg| command_matches_game(arg))
+}
+
+/// Returns true if the last component of a command (or command argument)
+/// is the game executable. Windows and Unix path separators are both handled.
+fn command_matches_game(command: &str) -> bool {
+    command
+        .rsplit(['/', '\\'])
+        .next()
+        .is_some_and(|file_name| file_name.eq_ignore_ascii_case(constant::GAME_EXECUTABLE))
+}
+
+/// List the image names of running processes.
+#[cfg(target_os = "windows")]
+fn list_process_commands() -> Vec<String> {
+    let output = std::process::Command::new("tasklist")
+        .args(["/FO", "CSV", "/NH"])
+        .output();
+    match output {
+        // Lines look like: "REPO.exe","1234","Console","1","1,234,567 K"
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split(',').next())
+            .map(|name| name.trim_matches('"').to_string())
+            .collect(),
+        Err(e) => {
+            log::error!(e:err; "Failed to list running processes.");
+            Vec::new()
+        }
+    }
+}
+
+/// List the executables of running processes, without their arguments.
+/// On macOS the game runs through CrossOver/Wine, so `REPO.exe` shows up as the executable.
+#[cfg(all(not(target_os = "windows"), not(target_os = "linux")))]
+fn list_process_commands() -> Vec<String> {
+    let output = std::process::Command::new("ps")
+        .args(["-axo", "comm="])
+        .output();
+    match output {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.trim().to_string())
+            .collect(),
+        Err(e) => {
+            log::error!(e:err; "Failed to list running processes.");
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    /// Create a fake `/proc/<pid>` entry.
+    fn fake_process(proc_root: &Path, pid: u32, comm: &str, cmdline: &[&str]) {
+        let process_dir = proc_root.join(pid.to_string());
+        fs::create_dir_all(&process_dir).unwrap();
+        fs::write(process_dir.join("comm"), format!("{comm}\n")).unwrap();
+        fs::write(process_dir.join("cmdline"), cmdline.join("\0")).unwrap();
+    }
+
+    mod find_game_pid {
+        use super::*;
+
+        #[test]
+        fn matches_comm() {
+            // Arrange
+            let proc_root = tempdir().unwrap();
+            fake_process(proc_root.path(), 12, "bash", &["/bin/bash"]);
+            fake_process(
+                proc_root.path(),
+                42,
+                "REPO.exe",
+                &["Z:\\games\\REPO\\REPO.exe"],
+            );
+
+            // Act
+            let result = find_game_pid(proc_root.path());
+
+            // Assert
+            assert_eq!(result, Some(42));
+        }
+
+        #[test]
+        fn matches_proton_cmdline() {
+            // Arrange
+            let proc_root = tempdir().unwrap();
+            fake_process(
+                proc_root.path(),
+                77,
+                "python3",
+                &[
+                    "python3",
+                    "/home/user/.steam/steam/steamapps/common/Proton 9.0/proton",
+                    "waitforexitandrun",
+                    "/home/user/.steam/steam/steamapps/common/REPO/REPO.exe",
+                ],
+            );
+
+            // Act
+            let result = find_game_pid(proc_root.path());
+
+            // Assert
+            assert_eq!(result, Some(77));
+        }
+
+        #[test]
+        fn matches_wine_executable() {
+            // Arrange
+            let proc_root = tempdir().unwrap();
+            fake_process(
+                proc_root.path(),
+                88,
+                "wine64-preload",
+                &["C:\\Program Files\\REPO\\REPO.exe", "-screen-fullscreen"],
+            );
+
+            // Act
+            let result = find_game_pid(proc_root.path());
+
+            // Assert
+            assert_eq!(result, Some(88));
+        }
+
+        #[test]
+        fn ignores_commands_opening_the_executable() {
+            // Arrange
+            let proc_root = tempdir().unwrap();
+            let executable = "/home/user/.steam/steam/steamapps/common/REPO/REPO.exe";
+            fake_process(proc_root.path(), 7, "vim", &["vim", executable]);
+            fake_process(proc_root.path(), 8, "strings", &["strings", "REPO.exe"]);
+            fake_process(
+                proc_root.path(),
+                9,
+                "python3",
+                &[
+                    "python3",
+                    "/usr/bin/protontricks",
+                    "-c",
+                    executable,
+                    "3241660",
+                ],
+            );
+
+            // Act
+            let result = find_game_pid(proc_root.path());
+
+            // Assert
+            assert_eq!(result, None);
+        }
+
+        #[test]
+        fn ignores_other_processes() {
+            // Arrange
+            let proc_root = tempdir().unwrap();
+            fake_process(
+                proc_root.path(),
+                5,
+                "repo-save-manag",
+                &["repo-save-manager"],
+            );
+            fake_process(proc_root.path(), 6, "vim", &["vim", "REPO.exe.txt"]);
+            fs::create_dir(proc_root.path().join("self")).unwrap();
+
+            // Act
+            let result = find_game_pid(proc_root.path());
+
+            // Assert
+            assert_eq!(result, None);
+        }
+    }
+}
@@ -1,11 +1,14 @@
 //! Structs representing a REPO save file's JSON.
+//!
+//! Unknown fields are preserved in `extra` maps, so that a save can be
+//! read, modified and written back without losing data.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Representation of a Save file in Rust
 /// Field naming to most closely match REPO save file field names.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct SaveGame {
     #[serde(rename = "dictionaryOfDictionaries")]
     pub dictionary_of_dictionaries: Dictionary,
@@ -17,16 +20,19 @@ pub struct SaveGame {
     pub date_and_time: StringValue,
     #[serde(rename = "teamName")]
     pub team_name: StringValue,
+    /// Fields not modelled above, kept so that they are written back unchanged.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Dictionary {
     #[serde(rename = "__type")]
     pub _type: String,
     pub value: DictionaryValue,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct DictionaryValue {
     #[serde(rename = "runStats")]
     pub run_stats: HashMap<String, i32>,
@@ -63,25 +69,76 @@ pub struct DictionaryValue {
     pub item: HashMap<String, i32>,
     #[serde(rename = "itemStatBattery")]
     pub item_stat_battery: HashMap<String, i32>,
+    /// Dictionaries not modelled above, kept so that they are written back unchanged.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct PlayerNames {
     #[serde(rename = "__type")]
     pub _type: String,
     pub value: HashMap<String, String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct TimePlayedValue {
     #[serde(rename = "__type")]
     pub _type: String,
     pub value: f32,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct StringValue {
     #[serde(rename = "__type")]
     pub _type: String,
     pub value: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serialising a parsed save gives back the original JSON, including unknown fields.
+    #[test]
+    fn round_trip_preserves_json() {
+        // Arrange
+        let original: serde_json::Value = serde_json::from_str(fixtures::SAVE_JSON).unwrap();
+
+        // Act
+        let save_game = fixtures::save_game();
+        let serialised = serde_json::to_value(&save_game).unwrap();
+
+        // Assert
+        assert_eq!(serialised, original);
+    }
+}
+
+/// Sample saves shared by the tests of the crate.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::SaveGame;
+    use crate::{constant, repo};
+    use std::path::{Path, PathBuf};
+
+    /// JSON of a small save with two players, as written by the game.
+    pub const SAVE_JSON: &str = include_str!("../../tests/fixtures/save.json");
+
+    pub fn save_game() -> SaveGame {
+        serde_json::from_str(SAVE_JSON).unwrap()
+    }
+
+    /// Create a save bundle directory named `name` in `root`, containing the sample save.
+    pub fn save_bundle_dir(root: &Path, name: &str) -> PathBuf {
+        let location = root.join(name);
+        std::fs::create_dir_all(&location).unwrap();
+        repo::crypt::encrypt_es3(
+            &location.join(format!("{name}.es3")),
+            SAVE_JSON.as_bytes(),
+            constant::ENCRYPTION_PASS,
+            false,
+        )
+        .unwrap();
+        location
+    }
+}
@@ -0,0 +1,428 @@
+//! Discovery of the R.E.P.O. save directories of Steam installations running the game
+//! through Proton.
+//!
+//! Steam can be installed natively, as a Flatpak or as a Snap, and each installation
+//! can have several libraries (eg. on other drives), listed in its `libraryfolders.vdf`.
+//! The game's Proton prefix is in the `compatdata` directory of the library it's installed in.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::constant;
+
+/// Steam installation directories relative to the home directory, with the kind of installation.
+const STEAM_ROOTS: [(&str, &str); 8] = [
+    (".steam/steam", "native"),
+    (".steam/root", "native"),
+    (".local/share/Steam", "native"),
+    (".steam/debian-installation", "native"),
+    (
+        ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+        "Flatpak",
+    ),
+    (".var/app/com.valvesoftware.Steam/.steam/steam", "Flatpak"),
+    ("snap/steam/common/.local/share/Steam", "Snap"),
+    ("snap/steam/common/.steam/steam", "Snap"),
+];
+
+/// Location of the save directory inside the game's Proton prefix.
+const PREFIX_SAVE_PATH: &str = "pfx/drive_c/users/steamuser/AppData/LocalLow/semiwork/Repo/saves";
+
+/// Why a directory is thought to hold R.E.P.O. saves.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CandidateReason {
+    /// The game's prefix is in the library of a Steam installation
+    SteamInstallation { kind: &'static str },
+    /// The game's prefix is in a library listed in a `libraryfolders.vdf`
+    LibraryFolder { kind: &'static str, vdf: PathBuf },
+}
+
+/// A directory that may hold R.E.P.O. saves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveCandidate {
+    /// The save directory, it may not exist yet if the game never saved
+    pub path: PathBuf,
+    /// The Steam library the game's prefix was found in
+    pub library: PathBuf,
+    pub reason: CandidateReason,
+}
+
+impl fmt::Display for CandidateReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CandidateReason::SteamInstallation { kind } => {
+                write!(f, "Proton prefix of the {kind} Steam installation")
+            }
+            CandidateReason::LibraryFolder { kind, vdf } => write!(
+                f,
+                "Proton prefix in a library of the {kind} Steam installation, listed in {}",
+                vdf.display()
+            ),
+        }
+    }
+}
+
+/// The game's Proton prefix in a Steam library.
+pub fn library_prefix_path(library: impl AsRef<Path>) -> PathBuf {
+    library
+        .as_ref()
+        .join("steamapps/compatdata")
+        .join(constant::STEAM_APP_ID)
+}
+
+/// Save directory of the game's Proton prefix in a Steam library.
+pub fn library_save_path(library: impl AsRef<Path>) -> PathBuf {
+    library_prefix_path(library).join(PREFIX_SAVE_PATH)
+}
+
+/// Steam installation directories that may exist under `home`, with their kind.
+pub fn steam_roots(home: impl AsRef<Path>) -> Vec<(PathBuf, &'static str)> {
+    STEAM_ROOTS
+        .iter()
+        .map(|(root, kind)| (home.as_ref().join(root), *kind))
+        .collect()
+}
+
+/// Find the libraries of the Steam installations under `home` holding the game's prefix.
+///
+/// Libraries reachable from several installations (eg. through the `~/.steam/steam` symlink)
+/// are only reported once, with the first reason found.
+pub fn discover_save_candidates(home: impl AsRef<Path>) -> Vec<SaveCandidate> {
+    let mut candidates: Vec<SaveCandidate> = Vec::new();
+    let mut seen: Vec<PathBuf> = Vec::new();
+    let mut add = |library: PathBuf, reason: CandidateReason| {
+        // The prefix is created when the game is first run, before it saves anything.
+        let prefix = library_prefix_path(&library);
+        let Ok(canonical) = prefix.canonicalize() else {
+            return;
+        };
+        if !prefix.is_dir() || seen.contains(&canonical) {
+            return;
+        }
+        seen.push(canonical);
+        candidates.push(SaveCandidate {
+            path: library_save_path(&library),
+            library,
+            reason,
+        });
+    };
+
+    for (root, kind) in steam_roots(home) {
+        if !root.is_dir() {
+            continue;
+        }
+        add(root.clone(), CandidateReason::SteamInstallation { kind });
+
+        for vdf in [
+            root.join("steamapps/libraryfolders.vdf"),
+            root.join("config/libraryfolders.vdf"),
+        ] {
+            let Ok(contents) = std::fs::read_to_string(&vdf) else {
+                continue;
+            };
+            for library in parse_library_folders(&contents) {
+                add(
+                    library,
+                    CandidateReason::LibraryFolder {
+                        kind,
+                        vdf: vdf.clone(),
+                    },
+                );
+            }
+        }
+    }
+    candidates
+}
+
+/// Extract the library paths of a Steam `libraryfolders.vdf` file.
+///
+/// Both formats are supported, the current one where each library is a section with a
+/// `path` key, and the older one where libraries are numbered keys with the path as value.
+/// Malformed files yield the libraries read before the error.
+pub fn parse_library_folders(contents: &str) -> Vec<PathBuf> {
+    let mut libraries = Vec::new();
+    // Keys of the sections the parser is in, outermost first.
+    let mut sections: Vec<String> = Vec::new();
+    let mut key: Option<String> = None;
+
+    for token in (VdfTokens { rest: contents }) {
+        match token {
+            VdfToken::Open => sections.push(key.take().unwrap_or_default()),
+            VdfToken::Close => {
+                if sections.pop().is_none() {
+                    break;
+                }
+            }
+            VdfToken::String(value) => {
+                let Some(k) = key.take() else {
+                    key = Some(value);
+                    continue;
+                };
+                let in_libraries = |depth: usize| {
+                    sections.len() == depth && sections[0].eq_ignore_ascii_case("libraryfolders")
+                };
+                let is_library = (in_libraries(2) && k.eq_ignore_ascii_case("path"))
+                    || (in_libraries(1) && k.parse::<u32>().is_ok());
+                if is_library {
+                    libraries.push(PathBuf::from(value));
+                }
+            }
+        }
+    }
+    libraries
+}
+
+#[derive(Debug, PartialEq)]
+enum VdfToken {
+    Open,
+    Close,
+    String(String),
+}
+
+/// Tokens of Valve's KeyValues text format: quoted strings, braces and `//` comments.
+struct VdfTokens<'a> {
+    rest: &'a str,
+}
+
+impl Iterator for VdfTokens<'_> {
+    type Item = VdfToken;
+
+    fn next(&mut self) -> Option<VdfToken> {
+        loop {
+            self.rest = self.rest.trim_start();
+            if let Some(comment) = self.rest.strip_prefix("//") {
+                self.rest = comment.split_once('\n').map_or("", |(_, rest)| rest);
+            } else {
+                break;
+            }
+        }
+
+        let mut chars = self.rest.char_indices();
+        let (_, first) = chars.next()?;
+        match first {
+            '{' => {
+                self.rest = &self.rest[1..];
+                Some(VdfToken::Open)
+            }
+            '}' => {
+                self.rest = &self.rest[1..];
+                Some(VdfToken::Close)
+            }
+            '"' => {
+                let mut value = String::new();
+                let mut escaped = false;
+                for (i, c) in chars {
+                    match (escaped, c) {
+                        (false, '\\') => escaped = true,
+                        (false, '"') => {
+                            self.rest = &self.rest[i + 1..];
+                            return Some(VdfToken::String(value));
+                        }
+                        (true, 'n') => {
+                            value.push('\n');
+                            escaped = false;
+                        }
+                        (true, 't') => {
+                            value.push('\t');
+                            escaped = false;
+                        }
+                        _ => {
+                            value.push(c);
+                            escaped = false;
+                        }
+                    }
+                }
+                // Unterminated string
+                self.rest = "";
+                None
+            }
+            _ => {
+                // Unquoted token, read up to the next whitespace or brace
+                let end = self
+                    .rest
+                    .find(|c: char| c.is_whitespace() || c == '{' || c == '}')
+                    .unwrap_or(self.rest.len());
+                let value = self.rest[..end].to_string();
+                self.rest = &self.rest[end..];
+                Some(VdfToken::String(value))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    /// Create the game's Proton prefix in a fake Steam library.
+    fn fake_prefix(library: &Path) -> PathBuf {
+        let save_path = library_save_path(library);
+        fs::create_dir_all(&save_path).unwrap();
+        save_path
+    }
+
+    /// Write a `libraryfolders.vdf` listing `libraries` in a fake Steam installation.
+    fn fake_library_folders(root: &Path, libraries: &[&Path]) {
+        let mut contents = "\"libraryfolders\"\n{\n".to_string();
+        for (i, library) in libraries.iter().enumerate() {
+            contents.push_str(&format!(
+                "\t\"{i}\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t\t\"label\"\t\t\"\"\n\
+                \t\t\"apps\"\n\t\t{{\n\t\t\t\"3241660\"\t\t\"123\"\n\t\t}}\n\t}}\n",
+                library.display()
+            ));
+        }
+        contents.push_str("}\n");
+        let vdf = root.join("steamapps/libraryfolders.vdf");
+        fs::create_dir_all(vdf.parent().unwrap()).unwrap();
+        fs::write(&vdf, contents).unwrap();
+    }
+
+    mod parse_library_folders {
+        use super::*;
+
+        #[test]
+        fn current_format() {
+            // Arrange
+            let contents = r#"
+                "libraryfolders"
+                {
+                    "0"
+                    {
+                        "path"		"/home/user/.local/share/Steam"
+                        "apps" { "3241660" "4242" }
+                    }
+                    // Second drive
+                    "1"
+                    {
+                        "path"		"/mnt/games/Steam \"Library\""
+                    }
+                }
+            "#;
+
+            // Act
+            let libraries = parse_library_folders(contents);
+
+            // Assert
+            assert_eq!(
+                libraries,
+                vec![
+                    PathBuf::from("/home/user/.local/share/Steam"),
+                    PathBuf::from("/mnt/games/Steam \"Library\""),
+                ]
+            );
+        }
+
+        #[test]
+        fn legacy_format() {
+            // Arrange
+            let contents = r#"
+                "LibraryFolders"
+                {
+                    "TimeNextStatsReport"		"1234"
+                    "ContentStatsID"		"-42"
+                    "1"		"/mnt/games/SteamLibrary"
+                }
+            "#;
+
+            // Act
+            let libraries = parse_library_folders(contents);
+
+            // Assert
+            assert_eq!(libraries, vec![PathBuf::from("/mnt/games/SteamLibrary")]);
+        }
+
+        #[test]
+        fn malformed_file() {
+            // Act
+            let libraries = parse_library_folders("\"libraryfolders\" { \"0\" { \"path\" \"/a");
+
+            // Assert
+            assert!(libraries.is_empty());
+        }
+    }
+
+    mod discover_save_candidates {
+        use super::*;
+
+        #[test]
+        #[cfg(unix)]
+        fn finds_prefix_in_every_library() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let home = temp_dir.path().join("home");
+            let native = home.join(".local/share/Steam");
+            let flatpak = home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam");
+            let other_drive = temp_dir.path().join("mnt/SteamLibrary");
+            let without_game = temp_dir.path().join("mnt/Empty");
+            fake_prefix(&native);
+            fake_prefix(&other_drive);
+            fake_prefix(&flatpak);
+            fs::create_dir_all(&without_game).unwrap();
+            fake_library_folders(&native, &[&native, &other_drive, &without_game]);
+            // ~/.steam/steam is usually a link to the native installation
+            fs::create_dir_all(home.join(".steam")).unwrap();
+            std::os::unix::fs::symlink(&native, home.join(".steam/steam")).unwrap();
+
+            // Act
+            let candidates = discover_save_candidates(&home);
+
+            // Assert
+            assert_eq!(
+                candidates,
+                vec![
+                    SaveCandidate {
+                        path: library_save_path(home.join(".steam/steam")),
+                        library: home.join(".steam/steam"),
+                        reason: CandidateReason::SteamInstallation { kind: "native" },
+                    },
+                    SaveCandidate {
+                        path: library_save_path(&other_drive),
+                        library: other_drive.clone(),
+                        reason: CandidateReason::LibraryFolder {
+                            kind: "native",
+                            vdf: home.join(".steam/steam/steamapps/libraryfolders.vdf"),
+                        },
+                    },
+                    SaveCandidate {
+                        path: library_save_path(&flatpak),
+                        library: flatpak.clone(),
+                        reason: CandidateReason::SteamInstallation { kind: "Flatpak" },
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn finds_snap_installation() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let snap = temp_dir.path().join("snap/steam/common/.local/share/Steam");
+            let save_path = fake_prefix(&snap);
+
+            // Act
+            let candidates = discover_save_candidates(temp_dir.path());
+
+            // Assert
+            assert_eq!(candidates.len(), 1);
+            assert_eq!(candidates[0].path, save_path);
+            assert_eq!(
+                candidates[0].reason.to_string(),
+                "Proton prefix of the Snap Steam installation"
+            );
+        }
+
+        #[test]
+        fn nothing_installed() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+
+            // Act
+            let candidates = discover_save_candidates(temp_dir.path());
+
+            // Assert
+            assert!(candidates.is_empty());
+        }
+    }
+}
@@ -66,8 +66,18 @@ fn get_linux_repo_save_paths() -> Vec<PathBuf> {
     if let Ok(home) = env::var("HOME") {
         let home = PathBuf::from(home);
 
-        // Steam Proton paths
-        paths.push(home.join(".steam/debian-installation/steamapps/compatdata/3241660/pfx/drive_c/users/steamuser/AppData/LocalLow/semiwork/Repo/saves/"));
+        // Steam Proton prefixes of the game, in every Steam library
+        for candidate in super::steam::discover_save_candidates(&home) {
+            log::debug!(
+                "Save directory candidate {:?}: {}",
+                candidate.path,
+                candidate.reason
+            );
+            paths.push(candidate.path);
+        }
+
+        // Where a native Steam installation keeps it, if the game wasn't found
+        paths.push(super::steam::library_save_path(home.join(".steam/steam")));
     }
 
     paths
@@ -0,0 +1,332 @@
+//! Semantic differences between two saves, eg. a game save and the backup about to replace it.
+//!
+//! Unlike `patch::json_changes`, values are compared as the game uses them: run stats and upgrades
+//! by name, players by Steam ID with their names, and items by their count of instances.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::repo::save::SaveGame;
+use crate::rsm::edit::{self, PLAYER_STATS};
+
+/// A value changed between two saves, None where it's missing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueChange {
+    /// Key in the save, eg. `currency` or `playerUpgradeHealth`
+    pub key: String,
+    /// Label shown to the user, eg. `Currency (k$)`
+    pub label: String,
+    pub before: Option<i32>,
+    pub after: Option<i32>,
+}
+
+/// Whether a player is in both saves or only in one of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerPresence {
+    Both,
+    /// Only in the save compared to
+    Added,
+    /// Only in the save compared from
+    Removed,
+}
+
+/// The changes to a player, their upgrades and crown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerDiff {
+    pub steam_id: String,
+    /// Name in the save compared to, or in the one compared from if the player was removed
+    pub name: String,
+    pub presence: PlayerPresence,
+    /// Upgrades and crown that differ, in the order of `PLAYER_STATS`
+    pub upgrades: Vec<ValueChange>,
+}
+
+/// An item whose count of instances differs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemDiff {
+    pub name: String,
+    pub before: usize,
+    pub after: usize,
+}
+
+/// What changes from a save (before) to another (after).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SaveDiff {
+    /// Team names before and after, if they differ
+    pub team_name: Option<(String, String)>,
+    /// Time played before and after in seconds, if it differs
+    pub time_played: Option<(f32, f32)>,
+    /// Run stats that differ, in the order of `edit::sorted_run_stats`
+    pub run_stats: Vec<ValueChange>,
+    /// Players added, removed or whose upgrades differ, by name
+    pub players: Vec<PlayerDiff>,
+    /// Items whose count differs, by name
+    pub items: Vec<ItemDiff>,
+}
+
+impl SaveDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == SaveDiff::default()
+    }
+}
+
+/// Compare two saves, listing what changes from `before` to `after`.
+pub fn diff_saves(before: &SaveGame, after: &SaveGame) -> SaveDiff {
+    SaveDiff {
+        team_name: (before.team_name.value != after.team_name.value).then(|| {
+            (
+                before.team_name.value.clone(),
+                after.team_name.value.clone(),
+            )
+        }),
+        time_played: (before.time_played.value != after.time_played.value)
+            .then_some((before.time_played.value, after.time_played.value)),
+        run_stats: diff_run_stats(
+            &before.dictionary_of_dictionaries.value.run_stats,
+            &after.dictionary_of_dictionaries.value.run_stats,
+        ),
+        players: diff_players(before, after),
+        items: diff_items(before, after),
+    }
+}
+
+fn diff_run_stats(before: &HashMap<String, i32>, after: &HashMap<String, i32>) -> Vec<ValueChange> {
+    // Sort the keys of both saves the way the run stats are shown.
+    let keys: HashMap<String, i32> = before
+        .iter()
+        .chain(after)
+        .map(|(k, _)| (k.clone(), 0))
+        .collect();
+    edit::sorted_run_stats(&keys)
+        .into_iter()
+        .filter_map(|(key, _)| {
+            let (before, after) = (before.get(&key).copied(), after.get(&key).copied());
+            if before == after {
+                return None;
+            }
+            let spec = edit::run_stat_spec(&key);
+            Some(ValueChange {
+                label: if spec.key.is_empty() {
+                    key.clone()
+                } else {
+                    spec.label.to_string()
+                },
+                key,
+                before,
+                after,
+            })
+        })
+        .collect()
+}
+
+fn diff_players(before: &SaveGame, after: &SaveGame) -> Vec<PlayerDiff> {
+    let before_players = edit::player_stats(before);
+    let after_players = edit::player_stats(after);
+    let steam_ids: BTreeSet<&String> = before_players
+        .iter()
+        .chain(&after_players)
+        .map(|player| &player.steam_id)
+        .collect();
+
+    let mut players: Vec<PlayerDiff> = steam_ids
+        .into_iter()
+        .filter_map(|steam_id| {
+            let before = before_players.iter().find(|p| p.steam_id == *steam_id);
+            let after = after_players.iter().find(|p| p.steam_id == *steam_id);
+            let presence = match (before, after) {
+                (Some(_), Some(_)) => PlayerPresence::Both,
+                (None, Some(_)) => PlayerPresence::Added,
+                _ => PlayerPresence::Removed,
+            };
+            let upgrades: Vec<ValueChange> = PLAYER_STATS
+                .iter()
+                .enumerate()
+                .filter_map(|(index, spec)| {
+                    let before = before.map(|player| player.values[index]);
+                    let after = after.map(|player| player.values[index]);
+                    // A player added or removed only shows the upgrades they have.
+                    let relevant = before != after
+                        && (presence == PlayerPresence::Both || before.or(after) != Some(0));
+                    relevant.then(|| ValueChange {
+                        key: spec.key.to_string(),
+                        label: spec.label.to_string(),
+                        before,
+                        after,
+                    })
+                })
+                .collect();
+            if presence == PlayerPresence::Both && upgrades.is_empty() {
+                return None;
+            }
+            Some(PlayerDiff {
+                steam_id: steam_id.clone(),
+                name: after.or(before).map(|p| p.name.clone()).unwrap_or_default(),
+                presence,
+                upgrades,
+            })
+        })
+        .collect();
+    players.sort_by(|a, b| {
+        a.name
+            .cmp(&b.name)
+            .then_with(|| a.steam_id.cmp(&b.steam_id))
+    });
+    players
+}
+
+fn diff_items(before: &SaveGame, after: &SaveGame) -> Vec<ItemDiff> {
+    let count = |save: &SaveGame| -> HashMap<String, usize> {
+        edit::inventory(save)
+            .into_iter()
+            .map(|item| (item.name, item.instances.len()))
+            .collect()
+    };
+    let (before, after) = (count(before), count(after));
+    let names: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let before = before.get(name).copied().unwrap_or(0);
+            let after = after.get(name).copied().unwrap_or(0);
+            (before != after).then(|| ItemDiff {
+                name: name.clone(),
+                before,
+                after,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::save::fixtures;
+
+    mod diff_saves {
+        use super::*;
+
+        #[test]
+        fn same_save_has_no_changes() {
+            // Arrange
+            let save = fixtures::save_game();
+
+            // Act
+            let diff = diff_saves(&save, &save);
+
+            // Assert
+            assert!(diff.is_empty());
+        }
+
+        #[test]
+        fn lists_time_played() {
+            // Arrange
+            let before = fixtures::save_game();
+            let mut after = before.clone();
+            after.time_played.value += 60.0;
+
+            // Act
+            let diff = diff_saves(&before, &after);
+
+            // Assert
+            assert_eq!(
+                diff.time_played,
+                Some((before.time_played.value, after.time_played.value))
+            );
+            assert_eq!(diff.team_name, None);
+        }
+
+        #[test]
+        fn lists_run_stats_by_label() {
+            // Arrange
+            let before = fixtures::save_game();
+            let mut after = before.clone();
+            after
+                .dictionary_of_dictionaries
+                .value
+                .run_stats
+                .insert("level".to_string(), 7);
+
+            // Act
+            let diff = diff_saves(&before, &after);
+
+            // Assert
+            assert_eq!(
+                diff.run_stats,
+                vec![ValueChange {
+                    key: "level".to_string(),
+                    label: "Level".to_string(),
+                    before: Some(4),
+                    after: Some(7),
+                }]
+            );
+        }
+
+        #[test]
+        fn lists_upgrades_of_player() {
+            // Arrange
+            let before = fixtures::save_game();
+            let mut after = before.clone();
+            after
+                .dictionary_of_dictionaries
+                .value
+                .player_upgrade_health
+                .insert("76561198000000001".to_string(), 3);
+
+            // Act
+            let diff = diff_saves(&before, &after);
+
+            // Assert
+            assert_eq!(
+                diff.players,
+                vec![PlayerDiff {
+                    steam_id: "76561198000000001".to_string(),
+                    name: "Alice".to_string(),
+                    presence: PlayerPresence::Both,
+                    upgrades: vec![ValueChange {
+                        key: "playerUpgradeHealth".to_string(),
+                        label: PLAYER_STATS[0].label.to_string(),
+                        before: Some(1),
+                        after: Some(3),
+                    }],
+                }]
+            );
+        }
+
+        #[test]
+        fn lists_removed_player_with_their_upgrades() {
+            // Arrange
+            let before = fixtures::save_game();
+            let mut after = before.clone();
+            edit::remove_player(&mut after, "76561198000000002").unwrap();
+
+            // Act
+            let diff = diff_saves(&before, &after);
+
+            // Assert
+            assert_eq!(diff.players.len(), 1);
+            assert_eq!(diff.players[0].name, "Bob");
+            assert_eq!(diff.players[0].presence, PlayerPresence::Removed);
+            assert!(
+                diff.players[0]
+                    .upgrades
+                    .iter()
+                    .all(|change| change.before != Some(0) && change.after.is_none())
+            );
+        }
+
+        #[test]
+        fn lists_item_counts() {
+            // Arrange
+            let before = fixtures::save_game();
+            let mut after = before.clone();
+            edit::edit_items(&mut after, &[edit::ItemEdit::Add("Item Gun".to_string())]).unwrap();
+
+            // Act
+            let diff = diff_saves(&before, &after);
+
+            // Assert
+            assert_eq!(diff.items.len(), 1);
+            assert_eq!(diff.items[0].name, "Item Gun");
+            assert_eq!(diff.items[0].after, diff.items[0].before + 1);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Discovery and validation of the directories used by the save manager,
+//! for the setup run on the first launch.
+
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::constant;
+use crate::repo;
+
+#[derive(Error, Debug)]
+pub enum DiscoveryError {
+    #[error("`{0}` is not a directory")]
+    NotADirectory(PathBuf),
+    #[error("`{0}` doesn't contain any REPO_SAVE_* save")]
+    NoSaves(PathBuf),
+    #[error("IO error: {0}")]
+    IOError(#[from] std::io::Error),
+}
+
+/// A directory that may hold the game saves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectoryCandidate {
+    pub path: PathBuf,
+    /// Why the directory was suggested
+    pub reason: String,
+    /// Names of the save bundles found in the directory
+    pub saves: Vec<String>,
+}
+
+/// Look for the directories the game may save to, with the saves found in each.
+///
+/// Directories where the game was installed but never saved are included, without saves.
+pub fn discover_save_directories() -> Vec<DirectoryCandidate> {
+    let mut candidates: Vec<(PathBuf, String)> = Vec::new();
+
+    #[cfg(target_os = "linux")]
+    if let Some(home) = std::env::var_os("HOME") {
+        candidates.extend(
+            repo::steam::discover_save_candidates(PathBuf::from(home))
+                .into_iter()
+                .map(|candidate| (candidate.path, candidate.reason.to_string())),
+        );
+    }
+
+    for path in repo::utils::get_repo_save_paths() {
+        if path.is_dir() && !candidates.iter().any(|(p, _)| *p == path) {
+            candidates.push((path, "Default save location of the game".to_string()));
+        }
+    }
+
+    candidates
+        .into_iter()
+        .map(|(path, reason)| DirectoryCandidate {
+            saves: find_saves(&path).unwrap_or_default(),
+            path,
+            reason,
+        })
+        .collect()
+}
+
+/// Check that a directory holds game saves, returns the names of the saves found.
+pub fn validate_save_directory(path: impl AsRef<Path>) -> Result<Vec<String>, DiscoveryError> {
+    let path = path.as_ref();
+    if !path.is_dir() {
+        return Err(DiscoveryError::NotADirectory(path.to_path_buf()));
+    }
+    let saves = find_saves(path)?;
+    if saves.is_empty() {
+        return Err(DiscoveryError::NoSaves(path.to_path_buf()));
+    }
+    Ok(saves)
+}
+
+/// Names of the `REPO_SAVE_*` bundles in a directory, sorted.
+/// Only the presence of the save file is checked, it isn't read.
+fn find_saves(path: &Path) -> Result<Vec<String>, DiscoveryError> {
+    let mut saves = Vec::new();
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(constant::SAVE_PREFIX)
+            && entry.path().join(format!("{name}.es3")).is_file()
+        {
+            saves.push(name);
+        }
+    }
+    saves.sort();
+    Ok(saves)
+}
+
+/// Backup directory proposed when none is configured, in the user's data directory.
+pub fn default_backup_directory() -> String {
+    match directories_next::ProjectDirs::from("", "", constant::APP_ID) {
+        Some(project_dir) => project_dir
+            .data_dir()
+            .join("backups")
+            .to_string_lossy()
+            .to_string(),
+        None => {
+            let home_path = PathBuf::from(std::env::var("HOME").unwrap_or_default());
+            home_path
+                .join(".local/share/rsm/backups")
+                .to_string_lossy()
+                .to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::save::fixtures;
+    use tempfile::tempdir;
+
+    mod validate_save_directory {
+        use super::*;
+
+        #[test]
+        fn lists_save_bundles() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_2");
+            fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_1");
+            fixtures::save_bundle_dir(temp_dir.path(), "OTHER_SAVE");
+            std::fs::create_dir(temp_dir.path().join("REPO_SAVE_EMPTY")).unwrap();
+
+            // Act
+            let saves = validate_save_directory(temp_dir.path()).unwrap();
+
+            // Assert
+            assert_eq!(saves, vec!["REPO_SAVE_1", "REPO_SAVE_2"]);
+        }
+
+        #[test]
+        fn refuses_directory_without_saves() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            fixtures::save_bundle_dir(temp_dir.path(), "OTHER_SAVE");
+
+            // Act
+            let without_saves = validate_save_directory(temp_dir.path());
+            let missing = validate_save_directory(temp_dir.path().join("missing"));
+
+            // Assert
+            assert!(matches!(without_saves, Err(DiscoveryError::NoSaves(_))));
+            assert!(matches!(missing, Err(DiscoveryError::NotADirectory(_))));
+        }
+    }
+}
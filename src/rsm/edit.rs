@@ -0,0 +1,1343 @@
+//! Editing of game saves.
+//!
+//! Edits are validated before anything is written, and the save bundle is copied to the
+//! snapshot directory first, so that the original save can always be recovered.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use thiserror::Error;
+
+use crate::fs_util;
+use crate::jobs::JobProgress;
+use crate::repo::save::{DictionaryValue, SaveGame};
+use crate::rsm::save_bundle::{self, SaveBundle, SaveBundleError};
+
+/// Directory holding the snapshots, inside the backup directory.
+/// It's hidden, so scans don't take the snapshots for backups.
+pub const SNAPSHOT_DIRECTORY: &str = ".snapshots";
+
+#[derive(Error, Debug)]
+pub enum EditError {
+    #[error("{label} must be between {min} and {max}, got {value}")]
+    OutOfRange {
+        label: String,
+        value: i32,
+        min: i32,
+        max: i32,
+    },
+    #[error("Unknown stat `{0}`")]
+    UnknownStat(String),
+    #[error("Player `{0}` not found in the save")]
+    UnknownPlayer(String),
+    #[error("Item `{0}` not found in the save")]
+    UnknownItem(String),
+    #[error("Invalid item name `{0}`, item names start with `Item ` and contain no `/`")]
+    InvalidItemName(String),
+    #[error("Team names must have between 1 and {MAX_TEAM_NAME_LEN} characters")]
+    InvalidTeamName,
+    #[error("Snapshot failed: {0}")]
+    SnapshotFailed(fs_util::SaveManagerError),
+    #[error("Copy failed: {0}")]
+    CopyFailed(fs_util::SaveManagerError),
+    #[error("Invalid patch: {0}")]
+    InvalidPatch(String),
+    #[error("Patch failed: {0}")]
+    PatchFailed(json_patch::PatchError),
+    #[error("The patched save doesn't match the save format: {0}")]
+    InvalidPatchedSave(serde_json::Error),
+    #[error(transparent)]
+    SaveBundle(#[from] SaveBundleError),
+}
+
+impl EditError {
+    /// What the user can do to fix the error.
+    pub fn hint(&self) -> String {
+        match self {
+            EditError::OutOfRange { .. }
+            | EditError::UnknownStat(_)
+            | EditError::UnknownPlayer(_)
+            | EditError::UnknownItem(_)
+            | EditError::InvalidItemName(_)
+            | EditError::InvalidTeamName => {
+                "Fix the values and save again, the save wasn't modified.".to_string()
+            }
+            EditError::InvalidPatch(_)
+            | EditError::PatchFailed(_)
+            | EditError::InvalidPatchedSave(_) => {
+                "Fix the patch and apply it again, the save wasn't modified.".to_string()
+            }
+            EditError::SnapshotFailed(err) | EditError::CopyFailed(err) => err.hint(),
+            EditError::SaveBundle(_) => {
+                "Check that the save can be read with `verify`, refresh the saves and try again."
+                    .to_string()
+            }
+        }
+    }
+}
+
+/// Bounds of a value stored in the save, with the name shown to the user.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatSpec {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub min: i32,
+    pub max: i32,
+}
+
+/// The run stats known to the editor, in the order they are shown.
+pub const RUN_STATS: &[StatSpec] = &[
+    StatSpec {
+        key: "level",
+        label: "Level",
+        min: 0,
+        max: 999,
+    },
+    StatSpec {
+        key: "currency",
+        label: "Currency (k$)",
+        min: 0,
+        max: 99_999,
+    },
+    StatSpec {
+        key: "lives",
+        label: "Lives",
+        min: 0,
+        max: 99,
+    },
+    StatSpec {
+        key: "chargingStationCharge",
+        label: "Charging station charge",
+        min: 0,
+        max: 100,
+    },
+    StatSpec {
+        key: "chargingStationChargeTotal",
+        label: "Charging station charge total",
+        min: 0,
+        max: 1000,
+    },
+    StatSpec {
+        key: "totalHaul",
+        label: "Total haul (k$)",
+        min: 0,
+        max: i32::MAX,
+    },
+    StatSpec {
+        key: "saveLevel",
+        label: "Save level",
+        min: 0,
+        max: 1,
+    },
+];
+
+/// Bounds of a run stat, stats unknown to the editor only have to be positive.
+pub fn run_stat_spec(key: &str) -> StatSpec {
+    RUN_STATS
+        .iter()
+        .find(|spec| spec.key == key)
+        .copied()
+        .unwrap_or(StatSpec {
+            key: "",
+            label: "Run stat",
+            min: 0,
+            max: i32::MAX,
+        })
+}
+
+/// The per-player dictionaries known to the editor, in the order they are shown.
+/// Players are keyed by Steam ID in each of them.
+///
+/// The game leaves out the players without an upgrade or the crown: a missing entry is 0.
+/// Edits, templates and `lint::lint_save` follow it, only `playerHealth` needs every player.
+pub const PLAYER_STATS: &[StatSpec] = &[
+    StatSpec {
+        key: "playerUpgradeHealth",
+        label: "Health",
+        min: 0,
+        max: 999,
+    },
+    StatSpec {
+        key: "playerUpgradeStamina",
+        label: "Stamina",
+        min: 0,
+        max: 999,
+    },
+    StatSpec {
+        key: "playerUpgradeExtraJump",
+        label: "Extra jump",
+        min: 0,
+        max: 999,
+    },
+    StatSpec {
+        key: "playerUpgradeLaunch",
+        label: "Launch",
+        min: 0,
+        max: 999,
+    },
+    StatSpec {
+        key: "playerUpgradeMapPlayerCount",
+        label: "Map player count",
+        min: 0,
+        max: 999,
+    },
+    StatSpec {
+        key: "playerUpgradeSpeed",
+        label: "Speed",
+        min: 0,
+        max: 999,
+    },
+    StatSpec {
+        key: "playerUpgradeStrength",
+        label: "Strength",
+        min: 0,
+        max: 999,
+    },
+    StatSpec {
+        key: "playerUpgradeRange",
+        label: "Range",
+        min: 0,
+        max: 999,
+    },
+    StatSpec {
+        key: "playerUpgradeThrow",
+        label: "Throw",
+        min: 0,
+        max: 999,
+    },
+    StatSpec {
+        key: CROWN_KEY,
+        label: "Crown",
+        min: 0,
+        max: 1,
+    },
+];
+
+/// Flag of the player wearing the crown, at most one player has it.
+pub const CROWN_KEY: &str = "playerHasCrown";
+
+/// Find a per-player stat by its key, or by its key without the `playerUpgrade`/`playerHas`
+/// prefix, ignoring case (eg. `health`, `extrajump`, `crown`).
+pub fn find_player_stat(name: &str) -> Option<StatSpec> {
+    PLAYER_STATS.iter().copied().find(|spec| {
+        let short = spec
+            .key
+            .strip_prefix("playerUpgrade")
+            .or_else(|| spec.key.strip_prefix("playerHas"))
+            .unwrap_or(spec.key);
+        spec.key.eq_ignore_ascii_case(name) || short.eq_ignore_ascii_case(name)
+    })
+}
+
+/// Check that a run stat is within bounds.
+pub fn validate_run_stat(key: &str, value: i32) -> Result<(), EditError> {
+    let spec = run_stat_spec(key);
+    let label = if spec.key.is_empty() { key } else { spec.label };
+    check_range(&spec, label, value)
+}
+
+fn check_range(spec: &StatSpec, label: &str, value: i32) -> Result<(), EditError> {
+    if (spec.min..=spec.max).contains(&value) {
+        return Ok(());
+    }
+    Err(EditError::OutOfRange {
+        label: label.to_string(),
+        value,
+        min: spec.min,
+        max: spec.max,
+    })
+}
+
+/// The run stats of a save in the order they are shown: known stats first, then the others by name.
+pub fn sorted_run_stats(run_stats: &HashMap<String, i32>) -> Vec<(String, i32)> {
+    let mut stats: Vec<(String, i32)> = run_stats
+        .iter()
+        .map(|(key, value)| (key.clone(), *value))
+        .collect();
+    stats.sort_by_key(|(key, _)| {
+        let position = RUN_STATS.iter().position(|spec| spec.key == key);
+        (position.unwrap_or(RUN_STATS.len()), key.clone())
+    });
+    stats
+}
+
+/// Set run stats of a save, leaving the stats not given unchanged.
+///
+/// Only the stats already in the save or known to the editor can be set.
+/// Nothing is changed if any value is invalid.
+pub fn set_run_stats(save: &mut SaveGame, stats: &HashMap<String, i32>) -> Result<(), EditError> {
+    let run_stats = &mut save.dictionary_of_dictionaries.value.run_stats;
+    for (key, value) in stats {
+        if !run_stats.contains_key(key) && !RUN_STATS.iter().any(|spec| spec.key == key) {
+            return Err(EditError::UnknownStat(key.clone()));
+        }
+        validate_run_stat(key, *value)?;
+    }
+    run_stats.extend(stats.iter().map(|(key, value)| (key.clone(), *value)));
+    Ok(())
+}
+
+/// The upgrades and crown of a player, as shown in the editor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerStats {
+    pub steam_id: String,
+    /// Name from the save's player names, the Steam ID if the player has none
+    pub name: String,
+    /// Values in the order of `PLAYER_STATS`, 0 if missing from the save
+    pub values: Vec<i32>,
+}
+
+/// The upgrades and crown of every player of a save, sorted by name.
+///
+/// Players found in the upgrade dictionaries but not in the player names are included,
+/// so that they can be inspected.
+pub fn player_stats(save: &SaveGame) -> Vec<PlayerStats> {
+    let value = &save.dictionary_of_dictionaries.value;
+    let names = &save.player_names.value;
+    let mut steam_ids: Vec<&String> = names.keys().collect();
+    for spec in PLAYER_STATS {
+        if let Some(dictionary) = player_dictionary(value, spec.key) {
+            steam_ids.extend(dictionary.keys());
+        }
+    }
+    steam_ids.sort();
+    steam_ids.dedup();
+
+    let mut players: Vec<PlayerStats> = steam_ids
+        .into_iter()
+        .map(|steam_id| PlayerStats {
+            steam_id: steam_id.clone(),
+            name: names.get(steam_id).unwrap_or(steam_id).clone(),
+            values: PLAYER_STATS
+                .iter()
+                .map(|spec| {
+                    player_dictionary(value, spec.key)
+                        .and_then(|dictionary| dictionary.get(steam_id))
+                        .copied()
+                        .unwrap_or(0)
+                })
+                .collect(),
+        })
+        .collect();
+    players.sort_by(|a, b| {
+        a.name
+            .cmp(&b.name)
+            .then_with(|| a.steam_id.cmp(&b.steam_id))
+    });
+    players
+}
+
+/// Steam ID of a player of a save, given their name or Steam ID.
+pub fn find_player(save: &SaveGame, name_or_id: &str) -> Option<String> {
+    let names = &save.player_names.value;
+    if names.contains_key(name_or_id) {
+        return Some(name_or_id.to_string());
+    }
+    names
+        .iter()
+        .find(|(_, name)| *name == name_or_id)
+        .map(|(steam_id, _)| steam_id.clone())
+}
+
+/// Set per-player stats, by Steam ID then by key of `PLAYER_STATS`.
+///
+/// Giving the crown to a player takes it from the others. Stats set to 0 get no entry if
+/// the player had none, see `PLAYER_STATS`.
+/// Nothing is changed if any player, stat or value is invalid.
+pub fn set_player_stats(
+    save: &mut SaveGame,
+    players: &HashMap<String, HashMap<String, i32>>,
+) -> Result<(), EditError> {
+    for (steam_id, stats) in players {
+        if !save.player_names.value.contains_key(steam_id) {
+            return Err(EditError::UnknownPlayer(steam_id.clone()));
+        }
+        for (key, value) in stats {
+            let spec = PLAYER_STATS
+                .iter()
+                .find(|spec| spec.key == key)
+                .ok_or_else(|| EditError::UnknownStat(key.clone()))?;
+            let name = &save.player_names.value[steam_id];
+            check_range(spec, &format!("{} of {name}", spec.label), *value)?;
+        }
+    }
+
+    let value = &mut save.dictionary_of_dictionaries.value;
+    for (steam_id, stats) in players {
+        for (key, stat) in stats {
+            let Some(dictionary) = player_dictionary_mut(value, key) else {
+                continue;
+            };
+            if key == CROWN_KEY && *stat == 1 {
+                dictionary.values_mut().for_each(|crown| *crown = 0);
+            }
+            // Don't add entries that change nothing.
+            if *stat != 0 || dictionary.contains_key(steam_id) {
+                dictionary.insert(steam_id.clone(), *stat);
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn player_dictionary<'a>(
+    value: &'a DictionaryValue,
+    key: &str,
+) -> Option<&'a HashMap<String, i32>> {
+    Some(match key {
+        "playerUpgradeHealth" => &value.player_upgrade_health,
+        "playerUpgradeStamina" => &value.player_upgrade_stamina,
+        "playerUpgradeExtraJump" => &value.player_upgrade_extra_jump,
+        "playerUpgradeLaunch" => &value.player_upgrade_launch,
+        "playerUpgradeMapPlayerCount" => &value.player_upgrade_map_player_count,
+        "playerUpgradeSpeed" => &value.player_upgrade_speed,
+        "playerUpgradeStrength" => &value.player_upgrade_strength,
+        "playerUpgradeRange" => &value.player_upgrade_range,
+        "playerUpgradeThrow" => &value.player_upgrade_throw,
+        CROWN_KEY => &value.player_has_crown,
+        _ => return None,
+    })
+}
+
+pub(crate) fn player_dictionary_mut<'a>(
+    value: &'a mut DictionaryValue,
+    key: &str,
+) -> Option<&'a mut HashMap<String, i32>> {
+    Some(match key {
+        "playerUpgradeHealth" => &mut value.player_upgrade_health,
+        "playerUpgradeStamina" => &mut value.player_upgrade_stamina,
+        "playerUpgradeExtraJump" => &mut value.player_upgrade_extra_jump,
+        "playerUpgradeLaunch" => &mut value.player_upgrade_launch,
+        "playerUpgradeMapPlayerCount" => &mut value.player_upgrade_map_player_count,
+        "playerUpgradeSpeed" => &mut value.player_upgrade_speed,
+        "playerUpgradeStrength" => &mut value.player_upgrade_strength,
+        "playerUpgradeRange" => &mut value.player_upgrade_range,
+        "playerUpgradeThrow" => &mut value.player_upgrade_throw,
+        CROWN_KEY => &mut value.player_has_crown,
+        _ => return None,
+    })
+}
+
+/// Whether an entry of the dictionary of dictionaries not known to the editor is per-player,
+/// eg. an upgrade added by a game update.
+pub(crate) fn is_extra_player_dictionary(key: &str, dictionary: &serde_json::Value) -> bool {
+    key.starts_with("player") && dictionary.is_object()
+}
+
+/// Remove a player from a save: from the player names and every per-player dictionary,
+/// including the ones not known to the editor.
+///
+/// The player can also be one found only in the dictionaries, eg. left over by another tool.
+pub fn remove_player(save: &mut SaveGame, steam_id: &str) -> Result<(), EditError> {
+    let value = &mut save.dictionary_of_dictionaries.value;
+    let mut found = save.player_names.value.remove(steam_id).is_some();
+    found |= value.player_health.remove(steam_id).is_some();
+    for spec in PLAYER_STATS {
+        if let Some(dictionary) = player_dictionary_mut(value, spec.key) {
+            found |= dictionary.remove(steam_id).is_some();
+        }
+    }
+    for (key, dictionary) in &mut value.extra {
+        if is_extra_player_dictionary(key, dictionary)
+            && let Some(dictionary) = dictionary.as_object_mut()
+        {
+            found |= dictionary.remove(steam_id).is_some();
+        }
+    }
+    if found {
+        Ok(())
+    } else {
+        Err(EditError::UnknownPlayer(steam_id.to_string()))
+    }
+}
+
+/// Copy the upgrades of player `source_id` of `source` to player `target_id` of `target`,
+/// eg. to continue with the same build in another run.
+///
+/// The crown isn't copied, it belongs to the run. If `target_id` isn't a player of `target`,
+/// they are added to it with their name and health in `source`, and get entries only for
+/// the upgrades they have, as in `set_player_stats`.
+/// Nothing is changed if an upgrade of the source player is invalid.
+pub fn transplant_player(
+    source: &SaveGame,
+    source_id: &str,
+    target: &mut SaveGame,
+    target_id: &str,
+) -> Result<(), EditError> {
+    let Some(player) = player_stats(source)
+        .into_iter()
+        .find(|player| player.steam_id == source_id)
+    else {
+        return Err(EditError::UnknownPlayer(source_id.to_string()));
+    };
+    let upgrades: Vec<(&StatSpec, i32)> = PLAYER_STATS
+        .iter()
+        .zip(player.values)
+        .filter(|(spec, _)| spec.key != CROWN_KEY)
+        .collect();
+    for (spec, value) in &upgrades {
+        check_range(spec, &format!("{} of {}", spec.label, player.name), *value)?;
+    }
+
+    let source_value = &source.dictionary_of_dictionaries.value;
+    let value = &mut target.dictionary_of_dictionaries.value;
+    for (spec, upgrade) in upgrades {
+        if let Some(dictionary) = player_dictionary_mut(value, spec.key)
+            && (upgrade != 0 || dictionary.contains_key(target_id))
+        {
+            dictionary.insert(target_id.to_string(), upgrade);
+        }
+    }
+    // Upgrades not known to the editor are copied as they are.
+    for (key, dictionary) in &source_value.extra {
+        if key.starts_with("playerUpgrade")
+            && let Some(upgrade) = dictionary.get(source_id)
+            && let Some(dictionary) = value
+                .extra
+                .entry(key.clone())
+                .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()))
+                .as_object_mut()
+        {
+            dictionary.insert(target_id.to_string(), upgrade.clone());
+        }
+    }
+
+    if !target.player_names.value.contains_key(target_id) {
+        target
+            .player_names
+            .value
+            .insert(target_id.to_string(), player.name);
+        if let Some(health) = source_value.player_health.get(source_id) {
+            value.player_health.insert(target_id.to_string(), *health);
+        }
+    }
+    Ok(())
+}
+
+/// Battery level of a fully charged item.
+pub const FULL_BATTERY: i32 = 100;
+
+const BATTERY: StatSpec = StatSpec {
+    key: "itemStatBattery",
+    label: "Battery",
+    min: 0,
+    max: FULL_BATTERY,
+};
+
+/// A change to the items of a save.
+///
+/// Items are stored once per instance in `item` and `itemStatBattery`, keyed by
+/// `<item name>/<number>`, eg. `Item Grenade Explosive/2`, while the other item
+/// dictionaries are keyed by item name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemEdit {
+    /// Add an instance of an item, fully charged, by item name
+    Add(String),
+    /// Remove an instance of an item
+    Remove(String),
+    /// Set the battery of an instance of an item
+    SetBattery(String, i32),
+}
+
+/// An instance of an item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemInstance {
+    pub key: String,
+    /// None if the save has no battery for the instance
+    pub battery: Option<i32>,
+}
+
+/// An item of a save, with its instances and what the item dictionaries record about it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InventoryItem {
+    pub name: String,
+    pub instances: Vec<ItemInstance>,
+    /// Count in `itemsPurchased`
+    pub purchased: i32,
+    /// Count in `itemsPurchasedTotal`
+    pub purchased_total: i32,
+    /// Level in `itemBatteryUpgrades`
+    pub battery_upgrades: i32,
+    /// Count in `itemsUpgradesPurchased`
+    pub upgrades_purchased: i32,
+}
+
+/// Name of the item an instance key belongs to, eg. `Item Grenade Explosive`
+/// for `Item Grenade Explosive/2`.
+pub fn item_name(instance: &str) -> &str {
+    instance.rsplit_once('/').map_or(instance, |(name, _)| name)
+}
+
+/// Every item found in the item dictionaries of a save, sorted by name.
+pub fn inventory(save: &SaveGame) -> Vec<InventoryItem> {
+    let value = &save.dictionary_of_dictionaries.value;
+    let mut names: Vec<&str> = value.item.keys().map(|key| item_name(key)).collect();
+    for dictionary in [
+        &value.items_purchased,
+        &value.items_purchased_total,
+        &value.item_battery_upgrades,
+        &value.items_upgrades_purchased,
+    ] {
+        names.extend(dictionary.keys().map(String::as_str));
+    }
+    names.sort();
+    names.dedup();
+
+    let count =
+        |dictionary: &HashMap<String, i32>, name: &str| dictionary.get(name).copied().unwrap_or(0);
+    names
+        .into_iter()
+        .map(|name| {
+            let mut instances: Vec<ItemInstance> = value
+                .item
+                .keys()
+                .filter(|key| item_name(key) == name)
+                .map(|key| ItemInstance {
+                    key: key.clone(),
+                    battery: value.item_stat_battery.get(key).copied(),
+                })
+                .collect();
+            instances.sort_by_key(|instance| instance_number(&instance.key));
+            InventoryItem {
+                name: name.to_string(),
+                instances,
+                purchased: count(&value.items_purchased, name),
+                purchased_total: count(&value.items_purchased_total, name),
+                battery_upgrades: count(&value.item_battery_upgrades, name),
+                upgrades_purchased: count(&value.items_upgrades_purchased, name),
+            }
+        })
+        .collect()
+}
+
+/// Number of an instance key, 0 if it has none.
+fn instance_number(instance: &str) -> u32 {
+    instance
+        .rsplit_once('/')
+        .and_then(|(_, number)| number.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Apply changes to the items of a save, in order.
+///
+/// The purchase counts of the items changed are kept consistent with their instances:
+/// `itemsPurchased` counts the instances, and `itemsPurchasedTotal` counts added items
+/// as purchases and is never below `itemsPurchased`.
+/// Nothing is changed if any edit is invalid.
+pub fn edit_items(save: &mut SaveGame, edits: &[ItemEdit]) -> Result<(), EditError> {
+    let mut value = save.dictionary_of_dictionaries.value.clone();
+    let mut changed: Vec<String> = Vec::new();
+    for edit in edits {
+        match edit {
+            ItemEdit::Add(name) => {
+                if !name.starts_with("Item ") || name.contains('/') {
+                    return Err(EditError::InvalidItemName(name.clone()));
+                }
+                let number = value
+                    .item
+                    .keys()
+                    .filter(|key| item_name(key) == name)
+                    .map(|key| instance_number(key))
+                    .max()
+                    .unwrap_or(0)
+                    + 1;
+                let instance = format!("{name}/{number}");
+                value.item.insert(instance.clone(), 1);
+                value.item_stat_battery.insert(instance, FULL_BATTERY);
+                *value.items_purchased_total.entry(name.clone()).or_insert(0) += 1;
+                changed.push(name.clone());
+            }
+            ItemEdit::Remove(instance) => {
+                value
+                    .item
+                    .remove(instance)
+                    .ok_or_else(|| EditError::UnknownItem(instance.clone()))?;
+                value.item_stat_battery.remove(instance);
+                changed.push(item_name(instance).to_string());
+            }
+            ItemEdit::SetBattery(instance, battery) => {
+                if !value.item.contains_key(instance) {
+                    return Err(EditError::UnknownItem(instance.clone()));
+                }
+                check_range(&BATTERY, &format!("Battery of {instance}"), *battery)?;
+                value.item_stat_battery.insert(instance.clone(), *battery);
+            }
+        }
+    }
+
+    for name in changed {
+        let count = value
+            .item
+            .keys()
+            .filter(|key| item_name(key) == name)
+            .count() as i32;
+        let total = value.items_purchased_total.entry(name.clone()).or_insert(0);
+        *total = (*total).max(count);
+        value.items_purchased.insert(name, count);
+    }
+    save.dictionary_of_dictionaries.value = value;
+    Ok(())
+}
+
+/// Maximum length of a team name, in characters.
+pub const MAX_TEAM_NAME_LEN: usize = 64;
+
+/// Rename the team of a save, surrounding whitespace is removed.
+pub fn set_team_name(save: &mut SaveGame, team_name: &str) -> Result<(), EditError> {
+    let team_name = team_name.trim();
+    if team_name.is_empty() || team_name.chars().count() > MAX_TEAM_NAME_LEN {
+        return Err(EditError::InvalidTeamName);
+    }
+    save.team_name.value = team_name.to_string();
+    Ok(())
+}
+
+/// Changes made in the editor, written to the save together.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SaveEdits {
+    pub team_name: Option<String>,
+    /// New name of the save bundle, its directory and save file are renamed to it,
+    /// see `save_bundle::new_save_name`
+    pub new_name: Option<String>,
+    pub run_stats: HashMap<String, i32>,
+    /// Steam IDs of the players to remove, see `remove_player`
+    pub remove_players: Vec<String>,
+    /// Per-player stats, by Steam ID, see `set_player_stats`
+    pub players: HashMap<String, HashMap<String, i32>>,
+    /// Changes to the items, applied in order, see `edit_items`
+    pub items: Vec<ItemEdit>,
+}
+
+impl SaveEdits {
+    pub fn is_empty(&self) -> bool {
+        self.team_name.is_none()
+            && self.new_name.is_none()
+            && self.run_stats.is_empty()
+            && self.remove_players.is_empty()
+            && self.players.is_empty()
+            && self.items.is_empty()
+    }
+
+    /// Apply the changes to a save, nothing is changed if any of them is invalid.
+    pub fn apply(&self, save: &mut SaveGame) -> Result<(), EditError> {
+        let mut edited = save.clone();
+        if let Some(team_name) = &self.team_name {
+            set_team_name(&mut edited, team_name)?;
+        }
+        if let Some(new_name) = &self.new_name {
+            save_bundle::validate_save_name(new_name)?;
+            // The game shows the date of the save, make it match the new name.
+            if let Some(date) = save_bundle::save_name_date(new_name) {
+                edited.date_and_time.value = date;
+            }
+        }
+        set_run_stats(&mut edited, &self.run_stats)?;
+        for steam_id in &self.remove_players {
+            remove_player(&mut edited, steam_id)?;
+        }
+        set_player_stats(&mut edited, &self.players)?;
+        edit_items(&mut edited, &self.items)?;
+        *save = edited;
+        Ok(())
+    }
+}
+
+/// Where the snapshots of the saves edited are kept, for a backup directory.
+pub fn snapshot_directory(backup_directory: impl AsRef<Path>) -> PathBuf {
+    backup_directory.as_ref().join(SNAPSHOT_DIRECTORY)
+}
+
+/// Copy a save bundle to `snapshot_directory`, in a directory named after the time of the copy.
+/// Returns the location of the copy.
+pub fn snapshot_bundle(
+    location: impl AsRef<Path>,
+    snapshot_directory: impl AsRef<Path>,
+) -> Result<PathBuf, fs_util::SaveManagerError> {
+    let location = location.as_ref();
+    let seconds = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    // Several edits can happen within a second, keep every snapshot.
+    let mut root = snapshot_directory.as_ref().join(seconds.to_string());
+    let mut suffix = 1;
+    while root.join(location.file_name().unwrap_or_default()).exists() {
+        root = snapshot_directory
+            .as_ref()
+            .join(format!("{seconds}_{suffix}"));
+        suffix += 1;
+    }
+    fs_util::copy_directory(location, &root, false)?;
+    Ok(root.join(location.file_name().unwrap_or_default()))
+}
+
+/// Apply `edit` to the data of a save bundle and write it back, after taking a snapshot of it.
+/// If `new_name` is given, the bundle is then renamed to it (see `save_bundle::rename_save_bundle`)
+/// and `save_bundle` describes the renamed bundle.
+///
+/// Returns the location of the snapshot, or None if `edit` left the save unchanged
+/// and it isn't renamed, in which case nothing is written.
+pub fn edit_save_bundle(
+    save_bundle: &mut SaveBundle,
+    snapshot_directory: impl AsRef<Path>,
+    edit: impl FnOnce(&mut SaveGame) -> Result<(), EditError>,
+    new_name: Option<&str>,
+) -> Result<Option<PathBuf>, EditError> {
+    if let Some(new_name) = new_name {
+        save_bundle::validate_save_name(new_name)?;
+        if save_bundle.location.with_file_name(new_name).exists() {
+            return Err(SaveBundleError::SaveExists(new_name.to_string()).into());
+        }
+    }
+    let original = save_bundle.get_data()?;
+    let mut save = original.clone();
+    edit(&mut save)?;
+    if save == original && new_name.is_none() {
+        return Ok(None);
+    }
+    let snapshot = snapshot_bundle(&save_bundle.location, snapshot_directory)
+        .map_err(EditError::SnapshotFailed)?;
+    save_bundle.set_data(&save)?;
+    if let Some(new_name) = new_name {
+        let location = save_bundle::rename_save_bundle(&save_bundle.location, new_name)?;
+        *save_bundle = SaveBundle::new(location)?;
+    }
+    Ok(Some(snapshot))
+}
+
+/// Copy a save bundle to `save_directory` as a new save named `new_name`
+/// (see `save_bundle::new_save_name`), with `edit` applied to the copy, eg. to rename the team.
+/// The date of the copy is set to the one of its name, and the bundle copied is left untouched.
+///
+/// The copy is made in a hidden staging directory, which is moved in place once complete.
+/// Returns the location of the new save.
+pub fn clone_save_bundle(
+    location: impl AsRef<Path>,
+    save_directory: impl AsRef<Path>,
+    new_name: &str,
+    edit: impl FnOnce(&mut SaveGame) -> Result<(), EditError>,
+    progress: &JobProgress,
+) -> Result<PathBuf, EditError> {
+    let location = location.as_ref();
+    let save_directory = save_directory.as_ref();
+    save_bundle::validate_save_name(new_name)?;
+    let destination = save_directory.join(new_name);
+    if destination.exists() {
+        return Err(SaveBundleError::SaveExists(new_name.to_string()).into());
+    }
+
+    let staging = save_directory.join(format!(".{new_name}.clone"));
+    let result = (|| {
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging).map_err(|e| EditError::CopyFailed(e.into()))?;
+        }
+        fs_util::copy_directory_with_progress(location, &staging, false, progress)
+            .map_err(EditError::CopyFailed)?;
+        let copy = staging.join(location.file_name().ok_or(SaveBundleError::NoFileName)?);
+        let copy = save_bundle::rename_save_bundle(&copy, new_name)?;
+
+        let mut save_bundle = SaveBundle::new(&copy)?;
+        let mut save = save_bundle.get_data()?;
+        if let Some(date) = save_bundle::save_name_date(new_name) {
+            save.date_and_time.value = date;
+        }
+        edit(&mut save)?;
+        save_bundle.set_data(&save)?;
+        std::fs::rename(&copy, &destination).map_err(|e| EditError::CopyFailed(e.into()))
+    })();
+    if staging.exists()
+        && let Err(cleanup_err) = std::fs::remove_dir_all(&staging)
+    {
+        log::warn!(cleanup_err:err; "Failed to remove staging directory {:?}", staging);
+    }
+    result.map(|()| destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::save::fixtures;
+    use tempfile::tempdir;
+
+    mod set_run_stats {
+        use super::*;
+
+        #[test]
+        fn refuses_invalid_values() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            let out_of_range = HashMap::from([("level".to_string(), 5), ("lives".to_string(), -1)]);
+            let unknown = HashMap::from([("levle".to_string(), 5)]);
+
+            // Act
+            let out_of_range = set_run_stats(&mut save, &out_of_range);
+            let unknown = set_run_stats(&mut save, &unknown);
+
+            // Assert
+            assert!(matches!(out_of_range, Err(EditError::OutOfRange { .. })));
+            assert!(matches!(unknown, Err(EditError::UnknownStat(_))));
+            assert_eq!(save, fixtures::save_game());
+        }
+    }
+
+    mod set_player_stats {
+        use super::*;
+
+        #[test]
+        fn crown_moves_between_players() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            let players = player_stats(&save);
+            let crown = PLAYER_STATS.len() - 1;
+            let with_crown = players.iter().find(|p| p.values[crown] == 1).unwrap();
+            let without_crown = players.iter().find(|p| p.values[crown] == 0).unwrap();
+            let changes = HashMap::from([(
+                without_crown.steam_id.clone(),
+                HashMap::from([
+                    (CROWN_KEY.to_string(), 1),
+                    ("playerUpgradeSpeed".to_string(), 4),
+                ]),
+            )]);
+
+            // Act
+            set_player_stats(&mut save, &changes).unwrap();
+
+            // Assert
+            let players = player_stats(&save);
+            let speed = PLAYER_STATS
+                .iter()
+                .position(|spec| spec.key == "playerUpgradeSpeed")
+                .unwrap();
+            let find = |steam_id: &str| players.iter().find(|p| p.steam_id == steam_id).unwrap();
+            assert_eq!(find(&without_crown.steam_id).values[crown], 1);
+            assert_eq!(find(&without_crown.steam_id).values[speed], 4);
+            assert_eq!(find(&with_crown.steam_id).values[crown], 0);
+        }
+
+        #[test]
+        fn refuses_unknown_player() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            let changes = HashMap::from([(
+                "0".to_string(),
+                HashMap::from([("playerUpgradeSpeed".to_string(), 1)]),
+            )]);
+
+            // Act
+            let result = set_player_stats(&mut save, &changes);
+
+            // Assert
+            assert!(matches!(result, Err(EditError::UnknownPlayer(_))));
+            assert_eq!(save, fixtures::save_game());
+        }
+    }
+
+    mod find_player_stat {
+        use super::*;
+
+        #[test]
+        fn finds_short_name_ignoring_case() {
+            // Act
+            let stat = find_player_stat("extrajump");
+
+            // Assert
+            assert_eq!(stat.unwrap().key, "playerUpgradeExtraJump");
+        }
+
+        #[test]
+        fn finds_crown() {
+            // Act
+            let stat = find_player_stat("Crown");
+
+            // Assert
+            assert_eq!(stat.unwrap().key, CROWN_KEY);
+        }
+
+        #[test]
+        fn finds_full_key() {
+            // Act
+            let stat = find_player_stat("playerUpgradeSpeed");
+
+            // Assert
+            assert_eq!(stat.unwrap().key, "playerUpgradeSpeed");
+        }
+
+        #[test]
+        fn refuses_unknown_name() {
+            // Act
+            let stat = find_player_stat("flying");
+
+            // Assert
+            assert!(stat.is_none());
+        }
+    }
+
+    mod remove_player {
+        use super::*;
+
+        #[test]
+        fn removes_player_from_every_dictionary() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            let steam_id = "76561198000000001";
+
+            // Act
+            let result = remove_player(&mut save, steam_id);
+
+            // Assert
+            assert!(result.is_ok());
+            let json = serde_json::to_string(&save).unwrap();
+            assert!(!json.contains(steam_id));
+            assert!(json.contains("76561198000000002"));
+        }
+
+        #[test]
+        fn refuses_unknown_player() {
+            // Arrange
+            let mut save = fixtures::save_game();
+
+            // Act
+            let result = remove_player(&mut save, "76561198000000009");
+
+            // Assert
+            assert!(matches!(result, Err(EditError::UnknownPlayer(_))));
+            assert_eq!(save, fixtures::save_game());
+        }
+    }
+
+    mod transplant_player {
+        use super::*;
+
+        #[test]
+        fn copies_upgrades_without_crown() {
+            // Arrange
+            let source = fixtures::save_game();
+            let mut target = fixtures::save_game();
+
+            // Act
+            let result = transplant_player(
+                &source,
+                "76561198000000001",
+                &mut target,
+                "76561198000000002",
+            );
+
+            // Assert
+            assert!(result.is_ok());
+            let value = &target.dictionary_of_dictionaries.value;
+            assert_eq!(value.player_upgrade_health["76561198000000002"], 1);
+            assert_eq!(value.player_has_crown.get("76561198000000002"), Some(&0));
+            assert_eq!(target.player_names.value["76561198000000002"], "Bob");
+        }
+
+        #[test]
+        fn adds_missing_player() {
+            // Arrange
+            let source = fixtures::save_game();
+            let mut target = fixtures::save_game();
+
+            // Act
+            let result = transplant_player(
+                &source,
+                "76561198000000001",
+                &mut target,
+                "76561198000000003",
+            );
+
+            // Assert
+            assert!(result.is_ok());
+            let value = &target.dictionary_of_dictionaries.value;
+            assert_eq!(target.player_names.value["76561198000000003"], "Alice");
+            assert_eq!(value.player_health["76561198000000003"], 100);
+            assert_eq!(value.player_upgrade_health["76561198000000003"], 1);
+            // No crown is 0, see `PLAYER_STATS`.
+            assert!(!value.player_has_crown.contains_key("76561198000000003"));
+        }
+
+        #[test]
+        fn refuses_unknown_source_player() {
+            // Arrange
+            let source = fixtures::save_game();
+            let mut target = fixtures::save_game();
+
+            // Act
+            let result = transplant_player(
+                &source,
+                "76561198000000009",
+                &mut target,
+                "76561198000000002",
+            );
+
+            // Assert
+            assert!(matches!(result, Err(EditError::UnknownPlayer(_))));
+            assert_eq!(target, fixtures::save_game());
+        }
+    }
+
+    mod edit_items {
+        use super::*;
+
+        fn find_item(save: &SaveGame, name: &str) -> InventoryItem {
+            inventory(save)
+                .into_iter()
+                .find(|item| item.name == name)
+                .unwrap()
+        }
+
+        #[test]
+        fn adds_instance_with_full_battery() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            let edits = [ItemEdit::Add("Item Grenade Explosive".to_string())];
+
+            // Act
+            edit_items(&mut save, &edits).unwrap();
+
+            // Assert
+            let grenade = find_item(&save, "Item Grenade Explosive");
+            assert_eq!(grenade.instances.len(), 3);
+            assert_eq!(grenade.instances[2].key, "Item Grenade Explosive/3");
+            assert_eq!(grenade.instances[2].battery, Some(FULL_BATTERY));
+            assert_eq!((grenade.purchased, grenade.purchased_total), (3, 4));
+        }
+
+        #[test]
+        fn adds_item_without_instances() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            let edits = [ItemEdit::Add("Item Health Pack Small".to_string())];
+
+            // Act
+            edit_items(&mut save, &edits).unwrap();
+
+            // Assert
+            let health_pack = find_item(&save, "Item Health Pack Small");
+            assert_eq!((health_pack.purchased, health_pack.purchased_total), (1, 2));
+        }
+
+        #[test]
+        fn removes_instance_keeping_total() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            let edits = [ItemEdit::Remove("Item Drone Battery/1".to_string())];
+
+            // Act
+            edit_items(&mut save, &edits).unwrap();
+
+            // Assert
+            let battery = find_item(&save, "Item Drone Battery");
+            assert!(battery.instances.is_empty());
+            assert_eq!((battery.purchased, battery.purchased_total), (0, 1));
+        }
+
+        #[test]
+        fn sets_battery() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            let edits = [ItemEdit::SetBattery(
+                "Item Grenade Explosive/1".to_string(),
+                40,
+            )];
+
+            // Act
+            edit_items(&mut save, &edits).unwrap();
+
+            // Assert
+            let grenade = find_item(&save, "Item Grenade Explosive");
+            assert_eq!(grenade.instances[0].battery, Some(40));
+        }
+
+        #[test]
+        fn refuses_unknown_item() {
+            // Arrange
+            let mut save = fixtures::save_game();
+
+            // Act
+            let result = edit_items(&mut save, &[ItemEdit::Remove("Item Nothing/1".into())]);
+
+            // Assert
+            assert!(matches!(result, Err(EditError::UnknownItem(_))));
+            assert_eq!(save, fixtures::save_game());
+        }
+
+        #[test]
+        fn refuses_invalid_name() {
+            // Arrange
+            let mut save = fixtures::save_game();
+
+            // Act
+            let result = edit_items(&mut save, &[ItemEdit::Add("Grenade/2".into())]);
+
+            // Assert
+            assert!(matches!(result, Err(EditError::InvalidItemName(_))));
+            assert_eq!(save, fixtures::save_game());
+        }
+
+        #[test]
+        fn refuses_all_edits_if_one_is_invalid() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            let edits = [
+                ItemEdit::Add("Item Grenade Explosive".into()),
+                ItemEdit::SetBattery("Item Drone Battery/1".into(), 101),
+            ];
+
+            // Act
+            let result = edit_items(&mut save, &edits);
+
+            // Assert
+            assert!(matches!(result, Err(EditError::OutOfRange { .. })));
+            assert_eq!(save, fixtures::save_game());
+        }
+    }
+
+    mod edit_save_bundle {
+        use super::*;
+
+        #[test]
+        fn snapshots_original_before_writing() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let location = fixtures::save_bundle_dir(&temp_dir.path().join("saves"), "REPO_SAVE_1");
+            let snapshots = snapshot_directory(temp_dir.path().join("backups"));
+            let mut save_bundle = SaveBundle::new(&location).unwrap();
+            let stats = HashMap::from([("level".to_string(), 9), ("currency".to_string(), 500)]);
+
+            // Act
+            let snapshot = edit_save_bundle(
+                &mut save_bundle,
+                &snapshots,
+                |save| set_run_stats(save, &stats),
+                None,
+            )
+            .unwrap()
+            .unwrap();
+
+            // Assert
+            assert_eq!(SaveBundle::new(&location).unwrap().level, 9);
+            let run_stats = save_bundle
+                .get_data()
+                .unwrap()
+                .dictionary_of_dictionaries
+                .value
+                .run_stats;
+            assert_eq!(run_stats["currency"], 500);
+            assert_eq!(run_stats["lives"], 3);
+            assert!(snapshot.starts_with(&snapshots));
+            assert_eq!(SaveBundle::new(&snapshot).unwrap().level, 4);
+        }
+
+        #[test]
+        fn leaves_unchanged_save_alone() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let location = fixtures::save_bundle_dir(&temp_dir.path().join("saves"), "REPO_SAVE_1");
+            let snapshots = snapshot_directory(temp_dir.path().join("backups"));
+            let mut save_bundle = SaveBundle::new(&location).unwrap();
+
+            // Act
+            let snapshot = edit_save_bundle(&mut save_bundle, &snapshots, |_| Ok(()), None);
+
+            // Assert
+            assert_eq!(snapshot.unwrap(), None);
+            assert!(!snapshots.exists());
+        }
+
+        #[test]
+        fn renames_team_and_save() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let location = fixtures::save_bundle_dir(&temp_dir.path().join("saves"), "REPO_SAVE_1");
+            let snapshots = snapshot_directory(temp_dir.path().join("backups"));
+            let mut save_bundle = SaveBundle::new(&location).unwrap();
+            let edits = SaveEdits {
+                team_name: Some("  Practice  ".to_string()),
+                new_name: Some("REPO_SAVE_2026_01_02_03_04_05".to_string()),
+                ..SaveEdits::default()
+            };
+
+            // Act
+            let snapshot = edit_save_bundle(
+                &mut save_bundle,
+                &snapshots,
+                |save| edits.apply(save),
+                edits.new_name.as_deref(),
+            )
+            .unwrap();
+
+            // Assert
+            assert!(snapshot.is_some());
+            assert!(!location.exists());
+            assert_eq!(save_bundle.name, "REPO_SAVE_2026_01_02_03_04_05");
+            assert_eq!(save_bundle.team_name, "Practice");
+            assert_eq!(save_bundle.date, "2026-01-02");
+            assert_eq!(
+                save_bundle,
+                SaveBundle::new(temp_dir.path().join("saves/REPO_SAVE_2026_01_02_03_04_05"))
+                    .unwrap()
+            );
+        }
+    }
+
+    mod clone_save_bundle {
+        use super::*;
+
+        #[test]
+        fn copies_save_under_new_name() {
+            // Arrange
+            let root = tempdir().unwrap();
+            let location = fixtures::save_bundle_dir(root.path(), "REPO_SAVE_1");
+            let save_directory = root.path().join("saves");
+            std::fs::create_dir(&save_directory).unwrap();
+            let new_name = "REPO_SAVE_2026_01_02_03_04_05";
+
+            // Act
+            let result = clone_save_bundle(
+                &location,
+                &save_directory,
+                new_name,
+                |save| set_team_name(save, "Clones"),
+                &JobProgress::default(),
+            );
+
+            // Assert
+            let clone = result.unwrap();
+            assert_eq!(clone, save_directory.join(new_name));
+            assert!(clone.join(format!("{new_name}.es3")).exists());
+            let save = SaveBundle::new(&clone).unwrap().get_data().unwrap();
+            assert_eq!(save.team_name.value, "Clones");
+            assert_eq!(save.date_and_time.value, "2026-01-02");
+            assert_eq!(
+                SaveBundle::new(&location).unwrap().get_data().unwrap(),
+                fixtures::save_game()
+            );
+        }
+
+        #[test]
+        fn refuses_taken_name() {
+            // Arrange
+            let root = tempdir().unwrap();
+            let location = fixtures::save_bundle_dir(root.path(), "REPO_SAVE_1");
+            let save_directory = root.path().join("saves");
+            let taken = fixtures::save_bundle_dir(&save_directory, "REPO_SAVE_2");
+
+            // Act
+            let result = clone_save_bundle(
+                &location,
+                &save_directory,
+                "REPO_SAVE_2",
+                |save| set_team_name(save, "Clones"),
+                &JobProgress::default(),
+            );
+
+            // Assert
+            assert!(matches!(
+                result,
+                Err(EditError::SaveBundle(SaveBundleError::SaveExists(_)))
+            ));
+            assert_eq!(std::fs::read_dir(&save_directory).unwrap().count(), 1);
+            let save = SaveBundle::new(&taken).unwrap().get_data().unwrap();
+            assert_eq!(save, fixtures::save_game());
+        }
+    }
+}
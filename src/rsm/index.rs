@@ -0,0 +1,148 @@
+//! On-disk cache of save bundle metadata.
+//!
+//! Reading the metadata of a bundle requires decrypting its save file. The index keeps
+//! the metadata read last time, keyed by bundle location along with the size and
+//! modification time of its files and the hash of its save file (see `SaveBundle`),
+//! so that the save lists can be shown at startup before the directories are scanned.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use super::save_bundle::SaveBundle;
+use crate::constant;
+
+/// Version of the index format, indexes of another version are discarded.
+const INDEX_VERSION: u32 = 3;
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct MetadataIndex {
+    version: u32,
+    bundles: HashMap<PathBuf, SaveBundle>,
+}
+
+impl MetadataIndex {
+    /// Location of the index in the user's cache directory.
+    pub fn default_path() -> Option<PathBuf> {
+        directories_next::ProjectDirs::from("", "", constant::APP_ID)
+            .map(|project_dir| project_dir.cache_dir().join("index.json"))
+    }
+
+    /// Load the index stored at `path`.
+    /// Returns an empty index if the file doesn't exist, can't be parsed or is outdated.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let contents = match std::fs::read(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                log::warn!(e:err; "Failed to read the metadata index.");
+                return Self::default();
+            }
+        };
+        match serde_json::from_slice::<MetadataIndex>(&contents) {
+            Ok(index) if index.version == INDEX_VERSION => index,
+            Ok(_) => Self::default(),
+            Err(e) => {
+                log::warn!(e:err; "Failed to parse the metadata index, it will be rebuilt.");
+                Self::default()
+            }
+        }
+    }
+
+    /// Write the index to `path`, replacing the previous one atomically.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let directory = path
+            .parent()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Index has no parent"))?;
+        std::fs::create_dir_all(directory)?;
+        let contents = serde_json::to_vec(&MetadataIndex {
+            version: INDEX_VERSION,
+            bundles: self.bundles.clone(),
+        })?;
+        let mut temp_file = tempfile::NamedTempFile::new_in(directory)?;
+        temp_file.write_all(&contents)?;
+        temp_file.persist(path).map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    /// The indexed bundles of `directory`, sorted by location.
+    pub fn bundles_in(&self, directory: impl AsRef<Path>) -> Vec<SaveBundle> {
+        let directory = directory.as_ref();
+        let mut save_bundles: Vec<SaveBundle> = self
+            .bundles
+            .values()
+            .filter(|save_bundle| save_bundle.location.parent() == Some(directory))
+            .cloned()
+            .collect();
+        save_bundles.sort_by(|a, b| a.location.cmp(&b.location));
+        save_bundles
+    }
+
+    /// Replace the indexed bundles of `directory`.
+    /// Returns true if the index changed.
+    pub fn update(&mut self, directory: impl AsRef<Path>, save_bundles: &[SaveBundle]) -> bool {
+        if self.bundles_in(&directory) == save_bundles {
+            return false;
+        }
+        let directory = directory.as_ref();
+        self.bundles
+            .retain(|location, _| location.parent() != Some(directory));
+        for save_bundle in save_bundles {
+            self.bundles
+                .insert(save_bundle.location.clone(), save_bundle.clone());
+        }
+        true
+    }
+
+    /// Forget every bundle.
+    pub fn clear(&mut self) {
+        self.bundles.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::save::fixtures;
+    use tempfile::tempdir;
+
+    #[test]
+    fn round_trip() {
+        // Arrange
+        let temp_dir = tempdir().unwrap();
+        let saves = temp_dir.path().join("saves");
+        let save_bundles = vec![
+            SaveBundle::new(fixtures::save_bundle_dir(&saves, "REPO_SAVE_1")).unwrap(),
+            SaveBundle::new(fixtures::save_bundle_dir(&saves, "REPO_SAVE_2")).unwrap(),
+        ];
+        let index_path = temp_dir.path().join("cache/index.json");
+        let mut index = MetadataIndex::default();
+
+        // Act
+        let changed = index.update(&saves, &save_bundles);
+        index.save(&index_path).unwrap();
+        let loaded = MetadataIndex::load(&index_path);
+
+        // Assert
+        assert!(changed);
+        assert_eq!(loaded.bundles_in(&saves), save_bundles);
+        assert!(loaded.bundles_in(temp_dir.path()).is_empty());
+        assert!(!index.update(&saves, &save_bundles));
+    }
+
+    #[test]
+    fn invalid_file_is_ignored() {
+        // Arrange
+        let temp_dir = tempdir().unwrap();
+        let index_path = temp_dir.path().join("index.json");
+        std::fs::write(&index_path, b"not json").unwrap();
+
+        // Act
+        let index = MetadataIndex::load(&index_path);
+
+        // Assert
+        assert_eq!(index, MetadataIndex::default());
+    }
+}
@@ -0,0 +1,587 @@
+//! Checks for suspicious saves, eg. left over by a bug or by another tool, with automatic fixes
+//! for the issues that can be fixed safely.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::repo::save::SaveGame;
+use crate::rsm::edit::{self, EditError, PLAYER_STATS};
+use crate::rsm::save_bundle::{self, BundleId, SaveBundleError};
+
+/// An issue found in a save.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintIssue {
+    /// A run stat is negative or absurdly high
+    RunStatOutOfRange {
+        key: String,
+        value: i32,
+        min: i32,
+        max: i32,
+    },
+    /// An upgrade or the crown of a player is out of range
+    UpgradeOutOfRange {
+        player: String,
+        key: String,
+        value: i32,
+        min: i32,
+        max: i32,
+    },
+    /// A per-player dictionary has an entry for a Steam ID not in the player names
+    UnknownPlayer {
+        dictionary: String,
+        steam_id: String,
+    },
+    /// A player of the player names has no health. Players without an upgrade or the crown have
+    /// no entry in these dictionaries, it means 0.
+    MissingPlayer { dictionary: String, player: String },
+    /// Several players have the crown
+    SeveralCrowns(Vec<String>),
+    /// The battery of an item instance that isn't in the items
+    UnknownBattery(String),
+    /// The save file of a bundle isn't named after its directory, the game doesn't read it
+    MisnamedSaveFile { file: String, expected: String },
+    /// The bundle has no save file
+    MissingSaveFile(String),
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintIssue::RunStatOutOfRange {
+                key,
+                value,
+                min,
+                max,
+            } => write!(f, "Run stat {key} is {value}, expected {min} to {max}"),
+            LintIssue::UpgradeOutOfRange {
+                player,
+                key,
+                value,
+                min,
+                max,
+            } => write!(f, "{key} of {player} is {value}, expected {min} to {max}"),
+            LintIssue::UnknownPlayer {
+                dictionary,
+                steam_id,
+            } => write!(
+                f,
+                "{dictionary} has an entry for {steam_id}, who isn't a player"
+            ),
+            LintIssue::MissingPlayer { dictionary, player } => {
+                write!(f, "{dictionary} has no entry for {player}")
+            }
+            LintIssue::SeveralCrowns(players) => {
+                write!(f, "Several players have the crown: {}", players.join(", "))
+            }
+            LintIssue::UnknownBattery(instance) => {
+                write!(
+                    f,
+                    "itemStatBattery has an entry for {instance}, which isn't an item"
+                )
+            }
+            LintIssue::MisnamedSaveFile { file, expected } => {
+                write!(f, "The save file is named {file}, expected {expected}")
+            }
+            LintIssue::MissingSaveFile(expected) => {
+                write!(f, "The save file {expected} is missing")
+            }
+        }
+    }
+}
+
+impl LintIssue {
+    /// Whether `fix_save` or `fix_bundle_files` fixes the issue.
+    pub fn fixable(&self) -> bool {
+        match self {
+            LintIssue::RunStatOutOfRange { .. }
+            | LintIssue::UpgradeOutOfRange { .. }
+            | LintIssue::UnknownBattery(_)
+            | LintIssue::MisnamedSaveFile { .. } => true,
+            // The health of a player, or who should keep the crown, can't be guessed.
+            // An unknown player may only have lost its name, its upgrades are worth keeping.
+            LintIssue::UnknownPlayer { .. }
+            | LintIssue::MissingPlayer { .. }
+            | LintIssue::SeveralCrowns(_)
+            | LintIssue::MissingSaveFile(_) => false,
+        }
+    }
+}
+
+/// The issues found in a save bundle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleLint {
+    pub id: BundleId,
+    pub location: PathBuf,
+    pub name: String,
+    pub issues: Vec<LintIssue>,
+    /// Why the save couldn't be read, if it couldn't
+    pub error: Option<String>,
+}
+
+impl BundleLint {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty() && self.error.is_none()
+    }
+
+    pub fn fixable(&self) -> bool {
+        self.issues.iter().any(LintIssue::fixable)
+    }
+}
+
+/// Names of the per-player dictionaries of a save with their entries, by Steam ID.
+fn player_dictionaries(save: &SaveGame) -> Vec<(String, BTreeSet<String>)> {
+    let value = &save.dictionary_of_dictionaries.value;
+    let mut dictionaries = vec![(
+        "playerHealth".to_string(),
+        value.player_health.keys().cloned().collect(),
+    )];
+    for spec in PLAYER_STATS {
+        if let Some(dictionary) = edit::player_dictionary(value, spec.key) {
+            dictionaries.push((spec.key.to_string(), dictionary.keys().cloned().collect()));
+        }
+    }
+    let mut extra: Vec<_> = value
+        .extra
+        .iter()
+        .filter(|(key, dictionary)| edit::is_extra_player_dictionary(key, dictionary))
+        .filter_map(|(key, dictionary)| {
+            Some((
+                key.clone(),
+                dictionary.as_object()?.keys().cloned().collect(),
+            ))
+        })
+        .collect();
+    extra.sort();
+    dictionaries.extend(extra);
+    dictionaries
+}
+
+/// Check the data of a save.
+pub fn lint_save(save: &SaveGame) -> Vec<LintIssue> {
+    let value = &save.dictionary_of_dictionaries.value;
+    let names = &save.player_names.value;
+    let mut issues = Vec::new();
+
+    for (key, stat) in edit::sorted_run_stats(&value.run_stats) {
+        let spec = edit::run_stat_spec(&key);
+        if !(spec.min..=spec.max).contains(&stat) {
+            issues.push(LintIssue::RunStatOutOfRange {
+                key,
+                value: stat,
+                min: spec.min,
+                max: spec.max,
+            });
+        }
+    }
+
+    for player in edit::player_stats(save) {
+        if !names.contains_key(&player.steam_id) {
+            continue;
+        }
+        for (spec, stat) in PLAYER_STATS.iter().zip(&player.values) {
+            if !(spec.min..=spec.max).contains(stat) {
+                issues.push(LintIssue::UpgradeOutOfRange {
+                    player: player.name.clone(),
+                    key: spec.key.to_string(),
+                    value: *stat,
+                    min: spec.min,
+                    max: spec.max,
+                });
+            }
+        }
+    }
+
+    let mut steam_ids: Vec<&String> = names.keys().collect();
+    steam_ids.sort();
+    for (dictionary, entries) in player_dictionaries(save) {
+        for steam_id in entries.iter().filter(|id| !names.contains_key(*id)) {
+            issues.push(LintIssue::UnknownPlayer {
+                dictionary: dictionary.clone(),
+                steam_id: steam_id.clone(),
+            });
+        }
+        // A missing upgrade or crown is 0, only the health is required.
+        if dictionary != "playerHealth" {
+            continue;
+        }
+        for steam_id in steam_ids.iter().filter(|id| !entries.contains(**id)) {
+            issues.push(LintIssue::MissingPlayer {
+                dictionary: dictionary.clone(),
+                player: names[*steam_id].clone(),
+            });
+        }
+    }
+
+    let mut crowned: Vec<String> = value
+        .player_has_crown
+        .iter()
+        .filter(|(_, crown)| **crown == 1)
+        .map(|(steam_id, _)| names.get(steam_id).unwrap_or(steam_id).clone())
+        .collect();
+    if crowned.len() > 1 {
+        crowned.sort();
+        issues.push(LintIssue::SeveralCrowns(crowned));
+    }
+
+    let mut batteries: Vec<&String> = value
+        .item_stat_battery
+        .keys()
+        .filter(|instance| !value.item.contains_key(*instance))
+        .collect();
+    batteries.sort();
+    issues.extend(
+        batteries
+            .into_iter()
+            .map(|instance| LintIssue::UnknownBattery(instance.clone())),
+    );
+    issues
+}
+
+/// Fix the issues of a save that can be fixed safely (see `LintIssue::fixable`):
+/// values out of range are brought back in range and batteries of unknown items are removed.
+/// Missing upgrades are left out, they are 0, and entries of unknown players are kept.
+pub fn fix_save(save: &mut SaveGame) {
+    let value = &mut save.dictionary_of_dictionaries.value;
+
+    for (key, stat) in &mut value.run_stats {
+        let spec = edit::run_stat_spec(key);
+        *stat = (*stat).clamp(spec.min, spec.max);
+    }
+
+    for spec in PLAYER_STATS {
+        if let Some(dictionary) = edit::player_dictionary_mut(value, spec.key) {
+            for stat in dictionary.values_mut() {
+                *stat = (*stat).clamp(spec.min, spec.max);
+            }
+        }
+    }
+
+    let items = value.item.clone();
+    value
+        .item_stat_battery
+        .retain(|instance, _| items.contains_key(instance));
+}
+
+/// Check that the save file of a bundle is named after its directory.
+pub fn lint_bundle_files(location: impl AsRef<Path>) -> Vec<LintIssue> {
+    let location = location.as_ref();
+    let Some(name) = location.file_name().and_then(|name| name.to_str()) else {
+        return Vec::new();
+    };
+    let expected = format!("{name}.es3");
+    if location.join(&expected).is_file() {
+        return Vec::new();
+    }
+    match save_files(location).as_slice() {
+        [file] => vec![LintIssue::MisnamedSaveFile {
+            file: file.clone(),
+            expected,
+        }],
+        _ => vec![LintIssue::MissingSaveFile(expected)],
+    }
+}
+
+/// Rename the save file of a bundle after its directory, if it's the only save file
+/// and it's named otherwise.
+pub fn fix_bundle_files(location: impl AsRef<Path>) -> Result<(), SaveBundleError> {
+    let location = location.as_ref();
+    for issue in lint_bundle_files(location) {
+        if let LintIssue::MisnamedSaveFile { file, expected } = issue {
+            std::fs::rename(location.join(file), location.join(expected))?;
+        }
+    }
+    Ok(())
+}
+
+/// Fix the issues of a save bundle that can be fixed safely, after taking a snapshot of it
+/// (see `edit::snapshot_bundle`): the save file is named after the directory, then the data is
+/// fixed with `fix_save`.
+///
+/// Returns the location of the snapshot, or None if there was nothing to fix.
+pub fn fix_bundle(
+    location: impl AsRef<Path>,
+    snapshot_directory: impl AsRef<Path>,
+) -> Result<Option<PathBuf>, EditError> {
+    let location = location.as_ref();
+    let lint = lint_bundle(location);
+    if !lint.fixable() {
+        return Ok(None);
+    }
+    let snapshot =
+        edit::snapshot_bundle(location, snapshot_directory).map_err(EditError::SnapshotFailed)?;
+    fix_bundle_files(location)?;
+    let save_file = location.join(format!("{}.es3", lint.name));
+    let mut save = save_bundle::read_save_file(&save_file)?;
+    fix_save(&mut save);
+    save_bundle::write_save_file(&save_file, &save)?;
+    Ok(Some(snapshot))
+}
+
+/// Names of the `.es3` files of a directory, sorted.
+fn save_files(location: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(location) else {
+        return Vec::new();
+    };
+    let mut files: Vec<String> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|file| file.ends_with(".es3"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Check a save bundle: the naming of its save file, then its data.
+pub fn lint_bundle(location: impl AsRef<Path>) -> BundleLint {
+    let location = location.as_ref();
+    let mut lint = BundleLint {
+        id: BundleId::from_location(location),
+        location: location.to_path_buf(),
+        name: location
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        issues: lint_bundle_files(location),
+        error: None,
+    };
+    let save_file = match lint.issues.first() {
+        Some(LintIssue::MisnamedSaveFile { file, .. }) => location.join(file),
+        Some(_) => return lint,
+        None => location.join(format!("{}.es3", lint.name)),
+    };
+    match save_bundle::read_save_file(save_file) {
+        Ok(save) => lint.issues.extend(lint_save(&save)),
+        Err(err) => lint.error = Some(err.to_string()),
+    }
+    lint
+}
+
+/// Check every save bundle of a directory, sorted by name.
+/// Hidden directories, eg. the snapshots and templates, are skipped.
+pub fn lint_directory(directory: impl AsRef<Path>) -> Vec<BundleLint> {
+    let Ok(entries) = std::fs::read_dir(directory.as_ref()) else {
+        return Vec::new();
+    };
+    let mut lints: Vec<BundleLint> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry.path().is_dir() && !entry.file_name().to_string_lossy().starts_with('.')
+        })
+        .map(|entry| lint_bundle(entry.path()))
+        .collect();
+    lints.sort_by(|a, b| a.name.cmp(&b.name));
+    lints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::save::fixtures;
+    use std::collections::HashMap;
+    use tempfile::tempdir;
+
+    mod lint_save {
+        use super::*;
+
+        #[test]
+        fn accepts_fixture() {
+            // Arrange
+            let save = fixtures::save_game();
+
+            // Act
+            let issues = lint_save(&save);
+
+            // Assert
+            assert!(issues.is_empty());
+        }
+
+        #[test]
+        fn finds_unknown_player() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            save.dictionary_of_dictionaries
+                .value
+                .player_upgrade_speed
+                .insert("76561198000000009".to_string(), 3);
+
+            // Act
+            let issues = lint_save(&save);
+
+            // Assert
+            assert_eq!(
+                issues,
+                vec![LintIssue::UnknownPlayer {
+                    dictionary: "playerUpgradeSpeed".to_string(),
+                    steam_id: "76561198000000009".to_string(),
+                }]
+            );
+        }
+
+        #[test]
+        fn finds_missing_health() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            save.dictionary_of_dictionaries
+                .value
+                .player_health
+                .remove("76561198000000002");
+
+            // Act
+            let issues = lint_save(&save);
+
+            // Assert
+            assert_eq!(
+                issues,
+                vec![LintIssue::MissingPlayer {
+                    dictionary: "playerHealth".to_string(),
+                    player: "Bob".to_string(),
+                }]
+            );
+        }
+
+        #[test]
+        fn accepts_missing_upgrades_and_crown() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            let value = &mut save.dictionary_of_dictionaries.value;
+            value.player_upgrade_range.remove("76561198000000002");
+            value.player_has_crown.remove("76561198000000002");
+
+            // Act
+            let issues = lint_save(&save);
+
+            // Assert
+            assert!(issues.is_empty());
+        }
+
+        #[test]
+        fn accepts_edited_saves() {
+            // Arrange
+            let source = fixtures::save_game();
+            let mut transplanted = fixtures::save_game();
+            let mut edited = fixtures::save_game();
+            let stats = HashMap::from([(
+                "76561198000000002".to_string(),
+                HashMap::from([
+                    ("playerUpgradeHealth".to_string(), 0),
+                    ("playerHasCrown".to_string(), 1),
+                ]),
+            )]);
+
+            // Act
+            edit::transplant_player(
+                &source,
+                "76561198000000001",
+                &mut transplanted,
+                "76561198000000003",
+            )
+            .unwrap();
+            edit::set_player_stats(&mut edited, &stats).unwrap();
+
+            // Assert
+            assert!(lint_save(&transplanted).is_empty());
+            assert!(lint_save(&edited).is_empty());
+        }
+    }
+
+    mod fix_save {
+        use super::*;
+
+        #[test]
+        fn fixes_safe_issues() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            let value = &mut save.dictionary_of_dictionaries.value;
+            value.run_stats.insert("lives".to_string(), -2);
+            value
+                .item_stat_battery
+                .insert("Item Gone/1".to_string(), 50);
+
+            // Act
+            fix_save(&mut save);
+
+            // Assert
+            assert!(lint_save(&save).is_empty());
+        }
+
+        #[test]
+        fn keeps_unknown_players() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            save.player_names.value.remove("76561198000000002");
+
+            // Act
+            fix_save(&mut save);
+
+            // Assert
+            let value = &save.dictionary_of_dictionaries.value;
+            assert_eq!(value.player_health.get("76561198000000002"), Some(&80));
+            assert!(lint_save(&save).iter().all(|issue| !issue.fixable()));
+        }
+
+        #[test]
+        fn leaves_health_and_crowns_to_the_user() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            let value = &mut save.dictionary_of_dictionaries.value;
+            value.player_health.remove("76561198000000002");
+            value
+                .player_has_crown
+                .insert("76561198000000002".to_string(), 1);
+
+            // Act
+            fix_save(&mut save);
+
+            // Assert
+            let remaining = lint_save(&save);
+            assert_eq!(remaining.len(), 2);
+            assert!(remaining.iter().all(|issue| !issue.fixable()));
+        }
+
+        #[test]
+        fn adds_no_upgrades() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            save.dictionary_of_dictionaries
+                .value
+                .player_upgrade_range
+                .remove("76561198000000002");
+
+            // Act
+            fix_save(&mut save);
+
+            // Assert
+            let value = &save.dictionary_of_dictionaries.value;
+            assert!(!value.player_upgrade_range.contains_key("76561198000000002"));
+        }
+    }
+
+    mod lint_bundle {
+        use super::*;
+
+        #[test]
+        fn finds_and_fixes_misnamed_save_file() {
+            // Arrange
+            let root = tempdir().unwrap();
+            let location = fixtures::save_bundle_dir(root.path(), "REPO_SAVE_1");
+            let misnamed = root.path().join("REPO_SAVE_2");
+            std::fs::rename(&location, &misnamed).unwrap();
+
+            // Act
+            let lint = lint_bundle(&misnamed);
+            let snapshot = fix_bundle(&misnamed, root.path().join(".snapshots")).unwrap();
+
+            // Assert
+            assert_eq!(
+                lint.issues,
+                vec![LintIssue::MisnamedSaveFile {
+                    file: "REPO_SAVE_1.es3".to_string(),
+                    expected: "REPO_SAVE_2.es3".to_string(),
+                }]
+            );
+            assert!(snapshot.unwrap().join("REPO_SAVE_1.es3").is_file());
+            assert!(lint_bundle(&misnamed).is_clean());
+        }
+    }
+}
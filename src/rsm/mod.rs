@@ -1,2 +1,10 @@
 // RSM specific stuff
+pub mod diff;
+pub mod discovery;
+pub mod edit;
+pub mod index;
+pub mod lint;
+pub mod patch;
 pub mod save_bundle;
+pub mod scan;
+pub mod template;
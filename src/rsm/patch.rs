@@ -0,0 +1,222 @@
+//! Scripted edits of the saves with JSON Patch ([RFC 6902]) and JSON merge patch ([RFC 7396])
+//! documents, applied to the JSON of the save.
+//!
+//! The patched JSON must still be a save: it's read back as a `SaveGame` before being written.
+//!
+//! [RFC 6902]: https://www.rfc-editor.org/rfc/rfc6902
+//! [RFC 7396]: https://www.rfc-editor.org/rfc/rfc7396
+
+use serde_json::Value;
+use std::path::Path;
+
+use crate::repo::save::SaveGame;
+use crate::rsm::edit::EditError;
+
+/// A patch to apply to the JSON of a save.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SavePatch {
+    /// JSON Patch, a list of operations
+    Json(json_patch::Patch),
+    /// JSON merge patch, an object merged into the save
+    Merge(Value),
+}
+
+impl SavePatch {
+    /// Parse a patch: an array is a JSON Patch, an object a JSON merge patch.
+    pub fn parse(data: &[u8]) -> Result<Self, EditError> {
+        let value: Value =
+            serde_json::from_slice(data).map_err(|e| EditError::InvalidPatch(e.to_string()))?;
+        match value {
+            Value::Array(_) => serde_json::from_value(value)
+                .map(SavePatch::Json)
+                .map_err(|e| EditError::InvalidPatch(e.to_string())),
+            Value::Object(_) => Ok(SavePatch::Merge(value)),
+            _ => Err(EditError::InvalidPatch(
+                "expected an array of operations (JSON Patch) or an object (merge patch)"
+                    .to_string(),
+            )),
+        }
+    }
+
+    /// Read and parse a patch file, see `parse`.
+    pub fn read(file: impl AsRef<Path>) -> Result<Self, EditError> {
+        let data = std::fs::read(file.as_ref()).map_err(|e| {
+            EditError::InvalidPatch(format!("can't read {}: {e}", file.as_ref().display()))
+        })?;
+        Self::parse(&data)
+    }
+
+    /// Apply the patch to a save, nothing is changed if it fails or if the result isn't a save.
+    pub fn apply(&self, save: &mut SaveGame) -> Result<(), EditError> {
+        let mut json = to_json(save)?;
+        match self {
+            SavePatch::Json(patch) => {
+                json_patch::patch(&mut json, patch).map_err(EditError::PatchFailed)?
+            }
+            SavePatch::Merge(patch) => json_patch::merge(&mut json, patch),
+        }
+        *save = serde_json::from_value(json).map_err(EditError::InvalidPatchedSave)?;
+        Ok(())
+    }
+}
+
+/// A value of the JSON of a save changed, added or removed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonChange {
+    /// JSON Pointer to the value, eg. `/teamName/value`
+    pub path: String,
+    /// None if the value was added
+    pub before: Option<Value>,
+    /// None if the value was removed
+    pub after: Option<Value>,
+}
+
+/// The changes between the JSON of two saves, eg. to preview a patch.
+pub fn json_changes(before: &SaveGame, after: &SaveGame) -> Result<Vec<JsonChange>, EditError> {
+    let mut patched = to_json(before)?;
+    let after = to_json(after)?;
+    let mut changes = Vec::new();
+    // The operations apply one after another, eg. indices shift after an array removal,
+    // so the value before each one is read from the JSON patched so far.
+    for operation in json_patch::diff(&patched, &after).0 {
+        let (path, before, after) = match &operation {
+            json_patch::PatchOperation::Add(op) => (&op.path, None, Some(op.value.clone())),
+            json_patch::PatchOperation::Replace(op) => (
+                &op.path,
+                patched.pointer(op.path.as_str()).cloned(),
+                Some(op.value.clone()),
+            ),
+            json_patch::PatchOperation::Remove(op) => {
+                (&op.path, patched.pointer(op.path.as_str()).cloned(), None)
+            }
+            // A diff is only made of additions, replacements and removals.
+            _ => continue,
+        };
+        changes.push(JsonChange {
+            path: path.to_string(),
+            before,
+            after,
+        });
+        json_patch::patch(&mut patched, std::slice::from_ref(&operation))
+            .map_err(EditError::PatchFailed)?;
+    }
+    Ok(changes)
+}
+
+fn to_json(save: &SaveGame) -> Result<Value, EditError> {
+    serde_json::to_value(save).map_err(EditError::InvalidPatchedSave)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::save::fixtures;
+
+    mod apply {
+        use super::*;
+
+        #[test]
+        fn applies_json_and_merge_patches() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            let json_patch = SavePatch::parse(
+                br#"[{"op": "replace", "path": "/dictionaryOfDictionaries/value/runStats/level", "value": 9}]"#,
+            )
+            .unwrap();
+            let merge_patch = SavePatch::parse(br#"{"teamName": {"value": "Patched"}}"#).unwrap();
+
+            // Act
+            let json_result = json_patch.apply(&mut save);
+            let merge_result = merge_patch.apply(&mut save);
+
+            // Assert
+            assert!(json_result.is_ok() && merge_result.is_ok());
+            assert_eq!(save.dictionary_of_dictionaries.value.run_stats["level"], 9);
+            assert_eq!(save.team_name.value, "Patched");
+        }
+
+        #[test]
+        fn refuses_patches_breaking_the_save() {
+            // Arrange
+            let mut save = fixtures::save_game();
+            let wrong_type = SavePatch::parse(
+                br#"{"dictionaryOfDictionaries": {"value": {"runStats": {"level": "high"}}}}"#,
+            )
+            .unwrap();
+            let missing_path =
+                SavePatch::parse(br#"[{"op": "remove", "path": "/notInTheSave"}]"#).unwrap();
+
+            // Act
+            let wrong_type = wrong_type.apply(&mut save);
+            let missing_path = missing_path.apply(&mut save);
+
+            // Assert
+            assert!(matches!(wrong_type, Err(EditError::InvalidPatchedSave(_))));
+            assert!(matches!(missing_path, Err(EditError::PatchFailed(_))));
+            assert_eq!(save, fixtures::save_game());
+        }
+    }
+
+    mod parse {
+        use super::*;
+
+        #[test]
+        fn refuses_other_values() {
+            // Act
+            let result = SavePatch::parse(b"42");
+
+            // Assert
+            assert!(matches!(result, Err(EditError::InvalidPatch(_))));
+        }
+    }
+
+    mod json_changes {
+        use super::*;
+
+        #[test]
+        fn lists_replaced_values() {
+            // Arrange
+            let before = fixtures::save_game();
+            let mut after = before.clone();
+            after.team_name.value = "Patched".to_string();
+
+            // Act
+            let changes = json_changes(&before, &after).unwrap();
+
+            // Assert
+            assert_eq!(
+                changes,
+                vec![JsonChange {
+                    path: "/teamName/value".to_string(),
+                    before: Some(Value::from("R.E.P.O.")),
+                    after: Some(Value::from("Patched")),
+                }]
+            );
+        }
+
+        #[test]
+        fn shows_removed_array_items() {
+            // Arrange
+            let mut before = fixtures::save_game();
+            before
+                .dictionary_of_dictionaries
+                .value
+                .extra
+                .insert("list".to_string(), serde_json::json!([1, 2, 3]));
+            let mut after = before.clone();
+            after
+                .dictionary_of_dictionaries
+                .value
+                .extra
+                .insert("list".to_string(), serde_json::json!([1]));
+
+            // Act
+            let changes = json_changes(&before, &after).unwrap();
+
+            // Assert
+            let removed: Vec<_> = changes.iter().map(|change| &change.before).collect();
+            assert_eq!(removed, vec![&Some(Value::from(2)), &Some(Value::from(3))]);
+            assert!(changes.iter().all(|change| change.after.is_none()));
+        }
+    }
+}
@@ -2,14 +2,21 @@ use crate::constant;
 use crate::repo;
 use serde;
 use serde_json;
+use sha1::{Digest, Sha1};
+use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::SystemTime;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum SaveBundleError {
     #[error("Decryption failed: {0}")]
     DecryptError(repo::crypt::DecryptError),
+    #[error("Encryption failed: {0}")]
+    EncryptError(repo::crypt::EncryptError),
+    #[error("IO error: {0}")]
+    IOError(#[from] std::io::Error),
     #[error("JSON error: {0}")]
     JSONError(serde_json::Error),
     #[error("Failed to get directory name")]
@@ -20,6 +27,10 @@ pub enum SaveBundleError {
     MissingFile,
     #[error("Expected file")]
     ExpectedFile,
+    #[error("Invalid save name `{0}`, expected REPO_SAVE_ followed by letters, digits or `_`")]
+    InvalidSaveName(String),
+    #[error("A save named `{0}` already exists")]
+    SaveExists(String),
 }
 
 /// Represents a REPO Save directory (official or backup)
@@ -27,6 +38,8 @@ pub enum SaveBundleError {
 /// as the bundle folder and optionally other backups of the .es3 file.
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq)]
 pub struct SaveBundle {
+    /// identifier derived from the location, unlike the name it's unique across directories
+    pub id: BundleId,
     /// directory location on disk
     pub location: PathBuf,
 
@@ -36,6 +49,86 @@ pub struct SaveBundle {
     pub level: i32,
     /// player list
     pub players: Vec<String>,
+    /// team name, set by the players in game
+    #[serde(default)]
+    pub team_name: String,
+    /// time played in seconds
+    #[serde(default)]
+    pub time_played: f32,
+    /// date and time the save was last written, as stored by the game
+    #[serde(default)]
+    pub date: String,
+    /// state of the bundle's files when the metadata was read
+    #[serde(default)]
+    pub fingerprint: Fingerprint,
+    /// SHA-1 of the save file when the metadata was read, hex encoded
+    #[serde(default)]
+    pub hash: String,
+    /// name of the save source a backup was made from, see `write_source`
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// Stable identifier of a save bundle, derived from its location.
+///
+/// Bundles in different directories can share a name (backups of the same save,
+/// snapshots, several save directories), the ID tells them apart.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(transparent)]
+pub struct BundleId(String);
+
+impl BundleId {
+    /// Number of hex digits kept from the hash of the location.
+    const LEN: usize = 16;
+
+    pub fn from_location(location: impl AsRef<Path>) -> Self {
+        let digest = Sha1::digest(location.as_ref().as_os_str().as_encoded_bytes());
+        let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+        BundleId(hex[..Self::LEN].to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for BundleId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for BundleId {
+    fn from(id: &str) -> Self {
+        BundleId(id.to_string())
+    }
+}
+
+/// Name, size and modification time of the files in a bundle directory.
+/// If the fingerprint of a bundle is unchanged, its metadata doesn't need to be read again.
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize, PartialEq)]
+pub struct Fingerprint {
+    files: Vec<(String, u64, SystemTime)>,
+}
+
+impl Fingerprint {
+    /// Fingerprint the files directly inside `location`, sub-directories are ignored.
+    pub fn of(location: impl AsRef<Path>) -> Result<Self, SaveBundleError> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(location)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                files.push((
+                    entry.file_name().to_string_lossy().to_string(),
+                    metadata.len(),
+                    metadata.modified()?,
+                ));
+            }
+        }
+        files.sort();
+        Ok(Fingerprint { files })
+    }
 }
 
 /// A SaveBundle represents how REPO stores a save on the disk.
@@ -64,35 +157,71 @@ impl SaveBundle {
             .ok_or(SaveBundleError::InvalidFileName)?
             .to_string();
         let save_file = location.as_ref().join(format!("{}.es3", &name));
+        // Fingerprint first, so that a change made while reading is caught by the next scan.
+        let fingerprint = Fingerprint::of(&location)?;
         let save_data = read_save_file(&save_file)?;
-        Ok(SaveBundle {
+        let hash = hash_file(&save_file)?;
+        let mut save_bundle = SaveBundle {
+            id: BundleId::from_location(&location),
             location: location.as_ref().to_path_buf(),
             name,
-            level: *save_data
-                .dictionary_of_dictionaries
-                .value
-                .run_stats
-                .get("level")
-                .unwrap_or(&0i32),
-            players: save_data.player_names.value.into_values().collect(),
-        })
+            level: 0,
+            players: Vec::new(),
+            team_name: String::new(),
+            time_played: 0.0,
+            date: String::new(),
+            fingerprint,
+            hash,
+            source: read_source(&location),
+        };
+        save_bundle.set_metadata(save_data);
+        Ok(save_bundle)
+    }
+
+    /// Path of the bundle's main save file.
+    pub fn save_file(&self) -> PathBuf {
+        self.location.join(format!("{}.es3", self.name))
     }
 
     /// Reads the save file, decrypts it and returns the Deserialised JSON data.
     pub fn get_data(&self) -> Result<repo::save::SaveGame, SaveBundleError> {
-        let save_file = self.location.join(format!("{}.es3", self.name));
-        let save_data = read_save_file(&save_file)?;
+        let save_data = read_save_file(self.save_file())?;
         Ok(save_data)
     }
 
+    /// Serialises the save data, encrypts it and replaces the content of the save file.
+    /// The metadata stored in the struct is updated to match the new data.
+    ///
+    /// The backup files of the bundle (eg. `REPO_SAVE_<date>_BACKUP1.es3`) are left untouched.
+    pub fn set_data(&mut self, save_data: &repo::save::SaveGame) -> Result<(), SaveBundleError> {
+        write_save_file(self.save_file(), save_data)?;
+        self.set_metadata(save_data.clone());
+        self.fingerprint = Fingerprint::of(&self.location)?;
+        self.hash = hash_file(self.save_file())?;
+        Ok(())
+    }
+
     /// Refresh the save metadata stored in the struct by re-reading the save file.
     /// This is useful if the save has been updated since last read.
     ///
     /// This method modifies the following fields:
     /// - level
     /// - players
+    /// - team_name
+    /// - time_played
+    /// - date
+    /// - fingerprint
+    /// - hash
     pub fn refresh_data(&mut self) -> Result<(), SaveBundleError> {
-        let save_data = read_save_file(&self.location)?;
+        self.fingerprint = Fingerprint::of(&self.location)?;
+        let save_data = self.get_data()?;
+        self.hash = hash_file(self.save_file())?;
+        self.set_metadata(save_data);
+        Ok(())
+    }
+
+    /// Copy the metadata shown to the user from the save data.
+    fn set_metadata(&mut self, save_data: repo::save::SaveGame) {
         self.level = *save_data
             .dictionary_of_dictionaries
             .value
@@ -103,10 +232,113 @@ impl SaveBundle {
         // Sorting the player list makes it consistent in the UI later down the line.
         // The theoretical max len of this list is 6 players so the cost will be quite low.
         self.players.sort();
+        self.team_name = save_data.team_name.value;
+        self.time_played = save_data.time_played.value;
+        self.date = save_data.date_and_time.value;
+    }
+}
+
+/// Name for a new save made now, in the format used by the game,
+/// eg. `REPO_SAVE_2025_04_12_15_39_47`.
+pub fn new_save_name() -> String {
+    chrono::Local::now()
+        .format("REPO_SAVE_%Y_%m_%d_%H_%M_%S")
+        .to_string()
+}
+
+/// Name for a new save made now in `directory`, see `new_save_name`.
+/// If a save already has that name, eg. when several saves are made within a second,
+/// the next second with no save is used.
+pub fn available_save_name(directory: impl AsRef<Path>) -> String {
+    let now = chrono::Local::now();
+    (0..)
+        .map(|seconds| {
+            (now + chrono::TimeDelta::seconds(seconds))
+                .format("REPO_SAVE_%Y_%m_%d_%H_%M_%S")
+                .to_string()
+        })
+        .find(|name| !directory.as_ref().join(name).exists())
+        .unwrap_or_else(new_save_name)
+}
+
+/// Date stored in `dateAndTime` for a save named after the time it was made,
+/// eg. `2025-04-12` for `REPO_SAVE_2025_04_12_15_39_47`. None for other names.
+pub fn save_name_date(name: &str) -> Option<String> {
+    let date = chrono::NaiveDateTime::parse_from_str(
+        name.strip_prefix(constant::SAVE_PREFIX)?,
+        "%Y_%m_%d_%H_%M_%S",
+    )
+    .ok()?;
+    Some(date.format("%Y-%m-%d").to_string())
+}
+
+/// Check that a name can be used for a save bundle: it's both the name of the bundle directory
+/// and of its save file, so it must be a plain file name the game recognises.
+pub fn validate_save_name(name: &str) -> Result<(), SaveBundleError> {
+    let valid = name
+        .strip_prefix(constant::SAVE_PREFIX)
+        .is_some_and(|rest| {
+            !rest.is_empty() && rest.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+    if valid {
         Ok(())
+    } else {
+        Err(SaveBundleError::InvalidSaveName(name.to_string()))
     }
 }
 
+/// Rename a save bundle: its directory, its save file and the backup files the game keeps
+/// next to it (eg. `<name>_BACKUP1.es3`), so that they keep matching the name of the directory.
+///
+/// If renaming a file fails, the files renamed and the directory are renamed back.
+/// Returns the new location of the bundle.
+pub fn rename_save_bundle(
+    location: impl AsRef<Path>,
+    new_name: &str,
+) -> Result<PathBuf, SaveBundleError> {
+    let location = location.as_ref();
+    validate_save_name(new_name)?;
+    let old_name = location
+        .file_name()
+        .ok_or(SaveBundleError::NoFileName)?
+        .to_str()
+        .ok_or(SaveBundleError::InvalidFileName)?
+        .to_string();
+    let new_location = location.with_file_name(new_name);
+    if new_location.exists() {
+        return Err(SaveBundleError::SaveExists(new_name.to_string()));
+    }
+    std::fs::rename(location, &new_location)?;
+
+    let mut renamed: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let result = (|| {
+        for entry in std::fs::read_dir(&new_location)? {
+            let file_name = entry?.file_name().to_string_lossy().to_string();
+            if let Some(rest) = file_name.strip_prefix(&old_name)
+                && rest.ends_with(".es3")
+            {
+                let from = new_location.join(&file_name);
+                let to = new_location.join(format!("{new_name}{rest}"));
+                std::fs::rename(&from, &to)?;
+                renamed.push((from, to));
+            }
+        }
+        Ok(())
+    })();
+    if let Err(e) = result {
+        for (from, to) in renamed.iter().rev() {
+            if let Err(undo_err) = std::fs::rename(to, from) {
+                log::error!(undo_err:err; "Failed to rename {:?} back", to);
+            }
+        }
+        if let Err(undo_err) = std::fs::rename(&new_location, location) {
+            log::error!(undo_err:err; "Failed to rename {:?} back", new_location);
+        }
+        return Err(SaveBundleError::IOError(e));
+    }
+    Ok(new_location)
+}
+
 /// Read a save file by decrypting it and deserializing the JSON.
 pub fn read_save_file(
     save_file: impl AsRef<Path>,
@@ -118,23 +350,134 @@ pub fn read_save_file(
     if !save_file.is_file() {
         return Err(SaveBundleError::ExpectedFile);
     }
-    let data: Vec<u8> = repo::crypt::decrypt_es3(&save_file, constant::ENCRYPTION_PASS)
-        .map_err(|e| SaveBundleError::DecryptError(e))?;
-    let save_data = serde_json::from_slice(&data).map_err(|e| SaveBundleError::JSONError(e))?;
+    let data: Vec<u8> = repo::crypt::decrypt_es3(save_file, constant::ENCRYPTION_PASS)
+        .map_err(SaveBundleError::DecryptError)?;
+    let save_data = serde_json::from_slice(&data).map_err(SaveBundleError::JSONError)?;
     Ok(save_data)
 }
 
+/// SHA-1 of a file's content, hex encoded.
+pub fn hash_file(file: impl AsRef<Path>) -> Result<String, SaveBundleError> {
+    let digest = Sha1::digest(std::fs::read(file)?);
+    Ok(digest.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// File recording the save source a backup was made from.
+///
+/// It's kept next to the bundle rather than inside it, so that it isn't copied back to
+/// the game save directory on restore. It's hidden, so scans ignore it.
+pub fn source_file(location: impl AsRef<Path>) -> PathBuf {
+    let location = location.as_ref();
+    let name = location.file_name().unwrap_or_default().to_string_lossy();
+    location.with_file_name(format!(".{name}.source"))
+}
+
+/// Name of the save source recorded for a bundle, None if there is none.
+pub fn read_source(location: impl AsRef<Path>) -> Option<String> {
+    let source = std::fs::read_to_string(source_file(location)).ok()?;
+    let source = source.trim();
+    (!source.is_empty()).then(|| source.to_string())
+}
+
+/// Record the save source a bundle was copied from.
+pub fn write_source(location: impl AsRef<Path>, source: &str) -> std::io::Result<()> {
+    std::fs::write(source_file(location), source)
+}
+
+/// Record `new` as the save source of the backups in `backup_directory` recorded from `old`,
+/// eg. when the source is renamed. Nothing is done if the directory doesn't exist.
+pub fn rename_recorded_source(
+    backup_directory: impl AsRef<Path>,
+    old: &str,
+    new: &str,
+) -> std::io::Result<()> {
+    let entries = match std::fs::read_dir(backup_directory) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        if !file_name.starts_with('.') || !file_name.ends_with(".source") {
+            continue;
+        }
+        if std::fs::read_to_string(&path)?.trim() == old {
+            std::fs::write(&path, new)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write a save file by serializing the JSON and encrypting it.
+///
+/// If the file already exists, its compression (gzip or not) is kept.
+/// The data is written to a temporary file which then replaces the save file,
+/// so that the save file is never left half-written. It gets the permissions of the
+/// file it replaces, temporary files are only readable by their owner.
+pub fn write_save_file(
+    save_file: impl AsRef<Path>,
+    save_data: &repo::save::SaveGame,
+) -> Result<(), SaveBundleError> {
+    let save_file = save_file.as_ref();
+    let gzip = std::fs::read(save_file)
+        .ok()
+        .and_then(|data| {
+            repo::crypt::decode_es3(&data, &[("repo", constant::ENCRYPTION_PASS)]).ok()
+        })
+        .is_some_and(|(_, info)| info.gzipped);
+
+    let data = serde_json::to_vec(save_data).map_err(SaveBundleError::JSONError)?;
+    let encrypted_data = repo::crypt::encrypt_es3_bytes(&data, constant::ENCRYPTION_PASS, gzip)
+        .map_err(SaveBundleError::EncryptError)?;
+
+    let directory = save_file.parent().ok_or(SaveBundleError::NoFileName)?;
+    let mut temp_file = tempfile::NamedTempFile::new_in(directory)?;
+    temp_file.write_all(&encrypted_data)?;
+    if let Ok(metadata) = std::fs::metadata(save_file) {
+        temp_file
+            .as_file()
+            .set_permissions(metadata.permissions())?;
+    }
+    temp_file
+        .persist(save_file)
+        .map_err(|e| SaveBundleError::IOError(e.error))?;
+    Ok(())
+}
+
 /// Given a path to a directory as a string, extract a Vector of
 /// SaveBundle objects.
+///
+/// Bundles that can't be read are logged and skipped.
 pub fn extract_save_bundles(path: impl AsRef<Path>) -> Vec<SaveBundle> {
-    let mut save_bundles = Vec::new();
-    let entries = match std::fs::read_dir(path) {
-        Ok(entries) => entries,
+    let save_bundles = match check_save_bundles(path) {
+        Ok(save_bundles) => save_bundles,
         Err(e) => {
             log::error!(e:err; "Error occurred when reading directory.");
-            return save_bundles;
+            return Vec::new();
         }
     };
+    save_bundles
+        .into_iter()
+        .filter_map(|(_, save_bundle)| match save_bundle {
+            Ok(sd) => Some(sd),
+            Err(e) => {
+                log::error!(e:err; "SaveDirectoryError occured.");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Given a path to a directory, try to read every sub-directory as a SaveBundle.
+///
+/// Returns the location of each sub-directory along with the result of reading it,
+/// or an error if the directory itself can't be read.
+pub fn check_save_bundles(
+    path: impl AsRef<Path>,
+) -> std::io::Result<Vec<(PathBuf, Result<SaveBundle, SaveBundleError>)>> {
+    let mut save_bundles = Vec::new();
+    let entries = std::fs::read_dir(path)?;
 
     for entry in entries {
         let entry = match entry {
@@ -153,17 +496,266 @@ pub fn extract_save_bundles(path: impl AsRef<Path>) -> Vec<SaveBundle> {
             }
         };
 
-        if !file_type.is_dir() {
+        // Hidden directories hold copies in progress, see `fs_util::copy_directory`.
+        if !file_type.is_dir() || entry.file_name().to_string_lossy().starts_with('.') {
             continue;
         }
-        let save_bundle = match SaveBundle::new(entry.path()) {
-            Ok(sd) => sd,
-            Err(e) => {
-                log::error!(e:err; "SaveDirectoryError occured.");
-                continue;
-            }
-        };
-        save_bundles.push(save_bundle);
+        save_bundles.push((entry.path(), SaveBundle::new(entry.path())));
+    }
+    Ok(save_bundles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::save::fixtures;
+    use tempfile::tempdir;
+
+    mod set_data {
+        use super::*;
+
+        #[test]
+        fn round_trip_keeps_unknown_fields() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let location =
+                fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_2025_04_12_15_39_47");
+            let mut save_bundle = SaveBundle::new(&location).unwrap();
+            let mut save_data = save_bundle.get_data().unwrap();
+
+            // Act
+            save_data.team_name.value = "Renamed".to_string();
+            save_bundle.set_data(&save_data).unwrap();
+
+            // Assert
+            let written = SaveBundle::new(&location).unwrap();
+            assert_eq!(written.team_name, "Renamed");
+            assert_eq!(written, save_bundle);
+            let written_data = written.get_data().unwrap();
+            assert_eq!(written_data, save_data);
+            assert!(
+                written_data
+                    .dictionary_of_dictionaries
+                    .value
+                    .extra
+                    .contains_key("playerUpgradeCrouchRest")
+            );
+        }
+    }
+
+    mod write_save_file {
+        use super::*;
+
+        #[cfg(unix)]
+        #[test]
+        fn keeps_permissions() {
+            // Arrange
+            use std::os::unix::fs::PermissionsExt;
+            let temp_dir = tempdir().unwrap();
+            let location = fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_1");
+            let save_file = location.join("REPO_SAVE_1.es3");
+            std::fs::set_permissions(&save_file, std::fs::Permissions::from_mode(0o640)).unwrap();
+
+            // Act
+            write_save_file(&save_file, &fixtures::save_game()).unwrap();
+
+            // Assert
+            let mode = std::fs::metadata(&save_file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+    }
+
+    mod rename_save_bundle {
+        use super::*;
+
+        #[test]
+        fn renames_directory_and_save_files() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let location =
+                fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_2025_04_12_15_39_47");
+            std::fs::write(
+                location.join("REPO_SAVE_2025_04_12_15_39_47_BACKUP1.es3"),
+                b"",
+            )
+            .unwrap();
+
+            // Act
+            let renamed = rename_save_bundle(&location, "REPO_SAVE_2026_01_02_03_04_05").unwrap();
+
+            // Assert
+            assert!(!location.exists());
+            let save_bundle = SaveBundle::new(&renamed).unwrap();
+            assert_eq!(save_bundle.name, "REPO_SAVE_2026_01_02_03_04_05");
+            assert!(
+                renamed
+                    .join("REPO_SAVE_2026_01_02_03_04_05_BACKUP1.es3")
+                    .is_file()
+            );
+        }
+
+        #[test]
+        fn refuses_taken_name() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let location = fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_1");
+            let other = fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_2");
+
+            // Act
+            let result = rename_save_bundle(&location, "REPO_SAVE_2");
+
+            // Assert
+            assert!(matches!(result, Err(SaveBundleError::SaveExists(_))));
+            assert!(location.join("REPO_SAVE_1.es3").is_file());
+            assert!(other.join("REPO_SAVE_2.es3").is_file());
+        }
+
+        #[test]
+        fn refuses_invalid_name() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let location = fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_1");
+
+            // Act
+            let result = rename_save_bundle(&location, "../REPO_SAVE_3");
+
+            // Assert
+            assert!(matches!(result, Err(SaveBundleError::InvalidSaveName(_))));
+            assert!(location.join("REPO_SAVE_1.es3").is_file());
+        }
+    }
+
+    mod save_name_date {
+        use super::*;
+
+        #[test]
+        fn reads_date_of_name() {
+            // Act
+            let date = save_name_date("REPO_SAVE_2026_01_02_03_04_05");
+
+            // Assert
+            assert_eq!(date.as_deref(), Some("2026-01-02"));
+        }
+
+        #[test]
+        fn ignores_other_names() {
+            // Act
+            let date = save_name_date("REPO_SAVE_1");
+
+            // Assert
+            assert_eq!(date, None);
+        }
+    }
+
+    mod validate_save_name {
+        use super::*;
+
+        #[test]
+        fn accepts_new_save_name() {
+            // Act
+            let result = validate_save_name(&new_save_name());
+
+            // Assert
+            assert!(result.is_ok());
+        }
+
+        #[test]
+        fn refuses_paths() {
+            // Act
+            let result = validate_save_name("REPO_SAVE_1/../REPO_SAVE_2");
+
+            // Assert
+            assert!(matches!(result, Err(SaveBundleError::InvalidSaveName(_))));
+        }
+    }
+
+    mod bundle_id {
+        use super::*;
+
+        #[test]
+        fn depends_on_location_only() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let saves = fixtures::save_bundle_dir(&temp_dir.path().join("saves"), "REPO_SAVE_1");
+            let backups =
+                fixtures::save_bundle_dir(&temp_dir.path().join("backups"), "REPO_SAVE_1");
+
+            // Act
+            let save = SaveBundle::new(&saves).unwrap();
+            let backup = SaveBundle::new(&backups).unwrap();
+
+            // Assert
+            assert_eq!(save.name, backup.name);
+            assert_ne!(save.id, backup.id);
+            assert_eq!(save.id, BundleId::from_location(&saves));
+        }
+    }
+
+    mod read_source {
+        use super::*;
+
+        #[test]
+        fn reads_recorded_source() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let location = fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_1");
+            let unrecorded = fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_2");
+
+            // Act
+            write_source(&location, "Steam Deck").unwrap();
+
+            // Assert
+            assert_eq!(read_source(&location).as_deref(), Some("Steam Deck"));
+            assert_eq!(read_source(&unrecorded), None);
+            assert_eq!(
+                SaveBundle::new(&location).unwrap().source.as_deref(),
+                Some("Steam Deck")
+            );
+            // The record isn't taken for a bundle
+            assert_eq!(extract_save_bundles(temp_dir.path()).len(), 2);
+        }
+    }
+
+    mod rename_recorded_source {
+        use super::*;
+
+        #[test]
+        fn renames_only_matching_records() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let renamed = fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_1");
+            let other = fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_2");
+            write_source(&renamed, "Steam Deck").unwrap();
+            write_source(&other, "Desktop").unwrap();
+
+            // Act
+            rename_recorded_source(temp_dir.path(), "Steam Deck", "Deck").unwrap();
+
+            // Assert
+            assert_eq!(read_source(&renamed).as_deref(), Some("Deck"));
+            assert_eq!(read_source(&other).as_deref(), Some("Desktop"));
+            assert!(rename_recorded_source(temp_dir.path().join("missing"), "a", "b").is_ok());
+        }
+    }
+
+    mod extract_save_bundles {
+        use super::*;
+
+        #[test]
+        fn skips_invalid_bundles() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_2025_04_12_15_39_47");
+            std::fs::create_dir(temp_dir.path().join("REPO_SAVE_2025_01_01_00_00_00")).unwrap();
+
+            // Act
+            let save_bundles = extract_save_bundles(temp_dir.path());
+
+            // Assert
+            assert_eq!(save_bundles.len(), 1);
+            assert_eq!(save_bundles[0].name, "REPO_SAVE_2025_04_12_15_39_47");
+            assert_eq!(save_bundles[0].level, 4);
+            assert_eq!(save_bundles[0].players, vec!["Alice", "Bob"]);
+        }
     }
-    save_bundles
 }
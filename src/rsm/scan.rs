@@ -0,0 +1,236 @@
+//! Incremental scanning of save and backup directories.
+//!
+//! Reading a bundle means decrypting its save file, which is slow (PBKDF2 and AES),
+//! so bundles whose files haven't changed since the last scan are reused as they are,
+//! and the others are read in parallel.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use super::save_bundle::{Fingerprint, SaveBundle, SaveBundleError, hash_file, read_source};
+
+/// What needs to be done to bring a list of known bundles up to date with a directory.
+#[derive(Debug, Default)]
+pub struct ScanPlan {
+    /// Known bundles whose files haven't changed, sorted by location
+    pub unchanged: Vec<SaveBundle>,
+    /// Bundles that are new or changed, and must be read
+    pub to_read: Vec<PathBuf>,
+}
+
+/// Compare the bundle directories found in `directory` with the `known` bundles.
+///
+/// Known bundles that are no longer in the directory are left out of the plan.
+/// Hidden directories are ignored, they hold copies in progress (see `fs_util::copy_directory`).
+pub fn plan_scan(directory: impl AsRef<Path>, known: &[SaveBundle]) -> ScanPlan {
+    let mut plan = ScanPlan::default();
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::error!(e:err; "Error occurred when reading directory.");
+            return plan;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+        if !is_dir || entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let location = entry.path();
+        let unchanged = known.iter().find(|save_bundle| {
+            save_bundle.location == location
+                && Fingerprint::of(&location).is_ok_and(|f| f == save_bundle.fingerprint)
+        });
+        match unchanged {
+            Some(save_bundle) => plan.unchanged.push(save_bundle.clone()),
+            None => plan.to_read.push(location),
+        }
+    }
+    plan.unchanged.sort_by(|a, b| a.location.cmp(&b.location));
+    plan.to_read.sort();
+    plan
+}
+
+/// Read a bundle, reusing the metadata of the `known` bundle if its save file's content
+/// is unchanged. This avoids decrypting copies, whose modification times differ.
+pub fn revalidate(
+    location: &Path,
+    known: Option<&SaveBundle>,
+) -> Result<SaveBundle, SaveBundleError> {
+    if let Some(known) = known
+        && !known.hash.is_empty()
+    {
+        let fingerprint = Fingerprint::of(location)?;
+        if hash_file(known.save_file()).is_ok_and(|hash| hash == known.hash) {
+            return Ok(SaveBundle {
+                fingerprint,
+                source: read_source(location),
+                ..known.clone()
+            });
+        }
+    }
+    SaveBundle::new(location)
+}
+
+/// Read bundles in parallel, passing each result to `on_read` as soon as it's ready.
+/// `on_read` is called from several threads, one result at a time.
+///
+/// Bundles are revalidated against the `known` bundle at the same location, see `revalidate`.
+pub fn read_save_bundles(
+    locations: &[PathBuf],
+    known: &[SaveBundle],
+    on_read: impl FnMut(&Path, Result<SaveBundle, SaveBundleError>) + Send,
+) {
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(locations.len());
+    let next = AtomicUsize::new(0);
+    let on_read = Mutex::new(on_read);
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                while let Some(location) = locations.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let known = known.iter().find(|s| s.location == *location);
+                    let result = revalidate(location, known);
+                    if let Ok(mut on_read) = on_read.lock() {
+                        on_read(location, result);
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// Bring a list of `known` bundles up to date with `directory`, reading only what changed.
+/// Bundles that can't be read are logged and left out.
+pub fn scan_save_bundles(directory: impl AsRef<Path>, known: &[SaveBundle]) -> Vec<SaveBundle> {
+    let plan = plan_scan(directory, known);
+    let mut save_bundles = plan.unchanged;
+    read_save_bundles(&plan.to_read, known, |location, result| match result {
+        Ok(save_bundle) => insert_sorted(&mut save_bundles, save_bundle),
+        Err(e) => log::error!(e:err; "Failed to read save bundle {:?}", location),
+    });
+    save_bundles
+}
+
+/// Insert a bundle in a list sorted by location, replacing the bundle at the same location.
+pub fn insert_sorted(save_bundles: &mut Vec<SaveBundle>, save_bundle: SaveBundle) {
+    match save_bundles.binary_search_by(|s| s.location.cmp(&save_bundle.location)) {
+        Ok(index) => save_bundles[index] = save_bundle,
+        Err(index) => save_bundles.insert(index, save_bundle),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::save::fixtures;
+    use tempfile::tempdir;
+
+    mod plan_scan {
+        use super::*;
+
+        #[test]
+        fn reuses_unchanged_bundles() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let unchanged = fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_1");
+            let changed = fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_2");
+            let removed = temp_dir.path().join("REPO_SAVE_3");
+            let new = fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_4");
+            let mut known = vec![
+                SaveBundle::new(&unchanged).unwrap(),
+                SaveBundle::new(&changed).unwrap(),
+                SaveBundle {
+                    location: removed,
+                    ..SaveBundle::new(&unchanged).unwrap()
+                },
+            ];
+            std::fs::write(changed.join("REPO_SAVE_2_BACKUP1.es3"), b"").unwrap();
+            known[0].level = 42;
+
+            // Act
+            let plan = plan_scan(temp_dir.path(), &known);
+
+            // Assert
+            assert_eq!(plan.unchanged, vec![known[0].clone()]);
+            assert_eq!(plan.to_read, vec![changed, new]);
+        }
+    }
+
+    mod revalidate {
+        use super::*;
+
+        #[test]
+        fn reuses_metadata_of_identical_copy() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let location = fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_1");
+            let mut known = SaveBundle::new(&location).unwrap();
+            known.level = 42;
+            let save_file = std::fs::File::options()
+                .write(true)
+                .open(known.save_file())
+                .unwrap();
+            save_file
+                .set_modified(std::time::SystemTime::UNIX_EPOCH)
+                .unwrap();
+
+            // Act
+            let save_bundle = revalidate(&location, Some(&known)).unwrap();
+
+            // Assert
+            assert_eq!(save_bundle.level, 42);
+            assert_ne!(save_bundle.fingerprint, known.fingerprint);
+        }
+
+        #[test]
+        fn reads_changed_content() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let location = fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_1");
+            let mut known = SaveBundle::new(&location).unwrap();
+            known.level = 42;
+            known.hash = "outdated".to_string();
+
+            // Act
+            let save_bundle = revalidate(&location, Some(&known)).unwrap();
+
+            // Assert
+            assert_eq!(save_bundle, SaveBundle::new(&location).unwrap());
+        }
+    }
+
+    mod read_save_bundles {
+        use super::*;
+
+        #[test]
+        fn reads_every_bundle() {
+            // Arrange
+            let temp_dir = tempdir().unwrap();
+            let locations = vec![
+                fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_1"),
+                fixtures::save_bundle_dir(temp_dir.path(), "REPO_SAVE_2"),
+                temp_dir.path().join("missing"),
+            ];
+            let mut save_bundles = Vec::new();
+            let mut failures = 0;
+
+            // Act
+            read_save_bundles(&locations, &[], |_, result| match result {
+                Ok(save_bundle) => insert_sorted(&mut save_bundles, save_bundle),
+                Err(_) => failures += 1,
+            });
+
+            // Assert
+            let names: Vec<&str> = save_bundles.iter().map(|s| s.name.as_str()).collect();
+            assert_eq!(names, vec!["REPO_SAVE_1", "REPO_SAVE_2"]);
+            assert_eq!(failures, 1);
+        }
+    }
+}
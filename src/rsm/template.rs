@@ -0,0 +1,408 @@
+//! Save templates, to start several new saves from the same state, eg. for a tournament.
+//!
+//! A template is the JSON of a save, kept in the template directory of the backup directory
+//! as `<name>.json`, so that it can be shared and tweaked by hand.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::repo::save::SaveGame;
+use crate::rsm::edit::{self, EditError};
+use crate::rsm::save_bundle::{self, SaveBundleError};
+
+/// Directory holding the templates, inside the backup directory.
+/// It's hidden, so scans don't take it for a backup.
+pub const TEMPLATE_DIRECTORY: &str = ".templates";
+
+/// Maximum length of a template name, in characters.
+pub const MAX_TEMPLATE_NAME_LEN: usize = 64;
+
+#[derive(Error, Debug)]
+pub enum TemplateError {
+    #[error(
+        "Invalid template name `{0}`, names have between 1 and {MAX_TEMPLATE_NAME_LEN} characters, \
+        don't start with `.` and contain no `/` or `\\`"
+    )]
+    InvalidName(String),
+    #[error("A template named `{0}` already exists")]
+    Exists(String),
+    #[error("Template `{0}` not found")]
+    NotFound(String),
+    #[error("Template `{0}` is invalid: {1}")]
+    Invalid(String, serde_json::Error),
+    #[error("IO error: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error(transparent)]
+    Edit(#[from] EditError),
+}
+
+impl TemplateError {
+    /// What the user can do to fix the error.
+    pub fn hint(&self) -> String {
+        match self {
+            TemplateError::InvalidName(_) | TemplateError::Exists(_) => {
+                "Choose another name for the template.".to_string()
+            }
+            TemplateError::NotFound(_) => {
+                "Check the name of the template, it may have been deleted.".to_string()
+            }
+            TemplateError::Invalid(..) => {
+                "Fix the JSON of the template, or capture it again from a save.".to_string()
+            }
+            TemplateError::IOError(_) => {
+                "Check that the backup directory exists and can be written to.".to_string()
+            }
+            TemplateError::Edit(err) => err.hint(),
+        }
+    }
+}
+
+impl From<SaveBundleError> for TemplateError {
+    fn from(err: SaveBundleError) -> Self {
+        TemplateError::Edit(err.into())
+    }
+}
+
+/// A template, as listed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateInfo {
+    pub name: String,
+    pub team_name: String,
+    /// Level as stored in the save, starting at 0
+    pub level: i32,
+    pub players: Vec<String>,
+}
+
+/// Where the templates are kept, for a backup directory.
+pub fn template_directory(backup_directory: impl AsRef<Path>) -> PathBuf {
+    backup_directory.as_ref().join(TEMPLATE_DIRECTORY)
+}
+
+pub fn validate_template_name(name: &str) -> Result<(), TemplateError> {
+    let valid = !name.trim().is_empty()
+        && name.chars().count() <= MAX_TEMPLATE_NAME_LEN
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\']);
+    if valid {
+        Ok(())
+    } else {
+        Err(TemplateError::InvalidName(name.to_string()))
+    }
+}
+
+fn template_file(template_directory: &Path, name: &str) -> PathBuf {
+    template_directory.join(format!("{name}.json"))
+}
+
+/// The templates of a template directory, sorted by name.
+/// Templates that can't be read are left out, and logged.
+pub fn list_templates(template_directory: impl AsRef<Path>) -> Vec<TemplateInfo> {
+    let Ok(entries) = std::fs::read_dir(template_directory.as_ref()) else {
+        return Vec::new();
+    };
+    let mut templates: Vec<TemplateInfo> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?.to_string();
+            match read_template(template_directory.as_ref(), &name) {
+                Ok(save) => Some(TemplateInfo {
+                    name,
+                    team_name: save.team_name.value.clone(),
+                    level: save
+                        .dictionary_of_dictionaries
+                        .value
+                        .run_stats
+                        .get("level")
+                        .copied()
+                        .unwrap_or(0),
+                    players: edit::player_stats(&save)
+                        .into_iter()
+                        .map(|player| player.name)
+                        .collect(),
+                }),
+                Err(err) => {
+                    log::warn!("Skipping template {:?}: {}", path, err);
+                    None
+                }
+            }
+        })
+        .collect();
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    templates
+}
+
+/// Read the save of a template.
+pub fn read_template(
+    template_directory: impl AsRef<Path>,
+    name: &str,
+) -> Result<SaveGame, TemplateError> {
+    validate_template_name(name)?;
+    let data = match std::fs::read(template_file(template_directory.as_ref(), name)) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(TemplateError::NotFound(name.to_string()));
+        }
+        result => result?,
+    };
+    serde_json::from_slice(&data).map_err(|e| TemplateError::Invalid(name.to_string(), e))
+}
+
+/// Keep a save as a template named `name`, without its players if `strip_players` is set,
+/// so that they are added as they join the new saves. Returns the location of the template.
+pub fn capture_template(
+    save: &SaveGame,
+    template_directory: impl AsRef<Path>,
+    name: &str,
+    strip_players: bool,
+) -> Result<PathBuf, TemplateError> {
+    validate_template_name(name)?;
+    let template_directory = template_directory.as_ref();
+    let file = template_file(template_directory, name);
+    if file.exists() {
+        return Err(TemplateError::Exists(name.to_string()));
+    }
+    let mut save = save.clone();
+    if strip_players {
+        for player in edit::player_stats(&save) {
+            edit::remove_player(&mut save, &player.steam_id)?;
+        }
+    }
+    std::fs::create_dir_all(template_directory)?;
+    let data = serde_json::to_vec_pretty(&save)
+        .map_err(|e| TemplateError::Invalid(name.to_string(), e))?;
+    // Written aside then moved in place, so that a template is never left half-written.
+    // The temporary file is hidden and isn't listed meanwhile.
+    let mut temp_file = tempfile::NamedTempFile::new_in(template_directory)?;
+    temp_file.write_all(&data)?;
+    temp_file.persist_noclobber(&file).map_err(|e| {
+        if e.error.kind() == std::io::ErrorKind::AlreadyExists {
+            TemplateError::Exists(name.to_string())
+        } else {
+            e.error.into()
+        }
+    })?;
+    Ok(file)
+}
+
+/// Delete a template.
+pub fn delete_template(
+    template_directory: impl AsRef<Path>,
+    name: &str,
+) -> Result<(), TemplateError> {
+    validate_template_name(name)?;
+    match std::fs::remove_file(template_file(template_directory.as_ref(), name)) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Err(TemplateError::NotFound(name.to_string()))
+        }
+        result => Ok(result?),
+    }
+}
+
+/// How a new save differs from its template.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NewSaveOptions {
+    /// Team name of the new save, the one of the template if None
+    pub team_name: Option<String>,
+    /// Set the upgrades and crown of the players of the template to 0,
+    /// rather than keeping the ones preset in the template
+    pub reset_upgrades: bool,
+}
+
+/// Write a new save bundle named `new_name` (see `save_bundle::new_save_name`) to
+/// `save_directory` from a template. The date of the save is set to the one of its name.
+/// Returns the location of the new save.
+pub fn new_save_from_template(
+    template: &SaveGame,
+    save_directory: impl AsRef<Path>,
+    new_name: &str,
+    options: &NewSaveOptions,
+) -> Result<PathBuf, TemplateError> {
+    save_bundle::validate_save_name(new_name)?;
+    let mut save = template.clone();
+    if let Some(date) = save_bundle::save_name_date(new_name) {
+        save.date_and_time.value = date;
+    }
+    if let Some(team_name) = &options.team_name {
+        edit::set_team_name(&mut save, team_name)?;
+    }
+    if options.reset_upgrades {
+        let players = edit::player_stats(&save)
+            .into_iter()
+            .map(|player| {
+                let stats = edit::PLAYER_STATS
+                    .iter()
+                    .map(|spec| (spec.key.to_string(), 0))
+                    .collect();
+                (player.steam_id, stats)
+            })
+            .filter(|(steam_id, _)| save.player_names.value.contains_key(steam_id))
+            .collect();
+        edit::set_player_stats(&mut save, &players)?;
+    }
+
+    let location = save_directory.as_ref().join(new_name);
+    if location.exists() {
+        return Err(SaveBundleError::SaveExists(new_name.to_string()).into());
+    }
+    // Written in a hidden directory then moved in place, so that the game never sees
+    // a half-written bundle, as `edit::clone_save_bundle` does.
+    let staging = save_directory.as_ref().join(format!(".{new_name}.new"));
+    let result = (|| -> Result<(), TemplateError> {
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging)?;
+        }
+        std::fs::create_dir_all(&staging)?;
+        save_bundle::write_save_file(staging.join(format!("{new_name}.es3")), &save)?;
+        std::fs::rename(&staging, &location)?;
+        Ok(())
+    })();
+    if staging.exists()
+        && let Err(cleanup_err) = std::fs::remove_dir_all(&staging)
+    {
+        log::warn!(cleanup_err:err; "Failed to remove staging directory {:?}", staging);
+    }
+    result.map(|()| location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::save::fixtures;
+    use crate::rsm::save_bundle::SaveBundle;
+    use tempfile::tempdir;
+
+    mod capture_template {
+        use super::*;
+
+        #[test]
+        fn strips_players() {
+            // Arrange
+            let root = tempdir().unwrap();
+            let save = fixtures::save_game();
+
+            // Act
+            let result = capture_template(&save, root.path(), "Tournament", true);
+
+            // Assert
+            assert!(result.is_ok());
+            let template = read_template(root.path(), "Tournament").unwrap();
+            assert!(template.player_names.value.is_empty());
+            assert!(edit::player_stats(&template).is_empty());
+        }
+
+        #[test]
+        fn keeps_players() {
+            // Arrange
+            let root = tempdir().unwrap();
+            let save = fixtures::save_game();
+
+            // Act
+            let result = capture_template(&save, root.path(), "Tournament", false);
+
+            // Assert
+            assert!(result.is_ok());
+            let template = read_template(root.path(), "Tournament").unwrap();
+            assert_eq!(template, save);
+            assert_eq!(std::fs::read_dir(root.path()).unwrap().count(), 1);
+        }
+
+        #[test]
+        fn refuses_existing_name() {
+            // Arrange
+            let root = tempdir().unwrap();
+            let save = fixtures::save_game();
+            capture_template(&save, root.path(), "Tournament", true).unwrap();
+
+            // Act
+            let result = capture_template(&save, root.path(), "Tournament", false);
+
+            // Assert
+            assert!(matches!(result, Err(TemplateError::Exists(_))));
+            let template = read_template(root.path(), "Tournament").unwrap();
+            assert!(template.player_names.value.is_empty());
+        }
+    }
+
+    mod list_templates {
+        use super::*;
+
+        #[test]
+        fn lists_captured_templates() {
+            // Arrange
+            let root = tempdir().unwrap();
+            capture_template(&fixtures::save_game(), root.path(), "Tournament", true).unwrap();
+
+            // Act
+            let templates = list_templates(root.path());
+
+            // Assert
+            assert_eq!(
+                templates,
+                vec![TemplateInfo {
+                    name: "Tournament".to_string(),
+                    team_name: "R.E.P.O.".to_string(),
+                    level: 4,
+                    players: Vec::new(),
+                }]
+            );
+        }
+    }
+
+    mod new_save_from_template {
+        use super::*;
+
+        #[test]
+        fn writes_new_bundle() {
+            // Arrange
+            let root = tempdir().unwrap();
+            let new_name = "REPO_SAVE_2026_01_02_03_04_05";
+            let options = NewSaveOptions {
+                team_name: Some("Team A".to_string()),
+                reset_upgrades: true,
+            };
+
+            // Act
+            let result =
+                new_save_from_template(&fixtures::save_game(), root.path(), new_name, &options);
+
+            // Assert
+            let save_bundle = SaveBundle::new(result.unwrap()).unwrap();
+            assert_eq!(std::fs::read_dir(root.path()).unwrap().count(), 1);
+            assert_eq!(save_bundle.name, new_name);
+            assert_eq!(save_bundle.team_name, "Team A");
+            let save = save_bundle.get_data().unwrap();
+            assert_eq!(save.date_and_time.value, "2026-01-02");
+            assert!(
+                edit::player_stats(&save)
+                    .iter()
+                    .all(|player| player.values.iter().all(|value| *value == 0))
+            );
+        }
+
+        #[test]
+        fn reset_save_passes_the_check() {
+            // Arrange
+            let root = tempdir().unwrap();
+            let options = NewSaveOptions {
+                team_name: None,
+                reset_upgrades: true,
+            };
+
+            // Act
+            let location = new_save_from_template(
+                &fixtures::save_game(),
+                root.path(),
+                "REPO_SAVE_2026_01_02_03_04_05",
+                &options,
+            )
+            .unwrap();
+
+            // Assert
+            assert!(crate::rsm::lint::lint_bundle(location).is_clean());
+        }
+    }
+}
@@ -1,17 +1,160 @@
 use egui::{self, Color32, RichText};
 use serde::{Deserialize, Serialize};
-use std::sync::mpsc::Sender;
+use std::collections::HashMap;
+use std::sync::mpsc::{Receiver, Sender};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use crate::controller::AppEvent;
-use crate::model::AppState;
-use crate::rsm;
+use repo_save_manager::controller::{AppEvent, Notification, NotificationLevel};
+use repo_save_manager::jobs::{JobInfo, JobStatus};
+use repo_save_manager::model::{AppState, SaveCheck, SaveLists, SaveSource, SharedSaveLists};
+use repo_save_manager::repo::save::SaveGame;
+use repo_save_manager::rsm::diff::{PlayerPresence, SaveDiff, ValueChange};
+use repo_save_manager::rsm::edit::{self, ItemEdit, SaveEdits};
+use repo_save_manager::rsm::lint::BundleLint;
+use repo_save_manager::rsm::save_bundle::{self, BundleId, SaveBundle};
+use repo_save_manager::rsm::template::{self, NewSaveOptions, TemplateInfo};
+
+/// How long notifications stay visible as toasts.
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// Maximum number of toasts shown at the same time.
+const MAX_TOASTS: usize = 3;
 
 // Main view struct
 pub struct AppView {
     pub state: Arc<Mutex<AppState>>,
+    /// Read every frame without locking the state
+    save_lists: Arc<SharedSaveLists>,
     event_sender: Sender<AppEvent>,
+    notification_receiver: Receiver<Notification>,
+    /// All notifications received, oldest first
+    notifications: Vec<Notification>,
+    show_notification_history: bool,
     had_focus: bool,
+    /// Changes made in the editor and not saved yet
+    editor_draft: Option<EditorDraft>,
+}
+
+/// Values being edited in the editor, for the save it was made from.
+struct EditorDraft {
+    id: BundleId,
+    /// The save when the draft was made, to start over when the save is written
+    original: Arc<SaveGame>,
+    team_name: String,
+    /// Rename the save folder and file to a new `REPO_SAVE_<now>` name when saving
+    new_identity: bool,
+    run_stats: Vec<(String, i32)>,
+    players: Vec<edit::PlayerStats>,
+    /// Steam IDs of the players to remove
+    removed_players: Vec<String>,
+    /// Game save or backup to copy the upgrades of a player from, with the player's name
+    transplant_source: Option<(BundleId, String)>,
+    /// Steam ID of the player to copy the upgrades to, None for the same player
+    transplant_target: Option<String>,
+    /// Changes to the items, in the order they were made
+    items: Vec<ItemEdit>,
+    /// Name of the item to add, as typed
+    new_item: String,
+    /// The items once the changes to them are saved, None until computed
+    inventory: Option<Arc<[edit::InventoryItem]>>,
+    /// The edits last validated, with the error applying them gives
+    validation: Option<(SaveEdits, Result<(), String>)>,
+}
+
+impl EditorDraft {
+    fn new(id: BundleId, save: &Arc<SaveGame>) -> Self {
+        EditorDraft {
+            id,
+            original: save.clone(),
+            team_name: save.team_name.value.clone(),
+            new_identity: false,
+            run_stats: edit::sorted_run_stats(&save.dictionary_of_dictionaries.value.run_stats),
+            players: edit::player_stats(save),
+            removed_players: Vec::new(),
+            transplant_source: None,
+            transplant_target: None,
+            items: Vec::new(),
+            new_item: String::new(),
+            inventory: None,
+            validation: None,
+        }
+    }
+
+    /// Whether `edits` can be applied to the save, the error otherwise.
+    /// Computed again only when the edits change, it copies the save.
+    fn validate(&mut self, edits: &SaveEdits) -> Result<(), String> {
+        match &self.validation {
+            Some((validated, result)) if validated == edits => result.clone(),
+            _ => {
+                let result = edits
+                    .apply(&mut SaveGame::clone(&self.original))
+                    .map_err(|err| err.to_string());
+                self.validation = Some((edits.clone(), result.clone()));
+                result
+            }
+        }
+    }
+
+    /// Record a change to the items, successive battery changes of an item are merged.
+    fn push_item_edit(&mut self, item_edit: ItemEdit) {
+        if let (Some(ItemEdit::SetBattery(last, _)), ItemEdit::SetBattery(instance, _)) =
+            (self.items.last(), &item_edit)
+            && last == instance
+        {
+            self.items.pop();
+        }
+        self.items.push(item_edit);
+        self.inventory = None;
+    }
+
+    /// The items as they will be once the changes are saved.
+    /// Computed again only when the items change, it copies the save.
+    fn inventory(&mut self) -> Arc<[edit::InventoryItem]> {
+        self.inventory
+            .get_or_insert_with(|| {
+                let mut save = SaveGame::clone(&self.original);
+                // Invalid changes are reported when validating the draft, show the save as it is then.
+                let _ = edit::edit_items(&mut save, &self.items);
+                edit::inventory(&save).into()
+            })
+            .clone()
+    }
+
+    /// The values changed, to be written to the save.
+    fn edits(&self) -> SaveEdits {
+        let original_run_stats = &self.original.dictionary_of_dictionaries.value.run_stats;
+        let original_players = edit::player_stats(&self.original);
+        let mut edits = SaveEdits {
+            team_name: (self.team_name.trim() != self.original.team_name.value)
+                .then(|| self.team_name.clone()),
+            new_name: self.new_identity.then(save_bundle::new_save_name),
+            run_stats: self
+                .run_stats
+                .iter()
+                .filter(|(key, value)| original_run_stats.get(key) != Some(value))
+                .cloned()
+                .collect(),
+            remove_players: self.removed_players.clone(),
+            items: self.items.clone(),
+            ..SaveEdits::default()
+        };
+        for (player, original) in self.players.iter().zip(&original_players) {
+            if self.removed_players.contains(&player.steam_id) {
+                continue;
+            }
+            let stats: HashMap<String, i32> = edit::PLAYER_STATS
+                .iter()
+                .zip(player.values.iter().zip(&original.values))
+                .filter(|(_, (value, original))| value != original)
+                .map(|(spec, (value, _))| (spec.key.to_string(), *value))
+                .collect();
+            if !stats.is_empty() {
+                edits.players.insert(player.steam_id.clone(), stats);
+            }
+        }
+        edits
+    }
 }
 
 #[derive(Clone, PartialEq, Deserialize, Serialize, Debug)]
@@ -21,18 +164,38 @@ pub enum SaveDirType {
 }
 
 impl AppView {
-    pub fn new(state: Arc<Mutex<AppState>>, event_sender: Sender<AppEvent>) -> Self {
+    pub fn new(
+        state: Arc<Mutex<AppState>>,
+        event_sender: Sender<AppEvent>,
+        notification_receiver: Receiver<Notification>,
+    ) -> Self {
+        let save_lists = state.lock().unwrap().save_lists.clone();
         AppView {
             state,
+            save_lists,
             event_sender,
+            notification_receiver,
+            notifications: Vec::new(),
+            show_notification_history: false,
             had_focus: false,
+            editor_draft: None,
         }
     }
 
     pub fn update(&mut self, ctx: &egui::Context) {
         self.handle_focus(ctx);
+        self.notifications
+            .extend(self.notification_receiver.try_iter());
         self.ui_top_panel(ctx);
+        self.ui_status_bar(ctx);
         self.ui_central_panel(ctx);
+        self.ui_toasts(ctx);
+        self.ui_notification_history(ctx);
+        self.ui_setup_wizard(ctx);
+        self.ui_editor(ctx);
+        self.ui_templates(ctx);
+        self.ui_save_check(ctx);
+        self.ui_comparison(ctx);
     }
 
     fn handle_focus(&mut self, ctx: &egui::Context) {
@@ -44,6 +207,16 @@ impl AppView {
     }
 
     fn on_regain_focus(&mut self) {
+        self.request_refresh();
+    }
+
+    /// Open the setup wizard.
+    pub fn start_setup(&self) {
+        self.event_sender.send(AppEvent::StartSetup).unwrap();
+    }
+
+    /// Ask the controller to refresh the saves in the background.
+    pub fn request_refresh(&self) {
         self.event_sender.send(AppEvent::RefreshSaves).unwrap();
     }
 
@@ -51,6 +224,38 @@ impl AppView {
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
+                    if ui
+                        .button("Rebuild save index")
+                        .on_hover_text("Read every save again instead of using cached metadata")
+                        .clicked()
+                    {
+                        let _ = self.event_sender.send(AppEvent::RebuildIndex);
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Templates...")
+                        .on_hover_text("Create new saves from templates")
+                        .clicked()
+                    {
+                        let _ = self.event_sender.send(AppEvent::OpenTemplates);
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Check saves...")
+                        .on_hover_text("Look for suspicious values in the game saves and backups")
+                        .clicked()
+                    {
+                        let _ = self.event_sender.send(AppEvent::CheckSaves);
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Setup wizard...")
+                        .on_hover_text("Find the game save directory and choose a backup directory")
+                        .clicked()
+                    {
+                        self.start_setup();
+                        ui.close_menu();
+                    }
                     if ui.button("Quit").clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                         let _ = self.event_sender.send(AppEvent::Exit);
@@ -63,9 +268,488 @@ impl AppView {
         });
     }
 
+    /// Shows the last notification, and gives access to the notification history.
+    fn ui_status_bar(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                match self.notifications.last() {
+                    Some(notification) => {
+                        ui.label(notification_text(ui, notification))
+                            .on_hover_text(notification.hint.as_deref().unwrap_or_default());
+                    }
+                    None => {
+                        ui.label("Ready");
+                    }
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui
+                        .button(format!("History ({})", self.notifications.len()))
+                        .clicked()
+                    {
+                        self.show_notification_history = !self.show_notification_history;
+                    }
+                });
+            });
+        });
+    }
+
+    /// Shows the most recent notifications in the corner of the window for a few seconds.
+    fn ui_toasts(&self, ctx: &egui::Context) {
+        let recent_notifications: Vec<&Notification> = self
+            .notifications
+            .iter()
+            .rev()
+            .take(MAX_TOASTS)
+            .filter(|n| n.time.elapsed().is_ok_and(|age| age < TOAST_DURATION))
+            .collect();
+        if recent_notifications.is_empty() {
+            return;
+        }
+
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-8.0, -40.0])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                for notification in recent_notifications {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.set_max_width(300.0);
+                        cmp_notification(ui, notification);
+                    });
+                    ui.add_space(4.0);
+                }
+            });
+
+        // Repaint to hide the toasts once they expire
+        ctx.request_repaint_after(Duration::from_millis(500));
+    }
+
+    fn ui_notification_history(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Notifications")
+            .open(&mut self.show_notification_history)
+            .default_size([400.0, 300.0])
+            .show(ctx, |ui| {
+                if self.notifications.is_empty() {
+                    ui.label("Nothing to show yet.");
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for notification in self.notifications.iter().rev() {
+                        let age = notification.time.elapsed().unwrap_or_default();
+                        ui.label(RichText::new(format_age(age)).small().weak());
+                        cmp_notification(ui, notification);
+                        ui.separator();
+                    }
+                });
+            });
+    }
+
+    /// Lists the discovered save directories, to choose the directories of the active source.
+    fn ui_setup_wizard(&self, ctx: &egui::Context) {
+        let Some(setup) = self.state.lock().unwrap().setup.clone() else {
+            return;
+        };
+
+        let modal = egui::Modal::new(egui::Id::new("setup_modal")).show(ctx, |ui| {
+            ui.set_width(500.0);
+            ui.heading("Setup");
+            ui.label("Choose where R.E.P.O. keeps its saves, and where to back them up.");
+            ui.add_space(8.0);
+
+            ui.label(RichText::new("Game save directory").strong());
+            if setup.discovering {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Looking for saves...");
+                });
+            } else if setup.candidates.is_empty() {
+                ui.label("No save directory found, browse to yours.");
+            }
+            for candidate in &setup.candidates {
+                let path = candidate.path.to_string_lossy().to_string();
+                if ui
+                    .radio(setup.save_directory == path, &path)
+                    .on_hover_text(candidate.saves.join("\n"))
+                    .clicked()
+                {
+                    self.event_sender
+                        .send(AppEvent::SetupChooseSaveDirectory(path))
+                        .unwrap();
+                }
+                let saves = match candidate.saves.len() {
+                    0 => "no saves yet".to_string(),
+                    1 => "1 save".to_string(),
+                    n => format!("{n} saves"),
+                };
+                ui.label(RichText::new(format!("{}, {saves}", candidate.reason)).weak());
+            }
+
+            ui.horizontal(|ui| {
+                let mut save_directory = setup.save_directory.clone();
+                if ui.text_edit_singleline(&mut save_directory).changed() {
+                    self.event_sender
+                        .send(AppEvent::SetupChooseSaveDirectory(save_directory))
+                        .unwrap();
+                }
+                if ui.button("Browse...").clicked()
+                    && let Some(path) = rfd::FileDialog::new().pick_folder()
+                {
+                    self.event_sender
+                        .send(AppEvent::SetupChooseSaveDirectory(
+                            path.display().to_string(),
+                        ))
+                        .unwrap();
+                }
+            });
+            match &setup.validation {
+                Some(Ok(saves)) => {
+                    ui.label(
+                        RichText::new(format!("{} saves found", saves.len()))
+                            .color(success_color(ui)),
+                    );
+                }
+                Some(Err(message)) => {
+                    ui.label(RichText::new(message).color(Color32::RED));
+                }
+                None => {}
+            }
+            ui.add_space(8.0);
+
+            ui.label(RichText::new("Backup directory").strong());
+            ui.horizontal(|ui| {
+                let mut backup_directory = setup.backup_directory.clone();
+                if ui.text_edit_singleline(&mut backup_directory).changed() {
+                    self.event_sender
+                        .send(AppEvent::SetupChooseBackupDirectory(backup_directory))
+                        .unwrap();
+                }
+                if ui.button("Browse...").clicked()
+                    && let Some(path) = rfd::FileDialog::new().pick_folder()
+                {
+                    self.event_sender
+                        .send(AppEvent::SetupChooseBackupDirectory(
+                            path.display().to_string(),
+                        ))
+                        .unwrap();
+                }
+            });
+
+            ui.add_space(16.0);
+            egui::Sides::new().show(
+                ui,
+                |_ui| {},
+                |ui| {
+                    let valid = matches!(setup.validation, Some(Ok(_)));
+                    if ui
+                        .add_enabled(valid, egui::Button::new("Finish"))
+                        .on_disabled_hover_text("Choose a directory containing REPO_SAVE_* saves")
+                        .clicked()
+                    {
+                        self.event_sender.send(AppEvent::FinishSetup).unwrap();
+                    }
+                    if ui.button("Skip").clicked() {
+                        self.event_sender.send(AppEvent::CancelSetup).unwrap();
+                    }
+                },
+            );
+        });
+
+        if modal.should_close() {
+            self.event_sender.send(AppEvent::CancelSetup).unwrap();
+        }
+    }
+
+    /// Detail view of the game save opened in the editor, with its run stats and the upgrades
+    /// of its players editable.
+    fn ui_editor(&mut self, ctx: &egui::Context) {
+        let Some(editor) = self.state.lock().unwrap().editor.clone() else {
+            self.editor_draft = None;
+            return;
+        };
+        // Start over when another save is opened, or when the save is written.
+        let draft = match &mut self.editor_draft {
+            Some(draft) if draft.id == editor.id && Arc::ptr_eq(&draft.original, &editor.save) => {
+                draft
+            }
+            draft => draft.insert(EditorDraft::new(editor.id.clone(), &editor.save)),
+        };
+        let event_sender = &self.event_sender;
+        let save_lists = self.save_lists.load();
+
+        let mut open = true;
+        egui::Window::new(format!("Edit {}", editor.name))
+            .id(egui::Id::new("save_editor"))
+            .open(&mut open)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Team:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut draft.team_name)
+                            .char_limit(edit::MAX_TEAM_NAME_LEN),
+                    );
+                });
+                ui.checkbox(&mut draft.new_identity, "Save as a new save")
+                    .on_hover_text(
+                        "Rename the save folder and its .es3 file to REPO_SAVE_<now>, \
+                        so that the game lists it as a new save.",
+                    );
+                ui.add_space(8.0);
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.label(RichText::new("Run stats").strong());
+                    cmp_editor_run_stats(ui, &mut draft.run_stats);
+                    ui.add_space(8.0);
+                    ui.label(RichText::new("Players").strong());
+                    cmp_editor_players(ui, &mut draft.players, &mut draft.removed_players);
+                    cmp_editor_transplant(ui, draft, &save_lists, event_sender);
+                    ui.add_space(8.0);
+                    ui.label(RichText::new("Items").strong());
+                    cmp_editor_items(ui, draft);
+                });
+
+                let edits = draft.edits();
+                // Validate the values the way they will be when saving.
+                let validation = draft.validate(&edits);
+                if let Err(err) = &validation {
+                    ui.label(RichText::new(err).color(Color32::RED));
+                }
+                ui.add_space(8.0);
+                ui.label(
+                    RichText::new(
+                        "A copy of the save is kept in the .snapshots directory of the \
+                        backup directory before it's written.",
+                    )
+                    .weak(),
+                );
+
+                egui::Sides::new().show(
+                    ui,
+                    |_ui| {},
+                    |ui| {
+                        if ui
+                            .add_enabled(
+                                !edits.is_empty() && validation.is_ok(),
+                                egui::Button::new("Save"),
+                            )
+                            .clicked()
+                        {
+                            event_sender
+                                .send(AppEvent::SaveEdits(editor.id.clone(), edits.clone()))
+                                .unwrap();
+                        }
+                        if ui
+                            .add_enabled(!edits.is_empty(), egui::Button::new("Revert"))
+                            .clicked()
+                        {
+                            *draft = EditorDraft::new(editor.id.clone(), &editor.save);
+                        }
+                    },
+                );
+            });
+
+        if !open {
+            self.event_sender.send(AppEvent::CloseEditor).unwrap();
+        }
+    }
+
+    /// The templates, from which new saves are created.
+    fn ui_templates(&self, ctx: &egui::Context) {
+        let Some(templates) = self.state.lock().unwrap().templates.clone() else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new("Templates")
+            .id(egui::Id::new("templates"))
+            .open(&mut open)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                if templates.is_empty() {
+                    ui.label(
+                        "No templates yet, use the \"Template\" button of a save or backup \
+                        to create one.",
+                    );
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for template in &templates {
+                        self.cmp_template(ui, template);
+                        ui.add_space(8.0);
+                    }
+                });
+            });
+
+        if !open {
+            self.event_sender.send(AppEvent::CloseTemplates).unwrap();
+        }
+    }
+
+    fn cmp_template(&self, ui: &mut egui::Ui, template: &TemplateInfo) {
+        egui::Frame::canvas(ui.style()).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.label(RichText::new(&template.name).strong());
+            ui.label(format!(
+                "Team {}, level {}",
+                template.team_name,
+                template.level + 1
+            ));
+            if template.players.is_empty() {
+                ui.label("No players");
+            } else {
+                ui.label(format!("Players: {}", template.players.join(", ")));
+            }
+
+            // The options typed are kept in egui's memory.
+            let id = egui::Id::new(("new_save_draft", &template.name));
+            let (mut team_name, mut reset_upgrades) =
+                ui.data_mut(|data| data.get_temp::<(String, bool)>(id).unwrap_or_default());
+            ui.horizontal(|ui| {
+                ui.label("Team:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut team_name)
+                        .hint_text(&template.team_name)
+                        .char_limit(edit::MAX_TEAM_NAME_LEN),
+                );
+            });
+            if !template.players.is_empty() {
+                ui.checkbox(&mut reset_upgrades, "Reset the upgrades of the players");
+            }
+            ui.horizontal(|ui| {
+                if ui
+                    .button("New save")
+                    .on_hover_text("Create a save named REPO_SAVE_<now> in the game saves")
+                    .clicked()
+                {
+                    let options = NewSaveOptions {
+                        team_name: Some(team_name.trim())
+                            .filter(|team_name| !team_name.is_empty())
+                            .map(str::to_string),
+                        reset_upgrades,
+                    };
+                    self.event_sender
+                        .send(AppEvent::NewSaveFromTemplate(
+                            template.name.clone(),
+                            options,
+                        ))
+                        .unwrap();
+                }
+                if ui
+                    .button(RichText::new("Delete").color(Color32::RED))
+                    .clicked()
+                {
+                    self.event_sender
+                        .send(AppEvent::DeleteTemplate(template.name.clone()))
+                        .unwrap();
+                }
+            });
+            ui.data_mut(|data| data.insert_temp(id, (team_name, reset_upgrades)));
+        });
+    }
+
+    /// The issues found by the last check of the saves, with fixes for the game saves.
+    fn ui_save_check(&self, ctx: &egui::Context) {
+        let save_check = {
+            let state = self.state.lock().unwrap();
+            if !state.show_save_check {
+                return;
+            }
+            state.save_check.clone().unwrap_or_default()
+        };
+
+        let mut open = true;
+        egui::Window::new("Save check")
+            .id(egui::Id::new("save_check"))
+            .open(&mut open)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (title, lints, fixable) in [
+                        ("Game Saves", &save_check.game_saves, true),
+                        ("Backup Saves", &save_check.backups, false),
+                    ] {
+                        ui.label(RichText::new(title).strong());
+                        let suspicious: Vec<&BundleLint> =
+                            lints.iter().filter(|lint| !lint.is_clean()).collect();
+                        if suspicious.is_empty() {
+                            ui.label(format!("No issues found in {} saves", lints.len()));
+                        }
+                        for lint in suspicious {
+                            self.cmp_bundle_lint(ui, lint, fixable);
+                        }
+                        ui.add_space(8.0);
+                    }
+                });
+            });
+
+        if !open {
+            self.event_sender.send(AppEvent::CloseSaveCheck).unwrap();
+        }
+    }
+
+    /// The issues of a bundle, with a button fixing the ones that can be if `fixable` is set.
+    fn cmp_bundle_lint(&self, ui: &mut egui::Ui, lint: &BundleLint, fixable: bool) {
+        egui::Frame::canvas(ui.style()).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.label(RichText::new(&lint.name).strong());
+            if let Some(error) = &lint.error {
+                ui.label(RichText::new(error).color(Color32::RED));
+            }
+            for issue in &lint.issues {
+                if issue.fixable() {
+                    ui.label(issue.to_string());
+                } else {
+                    ui.label(format!("{issue} (fix by hand)"));
+                }
+            }
+            if fixable
+                && lint.fixable()
+                && ui
+                    .button("Fix")
+                    .on_hover_text(
+                        "Fix the issues that can be fixed safely, the original is kept as a snapshot",
+                    )
+                    .clicked()
+            {
+                self.event_sender
+                    .send(AppEvent::FixSave(lint.location.clone()))
+                    .unwrap();
+            }
+        });
+    }
+
+    /// What changes from a save or backup to another.
+    fn ui_comparison(&self, ctx: &egui::Context) {
+        let Some(comparison) = self.state.lock().unwrap().comparison.clone() else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new("Compare")
+            .id(egui::Id::new("comparison"))
+            .open(&mut open)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "{} -> {}",
+                        comparison.before_name, comparison.after_name
+                    ))
+                    .strong(),
+                );
+                ui.add_space(8.0);
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    cmp_save_diff(ui, &comparison.diff);
+                });
+            });
+
+        if !open {
+            self.event_sender.send(AppEvent::CloseComparison).unwrap();
+        }
+    }
+
     fn ui_central_panel(&self, ctx: &egui::Context) {
         egui::CentralPanel::default().show(ctx, |ui| {
             self.cmp_central_file_panel(ui);
+            self.cmp_jobs(ui);
             ui.separator();
             self.cmp_central_sync_panel(ui);
         });
@@ -77,46 +761,65 @@ impl AppView {
         let state_lock = self.state.lock().unwrap();
         let mut save_directory = state_lock.save_directory.clone();
         let mut backup_directory = state_lock.backup_directory.clone();
+        let sources = state_lock.sources.clone();
+        let active_source = state_lock.active_source;
+        let directory_warnings = state_lock.directory_warnings.clone();
         drop(state_lock); // Release the lock before UI interactions
 
+        self.cmp_source_tabs(ui, &sources, active_source);
+        let own_backup_library = sources[active_source].backup_directory.is_some();
+
         ui.horizontal(|ui| {
             ui.label("Game Save Directory");
             ui.text_edit_singleline(&mut save_directory);
-            if ui.button("Browse...").clicked() {
-                if let Some(path) = rfd::FileDialog::new().pick_folder() {
-                    save_directory = path.display().to_string();
-                    self.event_sender
-                        .send(AppEvent::UpdateSaveDirectory(save_directory))
-                        .unwrap();
-                }
+            if ui.button("Browse...").clicked()
+                && let Some(path) = rfd::FileDialog::new().pick_folder()
+            {
+                save_directory = path.display().to_string();
+                self.event_sender
+                    .send(AppEvent::UpdateSaveDirectory(save_directory))
+                    .unwrap();
             }
         });
 
         ui.horizontal(|ui| {
             ui.label("Backup Directory");
             ui.text_edit_singleline(&mut backup_directory);
-            if ui.button("Browse...").clicked() {
-                if let Some(path) = rfd::FileDialog::new().pick_folder() {
-                    backup_directory = path.display().to_string();
-                    self.event_sender
-                        .send(AppEvent::UpdateBackupDirectory(backup_directory))
-                        .unwrap();
-                }
+            if ui.button("Browse...").clicked()
+                && let Some(path) = rfd::FileDialog::new().pick_folder()
+            {
+                backup_directory = path.display().to_string();
+                self.event_sender
+                    .send(AppEvent::UpdateBackupDirectory(backup_directory))
+                    .unwrap();
+            }
+            let mut own = own_backup_library;
+            if ui
+                .checkbox(&mut own, "Own library")
+                .on_hover_text(
+                    "Keep the backups of this source apart, instead of in the library shared \
+                    by the other sources",
+                )
+                .changed()
+            {
+                self.event_sender
+                    .send(AppEvent::SetOwnBackupLibrary(own))
+                    .unwrap();
             }
         });
 
+        // Operations refuse to run until conflicts are fixed, warn before they are attempted.
+        for warning in &directory_warnings {
+            ui.label(RichText::new(format!("⚠ {warning}")).color(Color32::RED))
+                .on_hover_text(warning.hint());
+        }
+
         ui.horizontal(|ui| {
             if ui.button("Refresh Saves").clicked() {
                 self.event_sender.send(AppEvent::RefreshSaves).unwrap();
             }
             if ui
-                .button(
-                    RichText::new("Backup All").color(if ui.style().visuals.dark_mode {
-                        Color32::GREEN
-                    } else {
-                        Color32::DARK_GREEN
-                    }),
-                )
+                .button(RichText::new("Backup All").color(success_color(ui)))
                 .clicked()
             {
                 self.event_sender.send(AppEvent::BackupAll).unwrap();
@@ -124,6 +827,93 @@ impl AppView {
         });
     }
 
+    /// One tab per save source, with the name of the active source editable.
+    fn cmp_source_tabs(&self, ui: &mut egui::Ui, sources: &[SaveSource], active_source: usize) {
+        ui.horizontal(|ui| {
+            for (index, source) in sources.iter().enumerate() {
+                if ui
+                    .selectable_label(index == active_source, &source.name)
+                    .on_hover_text(&source.save_directory)
+                    .clicked()
+                {
+                    self.event_sender
+                        .send(AppEvent::SelectSource(index))
+                        .unwrap();
+                }
+            }
+            if ui
+                .button("+")
+                .on_hover_text("Add a save source, eg. another Proton prefix or Steam account")
+                .clicked()
+            {
+                self.event_sender.send(AppEvent::AddSource).unwrap();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Source Name");
+            // The name being typed is kept by egui, it's applied once the field loses focus.
+            let id = ui.id().with(("source_name", active_source));
+            let mut name = ui
+                .data_mut(|data| data.get_temp::<String>(id))
+                .unwrap_or_else(|| sources[active_source].name.clone());
+            let response = ui.text_edit_singleline(&mut name);
+            if response.changed() {
+                ui.data_mut(|data| data.insert_temp(id, name.clone()));
+            }
+            if response.lost_focus() {
+                ui.data_mut(|data| data.remove::<String>(id));
+                self.event_sender
+                    .send(AppEvent::RenameSource(active_source, name))
+                    .unwrap();
+            }
+            if sources.len() > 1
+                && ui
+                    .button("Remove")
+                    .on_hover_text("Forget this source, its saves and backups are kept on disk")
+                    .clicked()
+            {
+                self.event_sender
+                    .send(AppEvent::RemoveSource(active_source))
+                    .unwrap();
+            }
+        });
+    }
+
+    /// Progress of the queued and running jobs.
+    fn cmp_jobs(&self, ui: &mut egui::Ui) {
+        let jobs: Vec<JobInfo> = self.state.lock().unwrap().jobs.clone();
+        if jobs.is_empty() {
+            return;
+        }
+
+        ui.separator();
+        for job in &jobs {
+            ui.horizontal(|ui| {
+                if ui.button("Cancel").clicked() {
+                    self.event_sender.send(AppEvent::CancelJob(job.id)).unwrap();
+                }
+                let text = match job.status {
+                    JobStatus::Queued => format!("{} (waiting)", job.label),
+                    JobStatus::Running => {
+                        let (files_done, files_total) = job.progress.files();
+                        let (bytes_done, bytes_total) = job.progress.bytes();
+                        format!(
+                            "{}: {files_done}/{files_total} files, {} / {}",
+                            job.label,
+                            format_bytes(bytes_done),
+                            format_bytes(bytes_total)
+                        )
+                    }
+                };
+                ui.add(egui::ProgressBar::new(job.progress.fraction()).text(text));
+            });
+        }
+
+        // Progress is updated without waking the view, poll it while jobs are running.
+        ui.ctx().request_repaint_after(Duration::from_millis(100));
+    }
+
     fn cmp_central_sync_panel(&self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
             ui.set_width(ui.available_width());
@@ -143,10 +933,20 @@ impl AppView {
 
         self.cmp_restore_confirmation_modal(ui);
         self.cmp_delete_confirmation_modal(ui);
+        self.cmp_patch_preview_modal(ui);
     }
 
     fn cmp_save_panel(&self, ui: &mut egui::Ui, save_type: &SaveDirType) {
-        let state_guard = self.state.lock().unwrap();
+        let save_lists = self.save_lists.load();
+        let state_lock = self.state.lock().unwrap();
+        let source_names: Vec<String> = state_lock
+            .sources
+            .iter()
+            .map(|source| source.name.clone())
+            .collect();
+        let active_source = state_lock.active_source;
+        let save_check: Option<Arc<SaveCheck>> = state_lock.save_check.clone();
+        drop(state_lock);
 
         ui.vertical(|ui| {
             ui.set_width(ui.available_width());
@@ -158,13 +958,21 @@ impl AppView {
             ui.add_space(16.0);
 
             let save_bundles = match save_type {
-                SaveDirType::GameSave => state_guard.game_save_bundles.clone(),
-                SaveDirType::BackupSave => state_guard.backup_save_bundles.clone(),
+                SaveDirType::GameSave => &save_lists.game_save_bundles,
+                SaveDirType::BackupSave => &save_lists.backup_save_bundles,
             };
-            drop(state_guard);
 
-            for save_bundle in save_bundles {
-                self.cmp_save_bundle_container(ui, save_type, save_bundle);
+            for save_bundle in save_bundles.iter() {
+                self.cmp_save_bundle_container(
+                    ui,
+                    save_type,
+                    save_bundle,
+                    &source_names,
+                    active_source,
+                    save_check
+                        .as_ref()
+                        .and_then(|save_check| save_check.bundle(&save_bundle.id)),
+                );
                 ui.add_space(8.0);
             }
         });
@@ -174,7 +982,10 @@ impl AppView {
         &self,
         ui: &mut egui::Ui,
         save_type: &SaveDirType,
-        save_bundle: rsm::save_bundle::SaveBundle,
+        save_bundle: &SaveBundle,
+        source_names: &[String],
+        active_source: usize,
+        lint: Option<&BundleLint>,
     ) {
         let response = ui.response();
         let visuals = ui.style().interact(&response);
@@ -183,12 +994,19 @@ impl AppView {
             .fill(visuals.bg_fill.gamma_multiply(0.3))
             .stroke(visuals.bg_stroke)
             .show(ui, |ui| {
-                egui::Grid::new(format!("save_grid_{}_{:?}", save_bundle.name, save_type))
+                egui::Grid::new(format!("save_grid_{}", save_bundle.id))
                     .num_columns(2)
                     .spacing([40.0, 4.0])
                     .striped(true)
                     .show(ui, |ui| {
-                        self.cmp_save_bundle_container_grid_contents(ui, save_type, &save_bundle);
+                        self.cmp_save_bundle_container_grid_contents(
+                            ui,
+                            save_type,
+                            save_bundle,
+                            source_names,
+                            active_source,
+                            lint,
+                        );
                     });
             });
     }
@@ -197,7 +1015,10 @@ impl AppView {
         &self,
         ui: &mut egui::Ui,
         save_type: &SaveDirType,
-        save_bundle: &rsm::save_bundle::SaveBundle,
+        save_bundle: &SaveBundle,
+        source_names: &[String],
+        active_source: usize,
+        lint: Option<&BundleLint>,
     ) {
         ui.label("Name");
         ui.label(&save_bundle.name);
@@ -211,29 +1032,113 @@ impl AppView {
         ui.label(save_bundle.players.join("\n"));
         ui.end_row();
 
-        ui.label("Actions");
-        match save_type {
-            SaveDirType::GameSave => {
-                if ui.button("Backup").clicked() {
+        if let Some(source) = &save_bundle.source {
+            ui.label("Source");
+            ui.label(source);
+            ui.end_row();
+        }
+
+        if let Some(lint) = lint.filter(|lint| !lint.is_clean()) {
+            ui.label("Issues");
+            ui.horizontal(|ui| {
+                let count = lint.issues.len() + usize::from(lint.error.is_some());
+                let issues = lint
+                    .error
+                    .iter()
+                    .cloned()
+                    .chain(lint.issues.iter().map(ToString::to_string))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ui.label(RichText::new(format!("⚠ {count} found")).color(Color32::ORANGE))
+                    .on_hover_text(issues);
+                if matches!(save_type, SaveDirType::GameSave)
+                    && lint.fixable()
+                    && ui.button("Fix").clicked()
+                {
                     self.event_sender
-                        .send(AppEvent::BackupSave(save_bundle.name.clone()))
+                        .send(AppEvent::FixSave(lint.location.clone()))
                         .unwrap();
                 }
+            });
+            ui.end_row();
+        }
+
+        ui.label("Actions");
+        match save_type {
+            SaveDirType::GameSave => {
+                ui.horizontal(|ui| {
+                    if ui.button("Backup").clicked() {
+                        self.event_sender
+                            .send(AppEvent::BackupSave(save_bundle.id.clone()))
+                            .unwrap();
+                    }
+                    if ui
+                        .button("Edit")
+                        .on_hover_text(
+                            "Change the run stats, the upgrades of the players and the items of the save",
+                        )
+                        .clicked()
+                    {
+                        self.event_sender
+                            .send(AppEvent::OpenEditor(save_bundle.id.clone()))
+                            .unwrap();
+                    }
+                    if ui
+                        .button("Patch...")
+                        .on_hover_text(
+                            "Apply a JSON Patch or JSON merge patch file to the save, \
+                            the changes are shown first",
+                        )
+                        .clicked()
+                        && let Some(file) = rfd::FileDialog::new()
+                            .add_filter("JSON", &["json"])
+                            .pick_file()
+                    {
+                        self.event_sender
+                            .send(AppEvent::PreviewPatch(save_bundle.id.clone(), file))
+                            .unwrap();
+                    }
+                    self.cmp_clone_menu(ui, save_bundle);
+                    self.cmp_template_menu(ui, save_bundle);
+                    self.cmp_compare_menu(ui, save_bundle);
+                });
             }
             SaveDirType::BackupSave => {
                 ui.horizontal(|ui| {
                     if ui.button("Restore").clicked() {
                         self.event_sender
-                            .send(AppEvent::RequestRestoreBackup(save_bundle.name.clone()))
+                            .send(AppEvent::RequestRestoreBackup(
+                                save_bundle.id.clone(),
+                                source_names[active_source].clone(),
+                            ))
                             .unwrap();
                     }
+                    if source_names.len() > 1 {
+                        ui.menu_button("Restore to", |ui| {
+                            for source in source_names {
+                                if ui.button(source).clicked() {
+                                    self.event_sender
+                                        .send(AppEvent::RequestRestoreBackup(
+                                            save_bundle.id.clone(),
+                                            source.clone(),
+                                        ))
+                                        .unwrap();
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    }
+
+                    self.cmp_clone_menu(ui, save_bundle);
+                    self.cmp_template_menu(ui, save_bundle);
+                    self.cmp_compare_menu(ui, save_bundle);
 
                     if ui
                         .button(egui::RichText::new("Delete").color(egui::Color32::RED))
                         .clicked()
                     {
                         self.event_sender
-                            .send(AppEvent::RequestDeleteBackup(save_bundle.name.clone()))
+                            .send(AppEvent::RequestDeleteBackup(save_bundle.id.clone()))
                             .unwrap();
                     }
                 });
@@ -241,43 +1146,170 @@ impl AppView {
         }
     }
 
+    /// Compare a save or backup with any other one.
+    fn cmp_compare_menu(&self, ui: &mut egui::Ui, save_bundle: &SaveBundle) {
+        ui.menu_button("Compare", |ui| {
+            ui.label("Show what changes from this save to:");
+            let save_lists = self.save_lists.load();
+            for (title, save_bundles) in [
+                ("Game Saves", &save_lists.game_save_bundles),
+                ("Backup Saves", &save_lists.backup_save_bundles),
+            ] {
+                ui.separator();
+                ui.label(RichText::new(title).strong());
+                for other in save_bundles
+                    .iter()
+                    .filter(|other| other.id != save_bundle.id)
+                {
+                    if ui.button(&other.name).clicked() {
+                        self.event_sender
+                            .send(AppEvent::CompareSaves(
+                                save_bundle.id.clone(),
+                                other.id.clone(),
+                            ))
+                            .unwrap();
+                        ui.close_menu();
+                    }
+                }
+            }
+        });
+    }
+
+    /// Copy a save or backup to the game save directory as a new save, with an optional team name.
+    fn cmp_clone_menu(&self, ui: &mut egui::Ui, save_bundle: &SaveBundle) {
+        ui.menu_button("Clone", |ui| {
+            ui.label("Copy to the game saves as a new save, the original is left untouched.");
+            // The team name typed is kept in egui's memory while the menu is open.
+            let id = egui::Id::new(("clone_team_name", &save_bundle.id));
+            let mut team_name = ui.data_mut(|data| data.get_temp::<String>(id).unwrap_or_default());
+            ui.add(
+                egui::TextEdit::singleline(&mut team_name)
+                    .hint_text(&save_bundle.team_name)
+                    .char_limit(edit::MAX_TEAM_NAME_LEN),
+            );
+            if ui.button("Clone as a new save").clicked() {
+                let team_name = Some(team_name.trim())
+                    .filter(|team_name| !team_name.is_empty())
+                    .map(str::to_string);
+                self.event_sender
+                    .send(AppEvent::CloneSave(save_bundle.id.clone(), team_name))
+                    .unwrap();
+                ui.data_mut(|data| data.remove::<String>(id));
+                ui.close_menu();
+            } else {
+                ui.data_mut(|data| data.insert_temp(id, team_name));
+            }
+        });
+    }
+
+    /// Keep a save or backup as a template, optionally without its players.
+    fn cmp_template_menu(&self, ui: &mut egui::Ui, save_bundle: &SaveBundle) {
+        ui.menu_button("Template", |ui| {
+            ui.label("Keep as a template, to create new saves from it with \"File > Templates\".");
+            let id = egui::Id::new(("template_draft", &save_bundle.id));
+            let (mut name, mut strip_players) =
+                ui.data_mut(|data| data.get_temp::<(String, bool)>(id).unwrap_or_default());
+            ui.add(
+                egui::TextEdit::singleline(&mut name)
+                    .hint_text("Template name")
+                    .char_limit(template::MAX_TEMPLATE_NAME_LEN),
+            );
+            ui.checkbox(&mut strip_players, "Remove the players")
+                .on_hover_text("Players are added to the new saves as they join");
+            let valid = template::validate_template_name(&name).is_ok();
+            if ui
+                .add_enabled(valid, egui::Button::new("Save as template"))
+                .clicked()
+            {
+                self.event_sender
+                    .send(AppEvent::CaptureTemplate(
+                        save_bundle.id.clone(),
+                        name,
+                        strip_players,
+                    ))
+                    .unwrap();
+                ui.data_mut(|data| data.remove::<(String, bool)>(id));
+                ui.close_menu();
+            } else {
+                ui.data_mut(|data| data.insert_temp(id, (name, strip_players)));
+            }
+        });
+    }
+
     fn cmp_restore_confirmation_modal(&self, ui: &mut egui::Ui) {
         let state_guard = self.state.lock().unwrap();
 
-        if let Some(backup_name) = &state_guard.confirm_restore_backup_name {
-            let backup_name = backup_name.clone();
+        if let Some(backup_id) = &state_guard.confirm_restore_backup_id {
+            let backup_id = backup_id.clone();
 
-            let backup_save_bundle = state_guard
-                .backup_save_bundles
-                .iter()
-                .find(|s| &s.name == &backup_name)
-                .cloned();
+            let save_lists = self.save_lists.load();
+            let backup_save_bundle = save_lists.backup(&backup_id);
+            let target_source = state_guard
+                .confirm_restore_source
+                .clone()
+                .unwrap_or_else(|| state_guard.active_source().name.clone());
 
-            let target_save_bundle = state_guard
-                .game_save_bundles
-                .iter()
-                .find(|s| &s.name == &backup_name)
-                .cloned();
+            // The backup is restored to the game save of the same name.
+            // Only the game saves of the active source are loaded.
+            let target_save_bundle = backup_save_bundle
+                .filter(|_| target_source == state_guard.active_source().name)
+                .and_then(|backup| {
+                    save_lists
+                        .game_save_bundles
+                        .iter()
+                        .find(|s| s.name == backup.name)
+                });
+
+            let game_running = state_guard.game_running;
+            let diff = state_guard.confirm_restore_diff.clone();
 
             // Drop the lock before showing the modal
             drop(state_guard);
 
-            if let (Some(backup_save_bundle), Some(target_save_bundle)) =
-                (backup_save_bundle, target_save_bundle)
-            {
+            if let Some(backup_save_bundle) = backup_save_bundle {
                 let modal = egui::Modal::new(egui::Id::new("restore_modal")).show(ui.ctx(), |ui| {
                     ui.set_width(400.0);
                     ui.heading("Warning!");
-                    ui.label(format!(
-                        "Save will be overwritten {}.",
-                        &target_save_bundle.name
-                    ));
-                    ui.label(format!(
-                        "Level: {} ->  {}",
-                        &target_save_bundle.level, &backup_save_bundle.level
-                    ));
-                    ui.label("Do you want to proceed?");
-                    ui.label("This action cannot be undone.");
+                    if let Some(target_save_bundle) = target_save_bundle {
+                        ui.label(format!(
+                            "Save will be overwritten {}.",
+                            &target_save_bundle.name
+                        ));
+                        match &diff {
+                            Some(diff) => {
+                                ui.add_space(8.0);
+                                egui::ScrollArea::vertical()
+                                    .max_height(300.0)
+                                    .show(ui, |ui| cmp_save_diff(ui, diff));
+                                ui.add_space(8.0);
+                            }
+                            None => {
+                                ui.label(format!(
+                                    "Level: {} ->  {}",
+                                    &target_save_bundle.level, &backup_save_bundle.level
+                                ));
+                            }
+                        }
+                        ui.label("Do you want to proceed?");
+                        ui.label("This action cannot be undone.");
+                    } else {
+                        ui.label(format!(
+                            "Save {} of source {} will be overwritten.",
+                            &backup_save_bundle.name, &target_source
+                        ));
+                        if let Some(diff) = &diff {
+                            ui.add_space(8.0);
+                            egui::ScrollArea::vertical()
+                                .max_height(300.0)
+                                .show(ui, |ui| cmp_save_diff(ui, diff));
+                            ui.add_space(8.0);
+                        }
+                        ui.label("Do you want to proceed?");
+                        ui.label("This action cannot be undone.");
+                    }
+                    if game_running {
+                        cmp_game_running_warning(ui);
+                    }
 
                     ui.add_space(32.0);
 
@@ -287,7 +1319,10 @@ impl AppView {
                         |ui| {
                             if ui.button("Yes").clicked() {
                                 self.event_sender
-                                    .send(AppEvent::ConfirmRestoreBackup(backup_name.clone()))
+                                    .send(AppEvent::ConfirmRestoreBackup(
+                                        backup_id.clone(),
+                                        target_source.clone(),
+                                    ))
                                     .unwrap();
                             }
 
@@ -309,17 +1344,85 @@ impl AppView {
         }
     }
 
+    /// The changes a patch makes to a save, to confirm before they are written.
+    fn cmp_patch_preview_modal(&self, ui: &mut egui::Ui) {
+        let Some(preview) = self.state.lock().unwrap().patch_preview.clone() else {
+            return;
+        };
+
+        let modal = egui::Modal::new(egui::Id::new("patch_modal")).show(ui.ctx(), |ui| {
+            ui.set_width(600.0);
+            ui.heading(format!("Patch {}", preview.name));
+            ui.label(format!("With {}", preview.file.display()));
+            ui.add_space(8.0);
+            if preview.changes.is_empty() {
+                ui.label("The patch doesn't change the save.");
+            } else {
+                ui.label(format!("{} changes:", preview.changes.len()));
+                egui::ScrollArea::both().max_height(300.0).show(ui, |ui| {
+                    for change in &preview.changes {
+                        let (text, color) = match (&change.before, &change.after) {
+                            (Some(before), Some(after)) => (
+                                format!("~ {}: {before} -> {after}", change.path),
+                                ui.visuals().warn_fg_color,
+                            ),
+                            (None, Some(after)) => {
+                                (format!("+ {}: {after}", change.path), Color32::GREEN)
+                            }
+                            (before, None) => (
+                                format!(
+                                    "- {}: {}",
+                                    change.path,
+                                    before.as_ref().map(ToString::to_string).unwrap_or_default()
+                                ),
+                                Color32::RED,
+                            ),
+                        };
+                        ui.label(RichText::new(text).monospace().color(color));
+                    }
+                });
+            }
+            ui.add_space(8.0);
+            ui.label(
+                RichText::new(
+                    "A copy of the save is kept in the .snapshots directory of the \
+                    backup directory before it's written.",
+                )
+                .weak(),
+            );
+
+            egui::Sides::new().show(
+                ui,
+                |_ui| {},
+                |ui| {
+                    if ui
+                        .add_enabled(!preview.changes.is_empty(), egui::Button::new("Apply"))
+                        .clicked()
+                    {
+                        self.event_sender.send(AppEvent::ConfirmPatch).unwrap();
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.event_sender.send(AppEvent::CancelPatch).unwrap();
+                    }
+                },
+            );
+        });
+
+        if modal.should_close() {
+            self.event_sender.send(AppEvent::CancelPatch).unwrap();
+        }
+    }
+
     fn cmp_delete_confirmation_modal(&self, ui: &mut egui::Ui) {
         let state_guard = self.state.lock().unwrap();
 
-        if let Some(backup_name) = &state_guard.confirm_backup_deletion_name {
-            let backup_name = backup_name.clone();
+        if let Some(backup_id) = &state_guard.confirm_backup_deletion_id {
+            let backup_id = backup_id.clone();
 
-            let backup_save_bundle = state_guard
-                .backup_save_bundles
-                .iter()
-                .find(|s| &s.name == &backup_name)
-                .cloned();
+            let save_lists = self.save_lists.load();
+            let backup_save_bundle = save_lists.backup(&backup_id);
+
+            let game_running = state_guard.game_running;
 
             // Drop the lock before showing the modal
             drop(state_guard);
@@ -335,6 +1438,9 @@ impl AppView {
                         ));
                         ui.label("Do you want to proceed?");
                         ui.label("This action cannot be undone.");
+                        if game_running {
+                            cmp_game_running_warning(ui);
+                        }
 
                         ui.add_space(32.0);
 
@@ -347,7 +1453,7 @@ impl AppView {
                                     .clicked()
                                 {
                                     self.event_sender
-                                        .send(AppEvent::ConfirmDeleteBackup(backup_name.clone()))
+                                        .send(AppEvent::ConfirmDeleteBackup(backup_id.clone()))
                                         .unwrap();
                                 }
 
@@ -369,3 +1475,438 @@ impl AppView {
         }
     }
 }
+
+/// One row per run stat, known stats first.
+fn cmp_editor_run_stats(ui: &mut egui::Ui, run_stats: &mut [(String, i32)]) {
+    egui::Grid::new("run_stats_grid")
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            for (key, value) in run_stats {
+                let spec = edit::run_stat_spec(key);
+                let label = if spec.key.is_empty() {
+                    key.as_str()
+                } else {
+                    spec.label
+                };
+                ui.label(label).on_hover_text(key.as_str());
+                ui.add(egui::DragValue::new(value).range(spec.min..=spec.max));
+                ui.end_row();
+            }
+        });
+}
+
+/// One row per player, labelled with their name, and one column per upgrade and the crown.
+fn cmp_editor_players(
+    ui: &mut egui::Ui,
+    players: &mut [edit::PlayerStats],
+    removed_players: &mut Vec<String>,
+) {
+    let mut crowned = None;
+    egui::ScrollArea::horizontal().show(ui, |ui| {
+        egui::Grid::new("players_grid")
+            .num_columns(edit::PLAYER_STATS.len() + 2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Player");
+                for spec in edit::PLAYER_STATS {
+                    ui.label(spec.label).on_hover_text(spec.key);
+                }
+                ui.end_row();
+
+                for (index, player) in players.iter_mut().enumerate() {
+                    let removed = removed_players.contains(&player.steam_id);
+                    let mut name = RichText::new(&player.name);
+                    if removed {
+                        name = name.strikethrough().weak();
+                    }
+                    ui.label(name).on_hover_text(&player.steam_id);
+                    for (spec, value) in edit::PLAYER_STATS.iter().zip(&mut player.values) {
+                        if spec.key == edit::CROWN_KEY {
+                            let mut crown = *value == 1;
+                            if ui
+                                .add_enabled(!removed, egui::Checkbox::without_text(&mut crown))
+                                .changed()
+                            {
+                                *value = i32::from(crown);
+                                if crown {
+                                    crowned = Some(index);
+                                }
+                            }
+                        } else {
+                            ui.add_enabled(
+                                !removed,
+                                egui::DragValue::new(value).range(spec.min..=spec.max),
+                            );
+                        }
+                    }
+                    if removed {
+                        if ui.button("Keep").clicked() {
+                            removed_players.retain(|steam_id| *steam_id != player.steam_id);
+                        }
+                    } else if ui
+                        .button("Remove")
+                        .on_hover_text("Remove the player from the save and all its dictionaries")
+                        .clicked()
+                    {
+                        removed_players.push(player.steam_id.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+
+    // Only one player wears the crown.
+    if let Some(crowned) = crowned
+        && let Some(crown) = edit::PLAYER_STATS
+            .iter()
+            .position(|spec| spec.key == edit::CROWN_KEY)
+    {
+        for (index, player) in players.iter_mut().enumerate() {
+            if index != crowned {
+                player.values[crown] = 0;
+            }
+        }
+    }
+}
+
+/// Copy the upgrades of a player of another save or backup to a player of the save edited.
+/// The copy is written right away, so it's only offered when there are no changes to save.
+fn cmp_editor_transplant(
+    ui: &mut egui::Ui,
+    draft: &mut EditorDraft,
+    save_lists: &SaveLists,
+    event_sender: &Sender<AppEvent>,
+) {
+    let bundles: Vec<(&SaveBundle, &str)> = save_lists
+        .game_save_bundles
+        .iter()
+        .map(|bundle| (bundle, "save"))
+        .chain(
+            save_lists
+                .backup_save_bundles
+                .iter()
+                .map(|bundle| (bundle, "backup")),
+        )
+        .collect();
+    let bundle_label = |id: &BundleId| {
+        bundles
+            .iter()
+            .find(|(bundle, _)| bundle.id == *id)
+            .map_or_else(
+                || id.to_string(),
+                |(bundle, kind)| format!("{} ({kind})", bundle.name),
+            )
+    };
+    let source_text = match &draft.transplant_source {
+        Some((id, player)) => format!("{player} of {}", bundle_label(id)),
+        None => "a player of another save".to_string(),
+    };
+    let target_text = match &draft.transplant_target {
+        Some(steam_id) => draft
+            .players
+            .iter()
+            .find(|player| player.steam_id == *steam_id)
+            .map_or(steam_id, |player| &player.name)
+            .clone(),
+        None => "the same player".to_string(),
+    };
+
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Copy the upgrades of");
+        egui::ComboBox::from_id_salt("transplant_source")
+            .selected_text(source_text)
+            .show_ui(ui, |ui| {
+                for (bundle, kind) in &bundles {
+                    for player in &bundle.players {
+                        let source = (bundle.id.clone(), player.clone());
+                        let selected = draft.transplant_source.as_ref() == Some(&source);
+                        if ui
+                            .selectable_label(
+                                selected,
+                                format!("{player} of {} ({kind})", bundle.name),
+                            )
+                            .clicked()
+                        {
+                            draft.transplant_source = Some(source);
+                        }
+                    }
+                }
+            });
+        ui.label("to");
+        let mut target = draft.transplant_target.clone();
+        egui::ComboBox::from_id_salt("transplant_target")
+            .selected_text(target_text)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut target, None, "the same player")
+                    .on_hover_text("Added to the save if they aren't in it");
+                for player in &draft.players {
+                    ui.selectable_value(&mut target, Some(player.steam_id.clone()), &player.name);
+                }
+            });
+        draft.transplant_target = target;
+
+        let has_changes = !draft.edits().is_empty();
+        if ui
+            .add_enabled(
+                draft.transplant_source.is_some() && !has_changes,
+                egui::Button::new("Copy"),
+            )
+            .on_disabled_hover_text("Save or revert the changes first")
+            .clicked()
+            && let Some((source, player)) = draft.transplant_source.clone()
+        {
+            event_sender
+                .send(AppEvent::TransplantPlayer(
+                    source,
+                    player,
+                    draft.id.clone(),
+                    draft.transplant_target.clone(),
+                ))
+                .unwrap();
+        }
+    });
+}
+
+/// The items of the save with their instances, which can be added, removed or recharged.
+fn cmp_editor_items(ui: &mut egui::Ui, draft: &mut EditorDraft) {
+    let inventory = draft.inventory();
+    let mut item_edits = Vec::new();
+
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut draft.new_item)
+                .hint_text("Item Grenade Explosive")
+                .desired_width(200.0),
+        );
+        if ui
+            .add_enabled(!draft.new_item.trim().is_empty(), egui::Button::new("Add"))
+            .clicked()
+        {
+            item_edits.push(ItemEdit::Add(draft.new_item.trim().to_string()));
+            draft.new_item.clear();
+        }
+        let discharged: Vec<&edit::ItemInstance> = inventory
+            .iter()
+            .flat_map(|item| &item.instances)
+            .filter(|instance| instance.battery.is_some_and(|b| b < edit::FULL_BATTERY))
+            .collect();
+        if ui
+            .add_enabled(!discharged.is_empty(), egui::Button::new("Recharge all"))
+            .clicked()
+        {
+            item_edits.extend(
+                discharged
+                    .iter()
+                    .map(|instance| ItemEdit::SetBattery(instance.key.clone(), edit::FULL_BATTERY)),
+            );
+        }
+    });
+
+    egui::Grid::new("items_grid")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for item in inventory.iter() {
+                ui.label(RichText::new(&item.name).strong());
+                ui.label(format!(
+                    "{} owned, {} purchased in total",
+                    item.purchased, item.purchased_total
+                ))
+                .on_hover_text(format!(
+                    "Battery upgrades: {}\nUpgrades purchased: {}",
+                    item.battery_upgrades, item.upgrades_purchased
+                ));
+                if ui.button("Add one").clicked() {
+                    item_edits.push(ItemEdit::Add(item.name.clone()));
+                }
+                ui.end_row();
+
+                for instance in &item.instances {
+                    ui.label(format!("    {}", instance.key));
+                    match instance.battery {
+                        Some(battery) => {
+                            let mut battery = battery;
+                            let response = ui.add(
+                                egui::DragValue::new(&mut battery)
+                                    .range(0..=edit::FULL_BATTERY)
+                                    .suffix("%"),
+                            );
+                            if response.changed() {
+                                item_edits
+                                    .push(ItemEdit::SetBattery(instance.key.clone(), battery));
+                            }
+                        }
+                        None => {
+                            ui.label("");
+                        }
+                    }
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(
+                                instance.battery != Some(edit::FULL_BATTERY),
+                                egui::Button::new("Recharge"),
+                            )
+                            .clicked()
+                        {
+                            item_edits.push(ItemEdit::SetBattery(
+                                instance.key.clone(),
+                                edit::FULL_BATTERY,
+                            ));
+                        }
+                        if ui
+                            .button(RichText::new("Remove").color(Color32::RED))
+                            .clicked()
+                        {
+                            item_edits.push(ItemEdit::Remove(instance.key.clone()));
+                        }
+                    });
+                    ui.end_row();
+                }
+            }
+        });
+
+    for item_edit in item_edits {
+        draft.push_item_edit(item_edit);
+    }
+}
+
+/// What changes from a save to another: team, time played, run stats, players and items.
+fn cmp_save_diff(ui: &mut egui::Ui, diff: &SaveDiff) {
+    if diff.is_empty() {
+        ui.label("No differences");
+        return;
+    }
+    if let Some((before, after)) = &diff.team_name {
+        ui.label(format!("Team: {before} -> {after}"));
+    }
+    if let Some((before, after)) = diff.time_played {
+        ui.label(format!(
+            "Time played: {} -> {}",
+            format_time_played(before),
+            format_time_played(after)
+        ));
+    }
+    if !diff.run_stats.is_empty() {
+        ui.add_space(4.0);
+        ui.label(RichText::new("Run stats").strong());
+        cmp_value_changes(ui, "diff_run_stats", &diff.run_stats);
+    }
+    for player in &diff.players {
+        ui.add_space(4.0);
+        let title = match player.presence {
+            PlayerPresence::Both => player.name.clone(),
+            PlayerPresence::Added => format!("{} (added)", player.name),
+            PlayerPresence::Removed => format!("{} (removed)", player.name),
+        };
+        ui.label(RichText::new(title).strong())
+            .on_hover_text(&player.steam_id);
+        cmp_value_changes(ui, ("diff_player", &player.steam_id), &player.upgrades);
+    }
+    if !diff.items.is_empty() {
+        ui.add_space(4.0);
+        ui.label(RichText::new("Items").strong());
+        egui::Grid::new("diff_items")
+            .num_columns(2)
+            .spacing([40.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                for item in &diff.items {
+                    ui.label(&item.name);
+                    ui.label(format!("{} -> {}", item.before, item.after));
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+/// Values before and after, a dash where a value is missing.
+fn cmp_value_changes(ui: &mut egui::Ui, id: impl std::hash::Hash, changes: &[ValueChange]) {
+    let value = |value: Option<i32>| value.map_or("-".to_string(), |value| value.to_string());
+    egui::Grid::new(id)
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            for change in changes {
+                ui.label(&change.label).on_hover_text(&change.key);
+                ui.label(format!(
+                    "{} -> {}",
+                    value(change.before),
+                    value(change.after)
+                ));
+                ui.end_row();
+            }
+        });
+}
+
+/// Format a time played in seconds, eg. `2 h 05 min`.
+fn format_time_played(seconds: f32) -> String {
+    let minutes = (seconds / 60.0) as u64;
+    format!("{} h {:02} min", minutes / 60, minutes % 60)
+}
+
+/// Warning shown in confirmation popups when the game is running.
+fn cmp_game_running_warning(ui: &mut egui::Ui) {
+    ui.add_space(8.0);
+    ui.label(
+        RichText::new(
+            "R.E.P.O. is running! Close the game before continuing, \
+            otherwise it will overwrite your saves when it exits.",
+        )
+        .color(Color32::RED),
+    );
+}
+
+/// A notification's message, with its hint below if it has one.
+fn cmp_notification(ui: &mut egui::Ui, notification: &Notification) {
+    ui.label(notification_text(ui, notification));
+    if let Some(hint) = &notification.hint {
+        ui.label(hint);
+    }
+}
+
+/// Notification message, colored by level.
+fn notification_text(ui: &egui::Ui, notification: &Notification) -> RichText {
+    let color = match notification.level {
+        NotificationLevel::Success => success_color(ui),
+        NotificationLevel::Error => Color32::RED,
+    };
+    RichText::new(&notification.message).color(color)
+}
+
+/// Green readable on the current theme.
+fn success_color(ui: &egui::Ui) -> Color32 {
+    if ui.style().visuals.dark_mode {
+        Color32::GREEN
+    } else {
+        Color32::DARK_GREEN
+    }
+}
+
+/// Format a size in bytes for humans, eg. `1.5 MB`.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// Human readable time since a notification, eg. `5 min ago`.
+fn format_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    match seconds {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{} min ago", seconds / 60),
+        _ => format!("{} h ago", seconds / 3600),
+    }
+}